@@ -1,17 +1,37 @@
 pub mod msg;
 
 use std::{
+    any::Any,
     cell::RefCell,
+    pin::Pin,
     rc::{Rc, Weak},
+    task::{Context as TaskContext, Poll},
 };
 
+use futures_signals::signal::{Mutable, MutableSignal, Signal, SignalExt};
+use wasm_bindgen::JsCast;
+
 use crate::{
-    context::{AppContext, AppContextRef},
-    dom::TagBuilder,
+    context::{AppContext, AppContextRef, Scope},
+    dom::{SignalView, TagBuilder},
+    web::{window, DomNode},
 };
 
 struct State<C> {
     state: Option<C>,
+    /// The scope the component was built in, including the values it
+    /// provided in `init`.
+    scope: Scope,
+    /// Changed to render the component again.
+    version: Mutable<usize>,
+    /// The number of renders that are still in the document.
+    views: usize,
+    /// The components built by the last render.
+    children: Vec<Rc<dyn Any>>,
+    /// The element of the last render.
+    node: Option<DomNode>,
+    /// Whether [`Component::mounted`] is called for the next render.
+    mount: bool,
 }
 
 pub struct Context<'a, C> {
@@ -25,6 +45,18 @@ impl<'a, C: Component> Context<'a, C> {
             context: AppContext::get_ref(),
         }
     }
+
+    /// Replace `component` with a new one built from `props`, like a
+    /// component that is removed and built again.
+    fn rebuild(self, component: &mut C, props: C::Properties) {
+        let state = self.state;
+        component.destroyed();
+        *component = C::init(props, Context { state });
+        let mut borrow = state.borrow_mut();
+        borrow.scope = AppContext::scope();
+        borrow.children.clear();
+        borrow.mount = true;
+    }
 }
 
 pub struct Handle<C: Component> {
@@ -46,9 +78,10 @@ impl<C: Component> Handle<C> {
     pub fn apply(&self, f: impl FnOnce(&mut C, Context<'_, C>)) {
         if let Some(state) = self.state.upgrade() {
             let mut borrow = state.borrow_mut();
+            let scope = borrow.scope.clone();
             if let Some(data) = borrow.state.as_mut() {
                 self.context.with(|| {
-                    f(data, Context { state: &state });
+                    AppContext::with_scope(scope, || f(data, Context { state: &state }));
                 });
             } else {
                 #[cfg(debug_assertions)]
@@ -65,33 +98,323 @@ impl<C: Component> Handle<C> {
             );
         }
     }
+
+    /// Like [`Self::apply`], but renders the component again if `f` returns
+    /// `true`.
+    ///
+    /// The new render replaces the previous one in place. Child components
+    /// are kept (see [`Component::changed`]), and so is the focus if the
+    /// focused element is still at the same position.
+    pub fn update(&self, f: impl FnOnce(&mut C, Context<'_, C>) -> ShouldRender) {
+        self.context.with(|| {
+            let mut render = false;
+            self.apply(|state, ctx| render = f(state, ctx));
+            if render {
+                self.render();
+            }
+        });
+    }
+
+    /// Render the component again, see [`Self::update`].
+    ///
+    /// Renders are batched, so the component is rendered once after the
+    /// current update. Only components with [`Component::RERENDER`] can
+    /// render again.
+    pub fn render(&self) {
+        if !C::RERENDER {
+            #[cfg(debug_assertions)]
+            tracing::warn!(
+                component=%std::any::type_name::<C>(),
+                "Tried to render a component without RERENDER again"
+            );
+            return;
+        }
+        if let Some(state) = self.state.upgrade() {
+            *state.borrow().version.lock_mut() += 1;
+        }
+    }
 }
 
+/// Whether a component has to be rendered again after an update.
+pub type ShouldRender = bool;
+
 pub trait Component: Sized + 'static {
     type Properties;
 
+    /// Whether the component can render again, see [`Handle::update`].
+    ///
+    /// Off by default: the view of such a component is rendered once and
+    /// only changes through signals.
+    const RERENDER: bool = false;
+
     fn init(props: Self::Properties, ctx: Context<'_, Self>) -> Self;
     fn render(&mut self, ctx: Context<'_, Self>) -> TagBuilder;
 
+    /// Called once the rendered view has been inserted into the document.
+    ///
+    /// See [`TagBuilder::on_mount`].
+    fn mounted(&mut self, _ctx: Context<'_, Self>) {}
+
+    /// Called when the component is removed.
+    fn destroyed(&mut self) {}
+
+    /// Called instead of [`Self::init`] when the parent component renders
+    /// again and builds this component in the same place, before it is
+    /// rendered again.
+    ///
+    /// Child components are matched by their type and order. By default the
+    /// component is destroyed and replaced with a new one, which is mounted
+    /// again. Override this to keep state across renders of the parent.
+    fn changed(&mut self, props: Self::Properties, ctx: Context<'_, Self>) {
+        ctx.rebuild(self, props);
+    }
+
     fn build(props: Self::Properties) -> crate::dom::View {
         build_component::<Self>(props)
     }
 }
 
+/// The child components built by a render, see [`Component::changed`].
+struct Children {
+    /// The children of the previous render that were not reused yet.
+    previous: Vec<Rc<dyn Any>>,
+    current: Vec<Rc<dyn Any>>,
+}
+
+thread_local! {
+    /// The children of the components that are rendering right now, the
+    /// innermost last.
+    static RENDERING: RefCell<Vec<Children>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Collects the children built while it is alive.
+struct Rendering;
+
+impl Rendering {
+    fn start(previous: Vec<Rc<dyn Any>>) -> Self {
+        RENDERING.with(|r| {
+            r.borrow_mut().push(Children {
+                previous,
+                current: Vec::new(),
+            })
+        });
+        Self
+    }
+
+    fn finish(self) -> Vec<Rc<dyn Any>> {
+        RENDERING
+            .with(|r| {
+                r.borrow_mut()
+                    .last_mut()
+                    .map(|c| std::mem::take(&mut c.current))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Take the next unused child of type `C` of the previous render.
+    fn reuse<C: Component>() -> Option<Rc<RefCell<State<C>>>> {
+        RENDERING.with(|r| {
+            let mut rendering = r.borrow_mut();
+            let previous = &mut rendering.last_mut()?.previous;
+            let index = previous
+                .iter()
+                .position(|child| child.is::<RefCell<State<C>>>())?;
+            previous.remove(index).downcast().ok()
+        })
+    }
+
+    fn register(child: Rc<dyn Any>) {
+        RENDERING.with(|r| {
+            if let Some(rendering) = r.borrow_mut().last_mut() {
+                rendering.current.push(child);
+            }
+        });
+    }
+}
+
+impl Drop for Rendering {
+    fn drop(&mut self) {
+        RENDERING.with(|r| r.borrow_mut().pop());
+    }
+}
+
 pub fn build_component<C: Component>(props: C::Properties) -> crate::dom::View {
-    let comp = Rc::new(RefCell::new(State { state: None }));
-    let mut state = C::init(props, Context { state: &comp });
+    if let Some(comp) = Rendering::reuse::<C>() {
+        Rendering::register(comp.clone());
+        // Taken out while running, so `changed` can rebuild the component.
+        let (scope, state) = {
+            let mut borrow = comp.borrow_mut();
+            (borrow.scope.clone(), borrow.state.take())
+        };
+        if let Some(mut state) = state {
+            AppContext::with_scope(scope, || state.changed(props, Context { state: &comp }));
+            comp.borrow_mut().state = Some(state);
+        }
+        return component_view(comp);
+    }
+
+    let comp = Rc::new(RefCell::new(State {
+        state: None,
+        scope: Scope::default(),
+        version: Mutable::new(0),
+        views: 0,
+        children: Vec::new(),
+        node: None,
+        mount: true,
+    }));
+    Rendering::register(comp.clone());
 
-    let mut node = {
+    // Values provided by the component are only visible to its subtree.
+    AppContext::with_scope(AppContext::scope(), || {
+        let state = C::init(props, Context { state: &comp });
         let mut borrow = comp.borrow_mut();
-        let node = state.render(Context { state: &comp });
         borrow.state = Some(state);
+        borrow.scope = AppContext::scope();
+    });
+    component_view(comp)
+}
 
-        node
+/// Render the component, reusing the child components of its last render.
+fn render_component<C: Component>(comp: &Rc<RefCell<State<C>>>) -> Option<TagBuilder> {
+    let (scope, previous) = {
+        let mut borrow = comp.borrow_mut();
+        (borrow.scope.clone(), std::mem::take(&mut borrow.children))
     };
+    let rendering = Rendering::start(previous);
+    let node = AppContext::with_scope(scope, || {
+        let mut borrow = comp.borrow_mut();
+        let state = borrow.state.as_mut()?;
+        Some(state.render(Context { state: comp }))
+    });
+    let mut node = node?;
+    comp.borrow_mut().children = rendering.finish();
 
+    // The component lives as long as one of its renders.
+    comp.borrow_mut().views += 1;
+    comp.borrow_mut().node = Some(node.dom_node().clone());
+    let removed = comp.clone();
     node.add_after_remove(move || {
-        std::mem::drop(comp);
+        let mut borrow = removed.borrow_mut();
+        borrow.views -= 1;
+        if borrow.views > 0 {
+            return;
+        }
+        let state = borrow.state.take();
+        drop(borrow);
+        if let Some(mut state) = state {
+            state.destroyed();
+        }
+    });
+    Some(node)
+}
+
+/// The view of a component, which is replaced when it renders again if the
+/// component supports it.
+fn component_view<C: Component>(comp: Rc<RefCell<State<C>>>) -> crate::dom::View {
+    let rendered = comp.borrow().version.get();
+    let Some(mut node) = render_component(&comp) else {
+        return crate::dom::View::Empty;
+    };
+
+    if std::mem::take(&mut comp.borrow_mut().mount) {
+        let mounted = Rc::downgrade(&comp);
+        node.add_on_mount(move || {
+            let Some(comp) = mounted.upgrade() else {
+                return;
+            };
+            // Taken out while running, like the state during `render`.
+            let state = comp.borrow_mut().state.take();
+            if let Some(mut state) = state {
+                state.mounted(Context { state: &comp });
+                comp.borrow_mut().state = Some(state);
+            }
+        });
+    }
+
+    if !C::RERENDER {
+        return node.into();
+    }
+
+    let versions = NewVersions {
+        signal: comp.borrow().version.signal(),
+        rendered,
+    };
+    let renders = versions.map(move |_| {
+        let focus = comp.borrow().node.as_ref().and_then(focus_path);
+        let Some(mut node) = render_component(&comp) else {
+            return crate::dom::View::Empty;
+        };
+        if let Some(path) = focus {
+            let root = node.dom_node().clone();
+            node.add_on_mount(move || restore_focus(&root, &path));
+        }
+        node.into()
     });
-    node.into()
+    crate::dom::View::Signal(SignalView::with_initial(node.into(), renders))
+}
+
+/// The versions of a component that were not rendered yet.
+struct NewVersions {
+    signal: MutableSignal<usize>,
+    rendered: usize,
+}
+
+impl Signal for NewVersions {
+    type Item = usize;
+
+    fn poll_change(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<usize>> {
+        loop {
+            match Pin::new(&mut self.signal).poll_change(cx) {
+                Poll::Ready(Some(version)) if version == self.rendered => continue,
+                Poll::Ready(Some(version)) => {
+                    self.rendered = version;
+                    return Poll::Ready(Some(version));
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+/// The child indices from `root` to the focused element, if it is inside of
+/// `root`.
+fn focus_path(root: &DomNode) -> Option<Vec<usize>> {
+    let root = root.as_web()?;
+    let mut node: web_sys::Node = window().document()?.active_element()?.into();
+    if !root.contains(Some(&node)) {
+        return None;
+    }
+    let mut path = Vec::new();
+    while &node != root {
+        let mut index = 0;
+        let mut sibling = node.previous_sibling();
+        while let Some(current) = sibling {
+            index += 1;
+            sibling = current.previous_sibling();
+        }
+        path.push(index);
+        node = node.parent_node()?;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Focus the element at `path` below `root`, see [`focus_path`].
+fn restore_focus(root: &DomNode, path: &[usize]) {
+    let Some(mut node) = root.as_web().cloned() else {
+        return;
+    };
+    for &index in path {
+        let mut child = node.first_child();
+        for _ in 0..index {
+            child = child.and_then(|c| c.next_sibling());
+        }
+        let Some(child) = child else {
+            return;
+        };
+        node = child;
+    }
+    if let Some(elem) = node.dyn_ref::<web_sys::HtmlElement>() {
+        elem.focus().ok();
+    }
 }
@@ -3,20 +3,32 @@ use crate::{
     effect::{spawn_guarded, EffectGuard},
 };
 
-use super::{Component, Context};
+use super::{Component, Context, ShouldRender};
 
 pub trait MsgComponent: Sized + 'static {
     type Properties;
     type Msg;
 
+    /// See [`Component::RERENDER`].
+    const RERENDER: bool = false;
+
     fn init(props: Self::Properties, ctx: Context<Self>) -> Self;
-    fn update(&mut self, msg: Self::Msg, ctx: Context<Self>);
+    /// Handle a message, returning whether the component has to be rendered
+    /// again.
+    fn update(&mut self, msg: Self::Msg, ctx: Context<Self>) -> ShouldRender;
     fn render(&mut self, ctx: Context<Self>) -> TagBuilder;
+
+    /// See [`Component::changed`].
+    fn changed(&mut self, props: Self::Properties, ctx: Context<Self>) {
+        ctx.rebuild(self, props);
+    }
 }
 
 impl<C: MsgComponent> Component for C {
     type Properties = <Self as MsgComponent>::Properties;
 
+    const RERENDER: bool = <Self as MsgComponent>::RERENDER;
+
     fn init(props: Self::Properties, ctx: Context<'_, Self>) -> Self {
         MsgComponent::init(props, ctx)
     }
@@ -24,6 +36,10 @@ impl<C: MsgComponent> Component for C {
     fn render(&mut self, ctx: Context<'_, Self>) -> TagBuilder {
         MsgComponent::render(self, ctx)
     }
+
+    fn changed(&mut self, props: Self::Properties, ctx: Context<'_, Self>) {
+        MsgComponent::changed(self, props, ctx)
+    }
 }
 
 impl<C: MsgComponent> Context<'_, C> {
@@ -93,9 +109,7 @@ impl<C: MsgComponent> Context<'_, C> {
 
 impl<C: MsgComponent> super::Handle<C> {
     pub fn send(&self, msg: C::Msg) {
-        self.apply(move |state, ctx| {
-            state.update(msg, ctx);
-        })
+        self.update(move |state, ctx| state.update(msg, ctx))
     }
 
     pub fn callback(&self, f: impl Fn() -> C::Msg) -> impl Fn() {
@@ -1,11 +1,121 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
 use futures::{future::LocalFutureObj, task::LocalSpawn};
-use wasm_bindgen::{prelude::Closure, JsCast};
+use futures_signals::signal::Mutable;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
 
 use crate::{
-    dom::{AbortGuard, Ev},
-    effect::{IntervalGuard, TimeoutGuard},
+    dom::{AbortGuard, Ev, EventOptions, SuspenseState},
+    effect::{AnimationFrameGuard, IntervalGuard, TimeoutGuard},
+    error::{catch_and_report, Boundary},
+    web::{
+        backend::{Backend, EventListener, EventTarget, MemoryBackend, WebBackend},
+        hydration::Hydration,
+        DomNode,
+    },
 };
 
+type MountSlot = RefCell<Option<Box<dyn FnOnce()>>>;
+
+/// A callback that runs once its node is inserted into the document.
+///
+/// Owned by the node, so callbacks of removed nodes are dropped.
+pub(crate) type MountCallback = Rc<MountSlot>;
+
+struct PendingMount {
+    node: DomNode,
+    callback: Weak<MountSlot>,
+    scope: Scope,
+}
+
+/// State that views and handlers inherit from the view they are created in.
+#[derive(Clone, Default)]
+pub(crate) struct Scope {
+    pub(crate) error_boundary: Option<Rc<Boundary>>,
+    /// The closest [`crate::dom::Suspense`].
+    pub(crate) suspense: Option<Rc<SuspenseState>>,
+    /// The path of the nested route, for the [`crate::router::Switch`]
+    /// rendered by the layout of the closest parent route.
+    pub(crate) nested_route: Option<Mutable<String>>,
+    /// The value provided last with [`provide_context`].
+    provided: Option<Rc<Provided>>,
+}
+
+/// A value provided with [`provide_context`], linked to the values that
+/// were provided before it.
+struct Provided {
+    value: Rc<dyn Any>,
+    parent: Option<Rc<Provided>>,
+}
+
+/// Provide `value` to the views and handlers that are created afterwards
+/// in the current scope.
+///
+/// The scope is the current component, signal render closure or
+/// [`with_context`] closure, so values are only visible in the subtree
+/// they were provided for, including signal views and event handlers
+/// created in it. Providing a value of the same type again shadows it
+/// for the views created afterwards.
+///
+/// ```
+/// use brass::{
+///     context::{provide_context, use_context, with_context},
+///     dom::builder::{div, p},
+/// };
+///
+/// #[derive(Clone)]
+/// struct Theme(&'static str);
+///
+/// fn themed() -> brass::dom::TagBuilder {
+///     let Theme(theme) = use_context::<Theme>().unwrap_or(Theme("light"));
+///     p().text(theme)
+/// }
+///
+/// let (_app, root) = brass::launch_memory(|| {
+///     provide_context(Theme("dark"));
+///     div()
+///         .and(with_context(Theme("blue"), themed))
+///         .and(themed())
+/// });
+/// assert_eq!(root.inner_html(), "<div><p>blue</p><p>dark</p></div>");
+/// ```
+///
+/// # Panics
+///
+/// If no app is active.
+pub fn provide_context<T: 'static>(value: T) {
+    let scope = &mut AppContext::get_mut().scope;
+    scope.provided = Some(Rc::new(Provided {
+        value: Rc::new(value),
+        parent: scope.provided.take(),
+    }));
+}
+
+/// The value of type `T` that was provided last for the current scope, see
+/// [`provide_context`].
+pub fn use_context<T: Clone + 'static>() -> Option<T> {
+    let mut provided = AppContext::scope().provided;
+    while let Some(current) = provided {
+        if let Some(value) = current.value.downcast_ref::<T>() {
+            return Some(value.clone());
+        }
+        provided = current.parent.clone();
+    }
+    None
+}
+
+/// Run `f` with `value` provided only to the views it creates.
+pub fn with_context<T: 'static, R>(value: T, f: impl FnOnce() -> R) -> R {
+    AppContext::with_scope(AppContext::scope(), || {
+        provide_context(value);
+        f()
+    })
+}
+
 /// The "global" context for an app.
 pub(crate) struct AppContext {
     /// All event types that have handlers.
@@ -14,21 +124,83 @@ pub(crate) struct AppContext {
     event_freelist: Vec<EventHandlerId>,
     executor: futures::executor::LocalPool,
     spawner: futures::executor::LocalSpawner,
+    /// Creates and manipulates the nodes of the app.
+    backend: &'static dyn Backend,
+    /// Set while adopting existing markup, see [`crate::hydrate`].
+    hydration: Option<Hydration>,
+    /// Mount callbacks of nodes that are not in the document yet.
+    pending_mounts: Vec<PendingMount>,
+    /// Set if bubbling events are delegated to the root.
+    delegation: Option<Delegation>,
+    /// The location of the router, created on first use.
+    location: Option<Rc<crate::router::Location>>,
+    /// The scope of the views and handlers that are being created.
+    scope: Scope,
 }
 
-static mut ACTIVE_CONTEXT: Option<&mut AppContext> = None;
+thread_local! {
+    /// The app that is running on this thread, see [`AppContext::enter`].
+    ///
+    /// Per thread, so that apps in tests running in parallel don't replace
+    /// each other.
+    static ACTIVE_CONTEXT: Cell<Option<*mut AppContext>> = const { Cell::new(None) };
+}
 
 impl AppContext {
     pub fn new() -> Box<Self> {
+        Self::with_backend(&WebBackend)
+    }
+
+    /// Create a context that renders into an in-memory document.
+    ///
+    /// Timers never fire in such a context.
+    pub fn new_memory() -> Box<Self> {
+        Self::with_backend(&MemoryBackend)
+    }
+
+    pub fn with_backend(backend: &'static dyn Backend) -> Box<Self> {
+        crate::error::install_panic_hook();
         let executor = futures::executor::LocalPool::new();
         Box::new(Self {
             active_events: Vec::new(),
             event_freelist: Vec::new(),
             spawner: executor.spawner(),
             executor,
+            backend,
+            hydration: None,
+            pending_mounts: Vec::new(),
+            delegation: None,
+            location: None,
+            scope: Scope::default(),
         })
     }
 
+    pub(crate) fn start_hydration(&mut self, root: web_sys::Node) {
+        self.hydration = Some(Hydration::new(root));
+    }
+
+    pub(crate) fn finish_hydration(&mut self) -> Result<(), String> {
+        self.hydration.take().map_or(Ok(()), Hydration::finish)
+    }
+
+    /// The hydration state of the active app, if it is hydrating.
+    pub(crate) fn hydration() -> Option<&'static mut Hydration> {
+        Self::active().and_then(|ctx| ctx.hydration.as_mut())
+    }
+
+    /// The backend of the active app.
+    ///
+    /// Falls back to the browser DOM on wasm targets and to an in-memory
+    /// document everywhere else if no app is active, which allows building
+    /// views outside of an app.
+    pub(crate) fn backend() -> &'static dyn Backend {
+        match Self::active() {
+            Some(ctx) => ctx.backend,
+            None if cfg!(all(target_arch = "wasm32", target_os = "unknown")) => &WebBackend,
+            None => &MemoryBackend,
+        }
+    }
+
     pub(crate) fn leak_ref(mut self: Box<Self>) -> AppContextRef {
         let ptr = unsafe { &mut *(self.as_mut() as *mut Self) };
         let r = AppContextRef(ptr);
@@ -45,11 +217,8 @@ impl AppContext {
     where
         F: FnOnce() -> O,
     {
-        self.enter();
-        let out = f();
-        Self::leave();
-
-        out
+        let _entered = self.enter();
+        f()
     }
 
     // fn with_ref<O, F>(&mut self, f: F) -> O
@@ -62,93 +231,166 @@ impl AppContext {
     //     out
     // }
 
-    fn enter(&mut self) {
-        unsafe {
-            ACTIVE_CONTEXT = Some(&mut *(self as *mut Self));
-        }
+    /// Make this the active context until the returned guard is dropped.
+    ///
+    /// The previously active context is restored afterwards, also when
+    /// unwinding from a panic.
+    fn enter(&mut self) -> Entered {
+        let previous = ACTIVE_CONTEXT.with(|active| active.replace(Some(self as *mut Self)));
+        Entered { previous }
     }
 
-    fn leave() {
-        unsafe {
-            ACTIVE_CONTEXT = None;
-        }
+    /// Leave the active app without restoring the previous one, after a
+    /// panic that aborts.
+    pub(crate) fn deactivate() {
+        ACTIVE_CONTEXT.with(|active| active.set(None));
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_active() -> bool {
+        Self::active().is_some()
+    }
+
+    /// The current scope of the active app, or an empty scope if no app is
+    /// active.
+    pub(crate) fn scope() -> Scope {
+        Self::active()
+            .map(|ctx| ctx.scope.clone())
+            .unwrap_or_default()
+    }
+
+    /// Run `f` with `scope` as the scope of the views and handlers it
+    /// creates.
+    pub(crate) fn with_scope<R>(scope: Scope, f: impl FnOnce() -> R) -> R {
+        let previous = std::mem::replace(&mut Self::get_mut().scope, scope);
+        let out = f();
+        Self::get_mut().scope = previous;
+        out
+    }
+
+    /// The active app of this thread.
+    fn active() -> Option<&'static mut Self> {
+        // SAFETY: the app is only active while it is entered, and it is only
+        // accessed from this thread.
+        ACTIVE_CONTEXT
+            .with(Cell::get)
+            .map(|ctx| unsafe { &mut *ctx })
     }
 
     fn get_mut() -> &'static mut Self {
-        unsafe {
-            ACTIVE_CONTEXT
-                .as_mut()
-                .expect("tried to acquire AppContext, but no app is active")
-        }
+        Self::active().expect("tried to acquire AppContext, but no app is active")
     }
 
     fn invoke_event_handler(&mut self, id: EventHandlerId, event: web_sys::Event) {
-        self.enter();
-        if let Some(handler) = self
+        let _entered = self.enter();
+        let backend = self.backend;
+        if let Some((handler, options, scope)) = self
             .active_events
             .get_mut(id.as_usize())
-            .and_then(|x| x.handler.as_mut())
+            .and_then(|x| Some((x.handler.as_mut()?, x.options, x.scope.clone())))
         {
-            (handler)(event);
+            backend.apply_event_options(&event, options);
+            Self::with_scope(scope, || catch_and_report(|| (handler)(event)));
         } else {
             tracing::error!("invoked event handler with invalid id");
             return;
         }
         self.process_futures();
-        AppContext::leave();
     }
 
     pub fn create_event_listener<H>(
         event: Ev,
+        options: EventOptions,
         callback: H,
-        target: web_sys::EventTarget,
+        target: EventTarget,
     ) -> EventHandlerRef
     where
         H: FnMut(web_sys::Event) + 'static,
     {
-        let inner = Self::get_mut();
-
-        let callback: Box<dyn FnMut(web_sys::Event)> = Box::new(callback);
+        Self::add_event_handler(event, options, Box::new(callback), target, false)
+    }
 
-        let handler = if let Some(h) = inner
-            .event_freelist
-            .pop()
-            .and_then(|x| inner.active_events.get_mut(x.0))
-        {
-            h.handler = Some(callback);
-            h.target = target;
-            h.ty = event;
-            h
-        } else {
-            let id = EventHandlerId(inner.active_events.len());
-
-            let id2 = id.clone();
-            let inner2 = Self::get_mut();
-            let boxed = Box::new(move |event: web_sys::Event| {
-                inner2.invoke_event_handler(id2, event);
-            }) as Box<dyn FnMut(web_sys::Event)>;
-            let closure = wasm_bindgen::closure::Closure::wrap(boxed);
-
-            let h = EventHandler {
-                id,
-                closure,
-                handler: Some(callback),
-                target,
-                ty: event,
-            };
+    /// Like [`Self::create_event_listener`], but delegates bubbling events to
+    /// the app root if delegation is enabled.
+    ///
+    /// `target` must be an element of the app.
+    ///
+    /// Handlers with listener flags are never delegated, see
+    /// [`EventOptions`].
+    pub(crate) fn create_element_listener<H>(
+        event: Ev,
+        options: EventOptions,
+        callback: H,
+        target: EventTarget,
+    ) -> EventHandlerRef
+    where
+        H: FnMut(web_sys::Event) + 'static,
+    {
+        Self::add_event_handler(event, options, Box::new(callback), target, true)
+    }
 
-            inner.active_events.push(h);
+    fn add_event_handler(
+        event: Ev,
+        options: EventOptions,
+        callback: Box<dyn FnMut(web_sys::Event)>,
+        target: EventTarget,
+        allow_delegation: bool,
+    ) -> EventHandlerRef {
+        let inner = Self::get_mut();
 
-            inner.active_events.last_mut().unwrap()
+        let delegated = allow_delegation
+            && inner.delegation.is_some()
+            && event.bubbles()
+            && !options.has_listener_flags()
+            && matches!(target, EventTarget::Web(_));
+
+        let id = match inner.event_freelist.pop() {
+            Some(id) => {
+                let h = &mut inner.active_events[id.as_usize()];
+                h.handler = Some(callback);
+                h.target = target;
+                h.ty = event;
+                h.options = options;
+                h.scope = inner.scope.clone();
+                id
+            }
+            None => {
+                let id = EventHandlerId(inner.active_events.len());
+                inner.active_events.push(EventHandler {
+                    id,
+                    listener: None,
+                    handler: Some(callback),
+                    target,
+                    ty: event,
+                    options,
+                    delegated,
+                    scope: inner.scope.clone(),
+                });
+                id
+            }
         };
 
-        crate::web::add_event_lister(
-            &handler.target,
-            handler.ty,
-            handler.closure.as_ref().unchecked_ref(),
-        );
+        let handler = &mut inner.active_events[id.as_usize()];
+        handler.delegated = delegated;
+        if delegated {
+            if let EventTarget::Web(elem) = &handler.target {
+                crate::web::delegate_add(elem, event, id.as_u32());
+            }
+            inner.delegation.as_mut().unwrap().listen(event);
+        } else {
+            let listener = handler.listener.get_or_insert_with(|| {
+                let inner2 = Self::get_mut();
+                let boxed = Box::new(move |event: web_sys::Event| {
+                    inner2.invoke_event_handler(id, event);
+                }) as Box<dyn FnMut(web_sys::Event)>;
+                inner.backend.create_event_listener(boxed)
+            });
+            inner
+                .backend
+                .add_event_listener(&handler.target, handler.ty, listener, options);
+        }
 
-        EventHandlerRef(handler.id.clone())
+        EventHandlerRef(id)
     }
 
     fn return_event_handler(id: EventHandlerId) {
@@ -161,12 +403,7 @@ impl AppContext {
             let ev = inner.active_events.pop().unwrap();
             debug_assert_eq!(ev.id.as_usize(), index);
 
-            ev.target
-                .remove_event_listener_with_callback(
-                    ev.ty.as_str(),
-                    ev.closure.as_ref().unchecked_ref(),
-                )
-                .ok();
+            ev.detach(inner.backend);
         } else {
             let handler = inner
                 .active_events
@@ -176,14 +413,81 @@ impl AppContext {
             handler.handler.take();
             inner.event_freelist.push(id);
 
-            handler
-                .target
-                .remove_event_listener_with_callback(
-                    handler.ty.as_str(),
-                    handler.closure.as_ref().unchecked_ref(),
-                )
-                .ok();
+            handler.detach(inner.backend);
+        }
+    }
+
+    /// Handle bubbling events of elements with one listener per event type
+    /// on `root` instead of one listener per handler.
+    pub(crate) fn enable_delegation(&mut self, root: web_sys::EventTarget) {
+        self.delegation = Some(Delegation {
+            roots: vec![root],
+            events: Vec::new(),
+            listeners: Vec::new(),
+        });
+    }
+
+    /// The router location of the active app.
+    pub(crate) fn location() -> Option<Rc<crate::router::Location>> {
+        Self::get_mut().location.clone()
+    }
+
+    pub(crate) fn set_location(location: Rc<crate::router::Location>) {
+        Self::get_mut().location = Some(location);
+    }
+
+    /// Also delegate events of elements inside `root`, if delegation is
+    /// enabled.
+    ///
+    /// Used for content that is rendered outside of the app root, like
+    /// portals.
+    pub(crate) fn add_delegation_root(root: &web_sys::EventTarget) {
+        if let Some(delegation) = Self::get_mut().delegation.as_mut() {
+            delegation.add_root(root);
+        }
+    }
+
+    /// Invoke the delegated handlers along the path of an event, from the
+    /// target up to `root`.
+    ///
+    /// Events are handled by the closest root only, so handlers aren't
+    /// invoked twice when roots are nested.
+    fn dispatch_delegated(&mut self, root: &JsValue, event: Ev, dom_event: web_sys::Event) {
+        let Some(delegation) = self.delegation.as_ref() else {
+            return;
+        };
+        let path = dom_event.composed_path();
+        let closest_root = path
+            .iter()
+            .find(|target| delegation.roots.iter().any(|r| JsValue::from(r) == *target));
+        if closest_root.as_ref() != Some(root) {
+            return;
+        }
+
+        for target in path.iter() {
+            if target == *root {
+                break;
+            }
+            let ids = crate::web::delegated_ids(&target, event);
+            if !ids.is_empty() {
+                crate::web::set_current_target(&dom_event, &target);
+                for id in ids {
+                    let id = EventHandlerId(id as usize);
+                    // Earlier handlers can remove later ones.
+                    let is_active = self
+                        .active_events
+                        .get(id.as_usize())
+                        .is_some_and(|h| h.handler.is_some() && h.delegated);
+                    if is_active {
+                        self.invoke_event_handler(id, dom_event.clone());
+                    }
+                }
+            }
+            if dom_event.cancel_bubble() {
+                break;
+            }
         }
+        crate::web::reset_current_target(&dom_event);
     }
 
     pub fn create_timeout(
@@ -191,12 +495,18 @@ impl AppContext {
         f: impl FnOnce() + 'static,
     ) -> TimeoutGuard {
         let inner = Self::get_mut();
+        if !inner.backend.has_event_loop() {
+            return TimeoutGuard {
+                _closure: None,
+                id: 0,
+            };
+        }
+
         // TODO: use callback cache
         let closure = Closure::once(move || {
-            inner.enter();
+            let _entered = inner.enter();
             f();
             inner.process_futures();
-            AppContext::leave();
         });
 
         let id = crate::web::window()
@@ -207,7 +517,32 @@ impl AppContext {
             .unwrap();
 
         TimeoutGuard {
-            _closure: closure,
+            _closure: Some(closure),
+            id,
+        }
+    }
+
+    pub fn create_animation_frame(f: impl FnOnce() + 'static) -> AnimationFrameGuard {
+        let inner = Self::get_mut();
+        if !inner.backend.has_event_loop() {
+            return AnimationFrameGuard {
+                _closure: None,
+                id: 0,
+            };
+        }
+
+        let closure = Closure::once(move || {
+            let _entered = inner.enter();
+            f();
+            inner.process_futures();
+        });
+
+        let id = crate::web::window()
+            .request_animation_frame(closure.as_ref().unchecked_ref())
+            .unwrap();
+
+        AnimationFrameGuard {
+            _closure: Some(closure),
             id,
         }
     }
@@ -217,13 +552,18 @@ impl AppContext {
         mut f: impl FnMut() + 'static,
     ) -> IntervalGuard {
         let inner = Self::get_mut();
+        if !inner.backend.has_event_loop() {
+            return IntervalGuard {
+                _closure: None,
+                id: 0,
+            };
+        }
 
         // TODO: use callback cache
         let closure = Closure::wrap(Box::new(move || {
-            inner.enter();
+            let _entered = inner.enter();
             f();
             inner.process_futures();
-            AppContext::leave();
         }) as Box<dyn FnMut()>);
 
         let id = crate::web::window()
@@ -234,7 +574,7 @@ impl AppContext {
             .unwrap();
 
         IntervalGuard {
-            _closure: closure,
+            _closure: Some(closure),
             id,
         }
     }
@@ -259,7 +599,11 @@ impl AppContext {
     {
         let context = Self::get_mut();
 
-        let obj = LocalFutureObj::new(Box::new(ContextFuture::new(Self::get_mut(), f)));
+        let obj = LocalFutureObj::new(Box::new(ContextFuture::new(
+            Self::get_mut(),
+            context.scope.clone(),
+            f,
+        )));
         context.spawner.spawn_local_obj(obj).unwrap();
 
         // // TODO: add a spawn_local_boxed method to wasm_bindgen_futures to
@@ -289,14 +633,65 @@ impl AppContext {
         F: std::future::Future<Output = ()> + 'static,
     {
         let context = Self::get_mut();
+        if !context.backend.has_event_loop() {
+            // There is no browser event loop, so everything runs on the
+            // app executor.
+            Self::spawn_custom_executor_unguarded(f);
+            return;
+        }
         wasm_bindgen_futures::spawn_local(async move {
             f.await;
             context.process_futures();
         });
     }
 
+    /// Run `callback` once `node` is inserted into the document.
+    pub(crate) fn queue_mount(node: DomNode, callback: &MountCallback) {
+        let context = Self::get_mut();
+        context.pending_mounts.push(PendingMount {
+            node,
+            callback: Rc::downgrade(callback),
+            scope: context.scope.clone(),
+        });
+    }
+
     pub(crate) fn process_futures(&mut self) {
-        self.executor.run_until_stalled();
+        loop {
+            self.executor.run_until_stalled();
+            // Mount callbacks can change state, which needs to be rendered.
+            if !self.run_mount_callbacks() {
+                break;
+            }
+        }
+    }
+
+    /// Run the callbacks of nodes that are now in the document.
+    ///
+    /// Returns `true` if any callback ran.
+    fn run_mount_callbacks(&mut self) -> bool {
+        // Adopted nodes might still be thrown away if hydration fails.
+        if self.hydration.is_some() || self.pending_mounts.is_empty() {
+            return false;
+        }
+
+        let mut ran = false;
+        for mount in std::mem::take(&mut self.pending_mounts) {
+            let Some(callback) = mount.callback.upgrade() else {
+                // The node was removed before it was mounted.
+                continue;
+            };
+            if !mount.node.is_connected() {
+                self.pending_mounts.push(mount);
+                continue;
+            }
+            let callback = callback.borrow_mut().take();
+            if let Some(callback) = callback {
+                let _entered = self.enter();
+                Self::with_scope(mount.scope, || catch_and_report(callback));
+                ran = true;
+            }
+        }
+        ran
     }
 }
 
@@ -315,21 +710,19 @@ impl AppContextRef {
 
     pub fn with<O, F: FnOnce() -> O>(&self, f: F) -> O {
         let inner = self.as_mut();
-        inner.enter();
+        let _entered = inner.enter();
         let out = f();
 
         inner.process_futures();
-        AppContext::leave();
         out
     }
 
     pub async fn with_async<O, F: std::future::Future<Output = O>>(&self, f: F) -> O {
         let inner = self.as_mut();
-        inner.enter();
+        let _entered = inner.enter();
         let out = f.await;
 
         inner.process_futures();
-        AppContext::leave();
         out
     }
 }
@@ -341,15 +734,86 @@ impl EventHandlerId {
     pub fn as_usize(&self) -> usize {
         self.0
     }
+
+    fn as_u32(&self) -> u32 {
+        self.0 as u32
+    }
 }
 
 struct EventHandler {
     id: EventHandlerId,
-    closure: Closure<dyn FnMut(web_sys::Event)>,
+    /// The listener added to the target, created on first use.
+    listener: Option<EventListener>,
     handler: Option<Box<dyn FnMut(web_sys::Event)>>,
     /// If Some(_), the event handler should be removed from the target element
-    target: web_sys::EventTarget,
+    target: EventTarget,
     ty: Ev,
+    options: EventOptions,
+    scope: Scope,
+    /// Whether the handler is invoked by the root listener instead.
+    delegated: bool,
+}
+
+impl EventHandler {
+    /// Stop invoking the handler for events of the target.
+    fn detach(&self, backend: &dyn Backend) {
+        if self.delegated {
+            if let EventTarget::Web(elem) = &self.target {
+                crate::web::delegate_remove(elem, self.ty, self.id.as_u32());
+            }
+        } else if let Some(listener) = &self.listener {
+            backend.remove_event_listener(&self.target, self.ty, listener, self.options);
+        }
+    }
+}
+
+/// Delegated event handling, see [`crate::launch_delegated`].
+struct Delegation {
+    /// The app root, followed by the targets of portals.
+    roots: Vec<web_sys::EventTarget>,
+    /// The delegated event types.
+    events: Vec<Ev>,
+    /// A listener on every root for every delegated event type.
+    listeners: Vec<EventListener>,
+}
+
+impl Delegation {
+    /// Make sure events of type `event` are dispatched.
+    fn listen(&mut self, event: Ev) {
+        if self.events.contains(&event) {
+            return;
+        }
+        self.events.push(event);
+        for root in self.roots.clone() {
+            self.add_listener(root, event);
+        }
+    }
+
+    fn add_root(&mut self, root: &web_sys::EventTarget) {
+        if self.roots.contains(root) {
+            return;
+        }
+        self.roots.push(root.clone());
+        for event in self.events.clone() {
+            self.add_listener(root.clone(), event);
+        }
+    }
+
+    fn add_listener(&mut self, root: web_sys::EventTarget, event: Ev) {
+        let context = AppContext::get_mut();
+        let backend = context.backend;
+        let current = JsValue::from(&root);
+        let listener = backend.create_event_listener(Box::new(move |dom_event| {
+            context.dispatch_delegated(&current, event, dom_event);
+        }));
+        backend.add_event_listener(
+            &EventTarget::Web(root),
+            event,
+            &listener,
+            EventOptions::new(),
+        );
+        self.listeners.push(listener);
+    }
 }
 
 pub struct EventHandlerRef(EventHandlerId);
@@ -360,37 +824,58 @@ impl Drop for EventHandlerRef {
     }
 }
 
+/// Restores the previously active context when dropped, see
+/// [`AppContext::enter`].
+struct Entered {
+    previous: Option<*mut AppContext>,
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        ACTIVE_CONTEXT.with(|active| active.set(self.previous));
+    }
+}
+
 pin_project_lite::pin_project! {
+    /// Polls a future with the app context and its scope active.
+    ///
+    /// A panic is reported as an error and completes the future.
     struct ContextFuture<F> {
         context: &'static mut AppContext,
+        scope: Scope,
         #[pin]
         inner: F,
     }
 }
 
 impl<F> ContextFuture<F> {
-    fn new(context: &'static mut AppContext, f: F) -> Self
+    fn new(context: &'static mut AppContext, scope: Scope, f: F) -> Self
     where
-        F: std::future::Future,
+        F: std::future::Future<Output = ()>,
     {
-        Self { context, inner: f }
+        Self {
+            context,
+            scope,
+            inner: f,
+        }
     }
 }
 
 impl<F> std::future::Future for ContextFuture<F>
 where
-    F: std::future::Future,
+    F: std::future::Future<Output = ()>,
 {
-    type Output = F::Output;
+    type Output = ();
 
     fn poll(
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
         let this = self.project();
-        this.context.enter();
-        let poll = this.inner.poll(cx);
-        AppContext::leave();
-        poll
+        let _entered = this.context.enter();
+        let inner = this.inner;
+        AppContext::with_scope(this.scope.clone(), || {
+            catch_and_report(|| inner.poll(cx)).unwrap_or(std::task::Poll::Ready(()))
+        })
     }
 }
@@ -406,8 +406,40 @@ make_str_enum! {
         Path = "path",
         D = "d",
         Xmlns = "xmlns",
+        XmlnsXlink = "xmlns:xlink",
         ViewBox = "viewBox",
         Fill = "fill",
+        MaskType = "mask-type",
+        TransformOrigin = "transform-origin",
+        Side = "side",
+        // MathML attributes
+        Accent = "accent",
+        AccentUnder = "accentunder",
+        ColumnAlign = "columnalign",
+        ColumnLines = "columnlines",
+        ColumnSpacing = "columnspacing",
+        DisplayStyle = "displaystyle",
+        Encoding = "encoding",
+        Fence = "fence",
+        Frame = "frame",
+        LineThickness = "linethickness",
+        LSpace = "lspace",
+        MathBackground = "mathbackground",
+        MathColor = "mathcolor",
+        MathSize = "mathsize",
+        MathVariant = "mathvariant",
+        MaxSize = "maxsize",
+        MinSize = "minsize",
+        MovableLimits = "movablelimits",
+        RowAlign = "rowalign",
+        RowLines = "rowlines",
+        RowSpacing = "rowspacing",
+        RSpace = "rspace",
+        ScriptLevel = "scriptlevel",
+        Separator = "separator",
+        Stretchy = "stretchy",
+        Symmetric = "symmetric",
+        VOffset = "voffset",
         AriaAtomic = "aria-atomic",
         AriaBusy = "aria-busy",
         AriaControls = "aria-controls",
@@ -454,3 +486,29 @@ make_str_enum! {
         AriaPlaceholder = "aria-placeholder",
     }
 }
+
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+impl Attr {
+    /// The namespace URI of prefixed attributes like `xlink:href`.
+    ///
+    /// These must be set with `setAttributeNS` to have an effect.
+    pub fn namespace(self) -> Option<&'static str> {
+        use Attr::*;
+        match self {
+            XlinkActuate | XlinkArcrole | XlinkHref | XlinkRole | XlinkShow | XlinkTitle
+            | XlinkType => Some(XLINK_NAMESPACE),
+            XmlBase | XmlLang | XmlSpace => Some(XML_NAMESPACE),
+            Xmlns | XmlnsXlink => Some(XMLNS_NAMESPACE),
+            _ => None,
+        }
+    }
+
+    /// The name without the namespace prefix.
+    pub fn local_name(self) -> &'static str {
+        let name = self.as_str();
+        name.split_once(':').map_or(name, |(_, local)| local)
+    }
+}
@@ -0,0 +1,222 @@
+//! Two-way bindings between form controls and [`Mutable`] state.
+//!
+//! The element is updated when the state changes, and the state is updated
+//! when the user changes the element. Bindings read the current value from
+//! the element instead of the event, so they work the same in in-memory
+//! documents.
+//!
+//! To avoid feedback loops, the state is only set when the value actually
+//! changed, and the element is only written when it shows a different value.
+//! Writing the value of a text input the user is typing into would otherwise
+//! move the cursor.
+
+use std::{fmt::Display, rc::Rc, str::FromStr};
+
+use futures_signals::signal::{Mutable, SignalExt};
+use web_sys::HtmlSelectElement;
+
+use crate::web::{get_property, set_property, DomNode, DomStr};
+
+use super::{builder::option, Attr, CheckboxInputEvent, DomEvent, Ev, Prop, PropValue, TagBuilder};
+
+/// Write a property, unless the element already has the value.
+fn sync_prop(elem: &DomNode, prop: Prop, value: PropValue) {
+    if get_property(elem, prop.into()) != value {
+        set_property(elem, prop.into(), value);
+    }
+}
+
+fn value_string(elem: &DomNode) -> String {
+    match get_property(elem, Prop::Value.into()) {
+        PropValue::Str(value) => value,
+        _ => String::new(),
+    }
+}
+
+fn is_checked(elem: &DomNode) -> bool {
+    get_property(elem, Prop::Checked.into()).as_bool() == Some(true)
+}
+
+impl<M> TagBuilder<M> {
+    /// Bind the value of an `<input>`, `<textarea>` or `<select>`.
+    pub fn add_bind_value(&mut self, state: &Mutable<String>) {
+        let elem = self.dom_node().clone();
+        self.spawn_ui(state.signal_cloned().for_each(move |value| {
+            sync_prop(&elem, Prop::Value, value.into());
+            async {}
+        }));
+
+        let elem = self.dom_node().clone();
+        let state = state.clone();
+        self.add_event_listener(Ev::Input, move |_| {
+            state.set_neq(value_string(&elem));
+        });
+    }
+
+    #[inline]
+    pub fn bind_value(mut self, state: &Mutable<String>) -> Self {
+        self.add_bind_value(state);
+        self
+    }
+
+    /// Bind the checked state of a checkbox.
+    pub fn add_bind_checked(&mut self, state: &Mutable<bool>) {
+        let elem = self.dom_node().clone();
+        self.spawn_ui(state.signal().for_each(move |checked| {
+            sync_prop(&elem, Prop::Checked, checked.into());
+            async {}
+        }));
+
+        let elem = self.dom_node().clone();
+        let state = state.clone();
+        self.add_event_listener(CheckboxInputEvent::event_type(), move |_| {
+            state.set_neq(is_checked(&elem));
+        });
+    }
+
+    #[inline]
+    pub fn bind_checked(mut self, state: &Mutable<bool>) -> Self {
+        self.add_bind_checked(state);
+        self
+    }
+
+    /// Bind a radio button of a group.
+    ///
+    /// The radio button is checked while `state` equals `value`, and sets
+    /// `state` to `value` when the user selects it. Bind every radio button
+    /// of the group to the same state.
+    pub fn add_bind_radio<T>(&mut self, state: &Mutable<T>, value: T)
+    where
+        T: PartialEq + Clone + 'static,
+    {
+        let elem = self.dom_node().clone();
+        let value = Rc::new(value);
+        let expected = value.clone();
+        self.spawn_ui(
+            state
+                .signal_ref(move |current| current == &*expected)
+                .for_each(move |checked| {
+                    sync_prop(&elem, Prop::Checked, checked.into());
+                    async {}
+                }),
+        );
+
+        let elem = self.dom_node().clone();
+        let state = state.clone();
+        self.add_event_listener(CheckboxInputEvent::event_type(), move |_| {
+            if is_checked(&elem) {
+                state.set_neq((*value).clone());
+            }
+        });
+    }
+
+    #[inline]
+    pub fn bind_radio<T>(mut self, state: &Mutable<T>, value: T) -> Self
+    where
+        T: PartialEq + Clone + 'static,
+    {
+        self.add_bind_radio(state, value);
+        self
+    }
+
+    /// Bind the value of a number input (or any input with a parsed value).
+    ///
+    /// Input that doesn't parse leaves `state` unchanged and stores the
+    /// error in `error`, which is reset to `None` by the next valid input.
+    /// Input that parses to the current state is not reformatted, so typing
+    /// `1.50` into an `f64` input is left alone.
+    pub fn add_bind_number<T>(&mut self, state: &Mutable<T>, error: &Mutable<Option<T::Err>>)
+    where
+        T: FromStr + Display + PartialEq + Clone + 'static,
+        T::Err: 'static,
+    {
+        let elem = self.dom_node().clone();
+        let clear_error = error.clone();
+        self.spawn_ui(state.signal_cloned().for_each(move |value| {
+            if value_string(&elem).parse::<T>().ok().as_ref() != Some(&value) {
+                set_property(&elem, Prop::Value.into(), value.to_string().into());
+                if clear_error.lock_ref().is_some() {
+                    clear_error.set(None);
+                }
+            }
+            async {}
+        }));
+
+        let elem = self.dom_node().clone();
+        let state = state.clone();
+        let error = error.clone();
+        self.add_event_listener(Ev::Input, move |_| match value_string(&elem).parse::<T>() {
+            Ok(value) => {
+                if error.lock_ref().is_some() {
+                    error.set(None);
+                }
+                state.set_neq(value);
+            }
+            Err(err) => error.set(Some(err)),
+        });
+    }
+
+    #[inline]
+    pub fn bind_number<T>(mut self, state: &Mutable<T>, error: &Mutable<Option<T::Err>>) -> Self
+    where
+        T: FromStr + Display + PartialEq + Clone + 'static,
+        T::Err: 'static,
+    {
+        self.add_bind_number(state, error);
+        self
+    }
+}
+
+impl TagBuilder<HtmlSelectElement> {
+    /// Add an `<option>` for each `(value, label)` pair and bind the
+    /// selected one to `state`.
+    ///
+    /// Nothing is selected while `state` doesn't match any of the options.
+    pub fn add_bind_options<T, L, I>(&mut self, state: &Mutable<T>, options: I)
+    where
+        T: PartialEq + Clone + 'static,
+        L: Into<DomStr<'static>>,
+        I: IntoIterator<Item = (T, L)>,
+    {
+        let mut values = Vec::new();
+        for (index, (value, label)) in options.into_iter().enumerate() {
+            self.add_tag(option().attr(Attr::Value, index.to_string()).text(label));
+            values.push(value);
+        }
+        let values = Rc::new(values);
+
+        let elem = self.dom_node().clone();
+        let options = values.clone();
+        self.spawn_ui(
+            state
+                .signal_ref(move |current| options.iter().position(|value| value == current))
+                .for_each(move |index| {
+                    let index = index.map_or(-1, |index| index as i32);
+                    sync_prop(&elem, Prop::SelectedIndex, index.into());
+                    async {}
+                }),
+        );
+
+        let elem = self.dom_node().clone();
+        let state = state.clone();
+        self.add_event_listener(Ev::Change, move |_| {
+            let index = get_property(&elem, Prop::SelectedIndex.into())
+                .as_f64()
+                .filter(|index| *index >= 0.0);
+            if let Some(value) = index.and_then(|index| values.get(index as usize)) {
+                state.set_neq(value.clone());
+            }
+        });
+    }
+
+    #[inline]
+    pub fn bind_options<T, L, I>(mut self, state: &Mutable<T>, options: I) -> Self
+    where
+        T: PartialEq + Clone + 'static,
+        L: Into<DomStr<'static>>,
+        I: IntoIterator<Item = (T, L)>,
+    {
+        self.add_bind_options(state, options);
+        self
+    }
+}
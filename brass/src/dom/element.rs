@@ -0,0 +1,259 @@
+//! Typed element builders.
+//!
+//! A [`TagBuilder<T>`] knows the DOM type of its element, so
+//! [`TagBuilder::elem`] returns the concrete `web_sys` type and
+//! element-specific methods are available. Typed builders are created with
+//! the constructors in [`super::builder`] or [`TagBuilder::new_typed`].
+//!
+//! Most element-specific values are DOM properties, see
+//! [`TagBuilder::set_prop`].
+
+use futures_signals::signal::{Signal, SignalExt};
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlAnchorElement, HtmlButtonElement, HtmlElement, HtmlFormElement, HtmlImageElement,
+    HtmlInputElement, HtmlLabelElement, HtmlOptionElement, HtmlSelectElement, HtmlTextAreaElement,
+    SvgElement,
+};
+
+use super::{Attr, Prop, TagBuilder};
+
+/// The marker type of a [`TagBuilder`], which determines the element type.
+pub trait ElementType: 'static {
+    type Element: JsCast;
+}
+
+impl ElementType for () {
+    type Element = web_sys::Element;
+}
+
+macro_rules! element_types {
+    ( $( $ty:ty ),* $(,)? ) => {
+        $(
+            impl ElementType for $ty {
+                type Element = $ty;
+            }
+        )*
+    };
+}
+
+element_types!(
+    HtmlElement,
+    HtmlAnchorElement,
+    HtmlButtonElement,
+    HtmlFormElement,
+    HtmlImageElement,
+    HtmlInputElement,
+    HtmlLabelElement,
+    HtmlOptionElement,
+    HtmlSelectElement,
+    HtmlTextAreaElement,
+    SvgElement,
+);
+
+impl TagBuilder<HtmlInputElement> {
+    /// Set the current value.
+    ///
+    /// Unlike the `value` attribute, this also updates inputs the user
+    /// already typed into.
+    pub fn set_value(&mut self, value: &str) {
+        self.set_prop(Prop::Value, value);
+    }
+
+    #[inline]
+    pub fn value(mut self, value: &str) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn add_value_signal<V, S>(&mut self, signal: S)
+    where
+        V: AsRef<str>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_prop_signal(Prop::Value, signal.map(|value| value.as_ref().to_string()));
+    }
+
+    #[inline]
+    pub fn value_signal<V, S>(mut self, signal: S) -> Self
+    where
+        V: AsRef<str>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_value_signal(signal);
+        self
+    }
+
+    /// Set the checked state of a checkbox or radio button.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.set_prop(Prop::Checked, checked);
+    }
+
+    #[inline]
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.set_checked(checked);
+        self
+    }
+
+    pub fn add_checked_signal<S>(&mut self, signal: S)
+    where
+        S: Signal<Item = bool> + 'static,
+    {
+        self.add_prop_signal(Prop::Checked, signal);
+    }
+
+    #[inline]
+    pub fn checked_signal<S>(mut self, signal: S) -> Self
+    where
+        S: Signal<Item = bool> + 'static,
+    {
+        self.add_checked_signal(signal);
+        self
+    }
+
+    /// Set the `type` attribute.
+    #[inline]
+    pub fn input_type(self, ty: &'static str) -> Self {
+        self.attr(Attr::Type, ty)
+    }
+}
+
+impl TagBuilder<HtmlTextAreaElement> {
+    /// Set the current value.
+    pub fn set_value(&mut self, value: &str) {
+        self.set_prop(Prop::Value, value);
+    }
+
+    #[inline]
+    pub fn value(mut self, value: &str) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn add_value_signal<V, S>(&mut self, signal: S)
+    where
+        V: AsRef<str>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_prop_signal(Prop::Value, signal.map(|value| value.as_ref().to_string()));
+    }
+
+    #[inline]
+    pub fn value_signal<V, S>(mut self, signal: S) -> Self
+    where
+        V: AsRef<str>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_value_signal(signal);
+        self
+    }
+}
+
+impl TagBuilder<HtmlSelectElement> {
+    /// Select the option with the given value.
+    ///
+    /// Options must be added before setting a static value.
+    pub fn set_value(&mut self, value: &str) {
+        self.set_prop(Prop::Value, value);
+    }
+
+    #[inline]
+    pub fn value(mut self, value: &str) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn add_value_signal<V, S>(&mut self, signal: S)
+    where
+        V: AsRef<str>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_prop_signal(Prop::Value, signal.map(|value| value.as_ref().to_string()));
+    }
+
+    #[inline]
+    pub fn value_signal<V, S>(mut self, signal: S) -> Self
+    where
+        V: AsRef<str>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_value_signal(signal);
+        self
+    }
+
+    /// Select the option at `index`, or nothing if `index` is `-1`.
+    ///
+    /// Options must be added before setting a static index.
+    pub fn set_selected_index(&mut self, index: i32) {
+        self.set_prop(Prop::SelectedIndex, index);
+    }
+
+    #[inline]
+    pub fn selected_index(mut self, index: i32) -> Self {
+        self.set_selected_index(index);
+        self
+    }
+
+    pub fn add_selected_index_signal<S>(&mut self, signal: S)
+    where
+        S: Signal<Item = i32> + 'static,
+    {
+        self.add_prop_signal(Prop::SelectedIndex, signal);
+    }
+
+    #[inline]
+    pub fn selected_index_signal<S>(mut self, signal: S) -> Self
+    where
+        S: Signal<Item = i32> + 'static,
+    {
+        self.add_selected_index_signal(signal);
+        self
+    }
+}
+
+impl TagBuilder<HtmlOptionElement> {
+    /// Set the `value` attribute.
+    #[inline]
+    pub fn value(self, value: &str) -> Self {
+        self.attr(Attr::Value, value)
+    }
+
+    /// Set the selected state.
+    pub fn set_selected(&mut self, selected: bool) {
+        self.set_prop(Prop::Selected, selected);
+    }
+
+    #[inline]
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.set_selected(selected);
+        self
+    }
+}
+
+impl TagBuilder<HtmlAnchorElement> {
+    #[inline]
+    pub fn href(self, href: &str) -> Self {
+        self.attr(Attr::Href, href)
+    }
+
+    #[inline]
+    pub fn href_signal<V, S>(self, signal: S) -> Self
+    where
+        V: Into<crate::DomStr<'static>>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.attr_signal(Attr::Href, signal)
+    }
+}
+
+impl TagBuilder<HtmlImageElement> {
+    #[inline]
+    pub fn src(self, src: &str) -> Self {
+        self.attr(Attr::Src, src)
+    }
+
+    #[inline]
+    pub fn alt(self, alt: &str) -> Self {
+        self.attr(Attr::Alt, alt)
+    }
+}
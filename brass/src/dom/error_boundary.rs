@@ -0,0 +1,118 @@
+use std::{fmt::Display, rc::Rc};
+
+use futures_signals::signal::SignalExt;
+
+use crate::{
+    context::AppContext,
+    error::{catch, report, Boundary, RenderError},
+};
+
+use super::{signal_view::SignalView, Render, View};
+
+/// A view that renders a fallback when its content fails.
+///
+/// Errors of the content are render closures returning `Err` (see the
+/// [`Render`] implementation of `Result`), and panics in its render closures,
+/// signal callbacks and event handlers, including those of views that are
+/// rendered later. On the first error the content is removed and replaced by
+/// the fallback. Errors of the fallback are handled by the next boundary
+/// outside.
+///
+/// All errors are also passed to the hook set with
+/// [`crate::error::set_error_hook`].
+///
+/// ```
+/// use brass::dom::{builder::p, ErrorBoundary};
+///
+/// let (_app, root) = brass::launch_memory(|| {
+///     ErrorBoundary::new(
+///         || "42x".parse::<u32>().map(|n| p().text(n.to_string())),
+///         |error| p().text(format!("Invalid number: {error}")),
+///     )
+/// });
+/// assert_eq!(root.inner_html(), "<p>Invalid number: invalid digit found in string</p>");
+/// ```
+pub struct ErrorBoundary {
+    content: Box<dyn FnOnce() -> View>,
+    fallback: Box<dyn FnOnce(RenderError) -> View>,
+}
+
+impl ErrorBoundary {
+    pub fn new<C, R, F, FR>(content: C, fallback: F) -> Self
+    where
+        C: FnOnce() -> R + 'static,
+        R: Render,
+        F: FnOnce(RenderError) -> FR + 'static,
+        FR: Render,
+    {
+        Self {
+            content: Box::new(move || content().render()),
+            fallback: Box::new(move |error| fallback(error).render()),
+        }
+    }
+}
+
+impl Render for ErrorBoundary {
+    fn render(self) -> View {
+        let boundary = Rc::new(Boundary::new());
+        let mut content = Some(self.content);
+        let mut fallback = Some(self.fallback);
+
+        let inner = boundary.clone();
+        let view = boundary
+            .error
+            .signal_cloned()
+            .map(move |error| match error {
+                None => {
+                    let Some(content) = content.take() else {
+                        return View::Empty;
+                    };
+                    let mut scope = AppContext::scope();
+                    scope.error_boundary = Some(inner.clone());
+                    AppContext::with_scope(scope, || {
+                        catch(content).unwrap_or_else(|error| {
+                            report(error);
+                            View::Empty
+                        })
+                    })
+                }
+                Some(error) => match fallback.take() {
+                    Some(fallback) => fallback(error),
+                    None => View::Empty,
+                },
+            });
+        View::Signal(SignalView::new(view))
+    }
+}
+
+impl From<ErrorBoundary> for View {
+    fn from(boundary: ErrorBoundary) -> Self {
+        boundary.render()
+    }
+}
+
+/// Errors are reported to the closest [`ErrorBoundary`], and render
+/// nothing in place.
+impl<V: Render, E: Display> Render for Result<V, E> {
+    fn render(self) -> View {
+        match self {
+            Ok(view) => view.render(),
+            Err(error) => {
+                report(RenderError::new(error));
+                View::Empty
+            }
+        }
+    }
+}
+
+impl<V: Into<View>, E: Display> From<Result<V, E>> for View {
+    fn from(result: Result<V, E>) -> Self {
+        match result {
+            Ok(view) => view.into(),
+            Err(error) => {
+                report(RenderError::new(error));
+                View::Empty
+            }
+        }
+    }
+}
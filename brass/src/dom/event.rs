@@ -1,6 +1,8 @@
 use wasm_bindgen::JsCast;
 use web_sys::HtmlInputElement;
 
+use crate::context::AppContext;
+
 // pub struct Event<E> {
 //     propagation_stopped: AtomicBool,
 //     event: E,
@@ -24,13 +26,25 @@ pub trait DomEvent: Sized {
     fn from_dom(ev: web_sys::Event) -> Option<Self>;
 }
 
+/// Cast an event to a specific type, or `None` if it has a different type.
+///
+/// The placeholder events of in-memory documents can't be checked, so they
+/// are always cast. This lets typed handlers run in tests.
+pub(crate) fn cast_event<E: JsCast>(ev: web_sys::Event) -> Option<E> {
+    if AppContext::backend().has_dom_events() {
+        ev.dyn_into().ok()
+    } else {
+        Some(ev.unchecked_into())
+    }
+}
+
 impl DomEvent for web_sys::InputEvent {
     fn event_type() -> crate::dom::Ev {
         crate::dom::Ev::Input
     }
 
     fn from_dom(ev: web_sys::Event) -> Option<Self> {
-        ev.dyn_into().ok()
+        cast_event(ev)
     }
 }
 
@@ -69,7 +83,7 @@ impl DomEvent for InputEvent {
     }
 
     fn from_dom(ev: web_sys::Event) -> Option<Self> {
-        ev.dyn_into().ok().map(Self)
+        cast_event(ev).map(Self)
     }
 }
 
@@ -108,7 +122,7 @@ impl DomEvent for ChangeEvent {
     }
 
     fn from_dom(ev: web_sys::Event) -> Option<Self> {
-        ev.dyn_into().ok().map(Self)
+        cast_event(ev).map(Self)
     }
 }
 
@@ -130,13 +144,14 @@ impl DomEvent for ClickEvent {
     }
 
     fn from_dom(ev: web_sys::Event) -> Option<Self> {
-        ev.dyn_into().ok().map(Self)
+        cast_event(ev).map(Self)
     }
 }
 
 // CheckboxInputEvent.
 
-pub struct CheckboxInputEvent(pub web_sys::InputEvent);
+/// The `change` event of a checkbox or radio button.
+pub struct CheckboxInputEvent(pub web_sys::Event);
 
 impl CheckboxInputEvent {
     pub fn value(&self) -> Option<bool> {
@@ -149,13 +164,13 @@ impl CheckboxInputEvent {
     }
 }
 
-// impl std::ops::Deref for ClickEvent {
-//     type Target = web_sys::MouseEvent;
+impl std::ops::Deref for CheckboxInputEvent {
+    type Target = web_sys::Event;
 
-//     fn deref(&self) -> &Self::Target {
-//         &self.0
-//     }
-// }
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
 impl DomEvent for CheckboxInputEvent {
     fn event_type() -> crate::dom::Ev {
@@ -163,7 +178,7 @@ impl DomEvent for CheckboxInputEvent {
     }
 
     fn from_dom(ev: web_sys::Event) -> Option<Self> {
-        ev.dyn_into().ok().map(Self)
+        Some(Self(ev))
     }
 }
 
@@ -185,7 +200,202 @@ impl DomEvent for KeyDownEvent {
     }
 
     fn from_dom(ev: web_sys::Event) -> Option<Self> {
-        ev.dyn_into().ok().map(Self)
+        cast_event(ev).map(Self)
+    }
+}
+
+impl KeyDownEvent {
+    /// The pressed key, or `None` for keys that produce characters.
+    pub fn named_key(&self) -> Option<Key> {
+        Key::parse(&self.0.key())
+    }
+}
+
+/// Define event wrappers with `Deref` to the `web_sys` event type.
+macro_rules! dom_events {
+    (
+        $(
+            $( #[$meta:meta] )*
+            $name:ident($ty:ty) = $ev:ident;
+        )*
+    ) => {
+        $(
+            $( #[$meta] )*
+            pub struct $name(pub $ty);
+
+            impl std::ops::Deref for $name {
+                type Target = $ty;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl DomEvent for $name {
+                fn event_type() -> crate::dom::Ev {
+                    crate::dom::Ev::$ev
+                }
+
+                fn from_dom(ev: web_sys::Event) -> Option<Self> {
+                    cast_event(ev).map(Self)
+                }
+            }
+        )*
+    };
+}
+
+dom_events! {
+    // Forms.
+    SubmitEvent(web_sys::SubmitEvent) = Submit;
+
+    // Focus.
+    FocusEvent(web_sys::FocusEvent) = Focus;
+    BlurEvent(web_sys::FocusEvent) = Blur;
+
+    // Keyboard.
+    KeyUpEvent(web_sys::KeyboardEvent) = KeyUp;
+
+    // Mouse.
+    MouseDownEvent(web_sys::MouseEvent) = MouseDown;
+    MouseUpEvent(web_sys::MouseEvent) = MouseUp;
+    MouseMoveEvent(web_sys::MouseEvent) = MouseMove;
+    MouseEnterEvent(web_sys::MouseEvent) = MouseEnter;
+    MouseLeaveEvent(web_sys::MouseEvent) = MouseLeave;
+    DblClickEvent(web_sys::MouseEvent) = DblClick;
+    ContextMenuEvent(web_sys::MouseEvent) = ContextMenu;
+    WheelEvent(web_sys::WheelEvent) = Wheel;
+
+    // Pointer.
+    PointerDownEvent(web_sys::PointerEvent) = PointerDown;
+    PointerUpEvent(web_sys::PointerEvent) = PointerUp;
+    PointerMoveEvent(web_sys::PointerEvent) = PointerMove;
+    PointerEnterEvent(web_sys::PointerEvent) = PointerEnter;
+    PointerLeaveEvent(web_sys::PointerEvent) = PointerLeave;
+    PointerCancelEvent(web_sys::PointerEvent) = PointerCancel;
+
+    // Touch.
+    TouchStartEvent(web_sys::TouchEvent) = TouchStart;
+    TouchMoveEvent(web_sys::TouchEvent) = TouchMove;
+    TouchEndEvent(web_sys::TouchEvent) = TouchEnd;
+    TouchCancelEvent(web_sys::TouchEvent) = TouchCancel;
+
+    // Drag and drop.
+    DragStartEvent(web_sys::DragEvent) = DragStart;
+    DragEvent(web_sys::DragEvent) = Drag;
+    DragEndEvent(web_sys::DragEvent) = DragEnd;
+    DragEnterEvent(web_sys::DragEvent) = DragEnter;
+    DragOverEvent(web_sys::DragEvent) = DragOver;
+    DragLeaveEvent(web_sys::DragEvent) = DragLeave;
+    DropEvent(web_sys::DragEvent) = Drop;
+
+    // Scrolling.
+    ScrollEvent(web_sys::Event) = Scroll;
+
+    // Animations and transitions.
+    AnimationStartEvent(web_sys::AnimationEvent) = AnimationStart;
+    AnimationEndEvent(web_sys::AnimationEvent) = AnimationEnd;
+    AnimationIterationEvent(web_sys::AnimationEvent) = AnimationIteration;
+    TransitionStartEvent(web_sys::TransitionEvent) = TransitionStart;
+    TransitionEndEvent(web_sys::TransitionEvent) = TransitionEnd;
+    TransitionRunEvent(web_sys::TransitionEvent) = TransitionRun;
+
+    // Clipboard.
+    CutEvent(web_sys::ClipboardEvent) = Cut;
+    CopyEvent(web_sys::ClipboardEvent) = Copy;
+    PasteEvent(web_sys::ClipboardEvent) = Paste;
+}
+
+impl KeyUpEvent {
+    /// The released key, or `None` for keys that produce characters.
+    pub fn named_key(&self) -> Option<Key> {
+        Key::parse(&self.0.key())
+    }
+}
+
+/// Data access of drag and drop events.
+macro_rules! data_transfer_events {
+    ( $( $name:ident ),* ) => {
+        $(
+            impl $name {
+                /// The data of the drag operation in `format`, eg `text/plain`.
+                pub fn data(&self, format: &str) -> Option<String> {
+                    self.0.data_transfer()?.get_data(format).ok()
+                }
+
+                /// Set the data of the drag operation in `format`.
+                ///
+                /// Only has an effect in `dragstart` handlers.
+                pub fn set_data(&self, format: &str, data: &str) {
+                    if let Some(transfer) = self.0.data_transfer() {
+                        transfer.set_data(format, data).ok();
+                    }
+                }
+            }
+        )*
+    };
+}
+
+data_transfer_events!(
+    DragStartEvent,
+    DragEvent,
+    DragEndEvent,
+    DragEnterEvent,
+    DragOverEvent,
+    DragLeaveEvent,
+    DropEvent
+);
+
+impl PasteEvent {
+    /// The pasted text.
+    pub fn text(&self) -> Option<String> {
+        self.0.clipboard_data()?.get_data("text/plain").ok()
+    }
+}
+
+make_str_enum! {
+    /// Values of [`web_sys::KeyboardEvent::key`] for keys that don't produce
+    /// characters.
+    Key {
+        Enter = "Enter",
+        Tab = "Tab",
+        Space = " ",
+        Escape = "Escape",
+        Backspace = "Backspace",
+        Delete = "Delete",
+        Insert = "Insert",
+        ArrowUp = "ArrowUp",
+        ArrowDown = "ArrowDown",
+        ArrowLeft = "ArrowLeft",
+        ArrowRight = "ArrowRight",
+        Home = "Home",
+        End = "End",
+        PageUp = "PageUp",
+        PageDown = "PageDown",
+        Shift = "Shift",
+        Control = "Control",
+        Alt = "Alt",
+        AltGraph = "AltGraph",
+        Meta = "Meta",
+        CapsLock = "CapsLock",
+        NumLock = "NumLock",
+        ScrollLock = "ScrollLock",
+        ContextMenu = "ContextMenu",
+        PrintScreen = "PrintScreen",
+        Pause = "Pause",
+        F1 = "F1",
+        F2 = "F2",
+        F3 = "F3",
+        F4 = "F4",
+        F5 = "F5",
+        F6 = "F6",
+        F7 = "F7",
+        F8 = "F8",
+        F9 = "F9",
+        F10 = "F10",
+        F11 = "F11",
+        F12 = "F12",
+        Dead = "Dead",
+        Unidentified = "Unidentified",
     }
 }
 
@@ -207,12 +417,13 @@ make_str_enum! {
         PageHide = "pagehide",
         PageShow = "pageshow",
         PopState = "popstate",
+        HashChange = "hashchange",
         AnimationStart = "animationstart",
         AnimationEnd = "animationend",
         AnimationIteration = "animationiteration",
-        TransitionStart = "transtionstart",
+        TransitionStart = "transitionstart",
         TransitionEnd = "transitionend",
-        TranstionRun = "transitionrun",
+        TransitionRun = "transitionrun",
         Rest = "rest",
         Submit = "submit",
         BeforePrint = "beforeprint",
@@ -337,3 +548,158 @@ make_str_enum! {
         SelectionChange = "selectionchange",
     }
 }
+
+impl Ev {
+    #[deprecated(note = "renamed to `Ev::TransitionRun`")]
+    #[allow(non_upper_case_globals)]
+    pub const TranstionRun: Ev = Ev::TransitionRun;
+
+    /// Whether events of this type bubble up to the ancestors of the target.
+    ///
+    /// Only bubbling events can be delegated, see [`crate::launch_delegated`].
+    pub fn bubbles(self) -> bool {
+        matches!(
+            self,
+            Self::Click
+                | Self::AuxClick
+                | Self::DblClick
+                | Self::ContextMenu
+                | Self::MouseDown
+                | Self::MouseUp
+                | Self::MouseMove
+                | Self::MouseOver
+                | Self::MouseOut
+                | Self::PointerDown
+                | Self::PointerMove
+                | Self::PointerUp
+                | Self::PointerCancel
+                | Self::PointerOver
+                | Self::PointerOut
+                | Self::GotPointerCapture
+                | Self::LostPointerCapture
+                | Self::TouchStart
+                | Self::TouchEnd
+                | Self::TouchMove
+                | Self::TouchCancel
+                | Self::Wheel
+                | Self::KeyDown
+                | Self::KeyUp
+                | Self::KeyPress
+                | Self::Input
+                | Self::Change
+                | Self::Submit
+                | Self::Cut
+                | Self::Copy
+                | Self::Paste
+                | Self::CompositionStart
+                | Self::CompositionUpdate
+                | Self::CompositionEnd
+                | Self::Drag
+                | Self::DragStart
+                | Self::DragEnd
+                | Self::DragEnter
+                | Self::DragLeave
+                | Self::DragOver
+                | Self::Drop
+                | Self::Select
+                | Self::AnimationStart
+                | Self::AnimationEnd
+                | Self::AnimationIteration
+                | Self::TransitionStart
+                | Self::TransitionEnd
+                | Self::TransitionRun
+        )
+    }
+}
+
+/// Options of an event listener.
+///
+/// `capture`, `passive` and `once` are passed to `addEventListener`.
+/// `prevent_default` and `stop_propagation` are applied to the event before
+/// the handler runs, so handlers don't need to call them themselves.
+///
+/// In `view!`, options are written as modifiers after the event name, for
+/// example `onsubmit|prevent=...` or `onclick|stop|once=...`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventOptions {
+    /// Invoke the handler in the capture phase, before handlers of descendants.
+    pub capture: bool,
+    /// Promise the browser that the handler doesn't prevent the default
+    /// action, which allows smooth scrolling for touch and wheel events.
+    pub passive: bool,
+    /// Remove the listener after the first event.
+    pub once: bool,
+    /// Call `preventDefault` on the event.
+    pub prevent_default: bool,
+    /// Call `stopPropagation` on the event.
+    pub stop_propagation: bool,
+}
+
+impl EventOptions {
+    pub const fn new() -> Self {
+        Self {
+            capture: false,
+            passive: false,
+            once: false,
+            prevent_default: false,
+            stop_propagation: false,
+        }
+    }
+
+    #[inline]
+    pub const fn capture(mut self) -> Self {
+        self.capture = true;
+        self
+    }
+
+    #[inline]
+    pub const fn passive(mut self) -> Self {
+        self.passive = true;
+        self
+    }
+
+    #[inline]
+    pub const fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    #[inline]
+    pub const fn prevent_default(mut self) -> Self {
+        self.prevent_default = true;
+        self
+    }
+
+    #[inline]
+    pub const fn stop_propagation(mut self) -> Self {
+        self.stop_propagation = true;
+        self
+    }
+
+    /// Whether any of the flags passed to `addEventListener` is set.
+    ///
+    /// Handlers with flags can't be delegated, since they need a listener on
+    /// the target itself.
+    pub(crate) fn has_listener_flags(&self) -> bool {
+        self.capture || self.passive || self.once
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_parse() {
+        assert_eq!(Key::parse("Enter"), Some(Key::Enter));
+        assert_eq!(Key::parse(" "), Some(Key::Space));
+        assert_eq!(Key::parse("a"), None);
+        assert_eq!(Key::ArrowLeft.as_str(), "ArrowLeft");
+    }
+
+    #[test]
+    fn test_event_names() {
+        assert_eq!(Ev::TransitionStart.as_str(), "transitionstart");
+        assert_eq!(Ev::parse("transitionrun"), Some(Ev::TransitionRun));
+    }
+}
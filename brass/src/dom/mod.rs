@@ -1,21 +1,48 @@
 mod attribute;
+mod binding;
+mod element;
+mod error_boundary;
 mod event;
 mod node;
+mod node_ref;
+mod portal;
+mod property;
 mod signal_vec_view;
 mod signal_view;
 mod style;
+mod suspense;
 mod tag;
+mod transition;
 mod view;
 
+pub(crate) use self::{signal_view::SignalView, suspense::SuspenseState, tag::current_namespace};
+
 pub use self::{
     attribute::Attr,
-    event::{ChangeEvent, CheckboxInputEvent, ClickEvent, DomEvent, Ev, InputEvent, KeyDownEvent},
+    element::ElementType,
+    error_boundary::ErrorBoundary,
+    event::{
+        AnimationEndEvent, AnimationIterationEvent, AnimationStartEvent, BlurEvent, ChangeEvent,
+        CheckboxInputEvent, ClickEvent, ContextMenuEvent, CopyEvent, CutEvent, DblClickEvent,
+        DomEvent, DragEndEvent, DragEnterEvent, DragEvent, DragLeaveEvent, DragOverEvent,
+        DragStartEvent, DropEvent, Ev, EventOptions, FocusEvent, InputEvent, Key, KeyDownEvent,
+        KeyUpEvent, MouseDownEvent, MouseEnterEvent, MouseLeaveEvent, MouseMoveEvent, MouseUpEvent,
+        PasteEvent, PointerCancelEvent, PointerDownEvent, PointerEnterEvent, PointerLeaveEvent,
+        PointerMoveEvent, PointerUpEvent, ScrollEvent, SubmitEvent, TouchCancelEvent,
+        TouchEndEvent, TouchMoveEvent, TouchStartEvent, TransitionEndEvent, TransitionRunEvent,
+        TransitionStartEvent, WheelEvent,
+    },
     node::{
-        builder, Apply, ApplyFuture, AttrValueApply, EventHandlerApply, Fragment, Node, Render,
-        TagBuilder, WithSignal,
+        builder, Apply, ApplyFuture, AttrValueApply, EventHandlerApply, Fragment, Node,
+        PropValueApply, Render, TagBuilder, WithSignal,
     },
+    node_ref::NodeRef,
+    portal::{Portal, PortalTarget},
+    property::{Prop, PropName, PropValue},
     style::Style,
-    tag::Tag,
+    suspense::{PendingGuard, Suspense},
+    tag::{with_namespace, Namespace, Tag},
+    transition::Transition,
     view::View,
 };
 
@@ -1,4 +1,8 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    rc::Rc,
+};
 
 use futures::Future;
 use futures_signals::{
@@ -10,16 +14,18 @@ use wasm_bindgen::JsCast;
 
 use crate::{
     component::{build_component, Component},
-    context::{AppContext, EventHandlerRef},
+    context::{AppContext, EventHandlerRef, MountCallback},
     web::{
-        self, create_element, create_text, elem_add_class, elem_remove_class, elem_set_class_js,
-        empty_string, remove_attr, set_attribute, set_style, set_text_data, DomStr,
+        self, create_element, create_text, elem_add_class, elem_remove_class, hydration,
+        remove_attr, set_attribute, set_attribute_empty, set_property, set_style, set_text_data,
+        DomNode, DomStr,
     },
 };
 
 use super::{
-    signal_vec_view::SignalVecView, signal_view::SignalView, view::RetainedView, AbortGuard, Attr,
-    DomEvent, Ev, Style, Tag, View,
+    element::ElementType, event::cast_event, signal_vec_view::SignalVecView,
+    signal_view::SignalView, view::RetainedView, AbortGuard, Attr, DomEvent, Ev, EventOptions,
+    NodeRef, PropName, PropValue, Style, Tag, Transition, View,
 };
 
 pub struct Fragment {
@@ -33,7 +39,7 @@ impl From<Fragment> for View {
 }
 
 pub struct Node {
-    node: web_sys::Node,
+    node: DomNode,
     events: Vec<EventHandlerRef>,
     after_remove: Vec<Box<dyn FnOnce()>>,
     aborts: Vec<AbortGuard>,
@@ -42,24 +48,38 @@ pub struct Node {
 
 impl Node {
     #[inline]
-    pub fn node(&self) -> &web_sys::Node {
+    pub fn node(&self) -> &DomNode {
         &self.node
     }
 
-    pub(crate) fn attach(&self, parent: &web_sys::Element) {
-        parent.append_child(&self.node).unwrap();
+    pub(crate) fn attach(&self, parent: &DomNode) {
+        parent.append_child(&self.node);
     }
 
     pub fn new_text(value: DomStr<'_>) -> Self {
-        let text = web::create_text(value);
         Self {
-            node: text.into(),
+            node: web::create_text(value),
+            events: Vec::new(),
+            after_remove: Vec::new(),
+            aborts: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// A node without content that only marks a position.
+    pub(crate) fn new_placeholder() -> Self {
+        Self {
+            node: web::create_empty_node(),
             events: Vec::new(),
             after_remove: Vec::new(),
             aborts: Vec::new(),
             children: Vec::new(),
         }
     }
+
+    pub(crate) fn add_after_remove<F: FnOnce() + 'static>(&mut self, f: F) {
+        self.after_remove.push(Box::new(f));
+    }
 }
 
 impl Drop for Node {
@@ -84,10 +104,9 @@ pub struct TagBuilder<T = ()> {
 impl TagBuilder<()> {
     pub fn new(tag: Tag) -> Self {
         // TODO: use cache!
-        let elem = create_element(tag);
         Self {
             node: Node {
-                node: elem.into(),
+                node: create_element(tag),
                 events: Vec::new(),
                 after_remove: Vec::new(),
                 aborts: Vec::new(),
@@ -104,15 +123,85 @@ impl TagBuilder<()> {
             _marker: PhantomData,
         }
     }
+}
 
-    pub fn elem(&self) -> &web_sys::Element {
-        self.node.node.unchecked_ref()
+impl<T: ElementType> TagBuilder<T> {
+    /// Create a typed builder.
+    ///
+    /// The element created for `tag` must be a `T`.
+    pub fn new_typed(tag: Tag) -> Self {
+        TagBuilder::new(tag).cast()
     }
 
-    pub fn with_ref(self, target: &mut Option<web_sys::Element>) -> Self {
-        *target = Some(self.elem().clone());
+    /// The browser DOM element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element belongs to an in-memory document, for example
+    /// during server side rendering.
+    pub fn elem(&self) -> &T::Element {
+        match &self.node.node {
+            DomNode::Web(node) => node.unchecked_ref(),
+            DomNode::Memory(_) => {
+                panic!("TagBuilder::elem() is only available for browser DOM elements")
+            }
+        }
+    }
+
+    /// Fill `node_ref` with the element.
+    ///
+    /// The reference is cleared when the element is removed.
+    pub fn add_node_ref(&mut self, node_ref: &NodeRef<T::Element>) {
+        let node = self.node.node.clone();
+        node_ref.set(node.clone());
+        let node_ref = node_ref.clone();
+        self.add_after_remove(move || node_ref.clear(&node));
+    }
+
+    #[inline]
+    pub fn node_ref(mut self, node_ref: &NodeRef<T::Element>) -> Self {
+        self.add_node_ref(node_ref);
+        self
+    }
+
+    /// Store the browser DOM element in `target`.
+    ///
+    /// Leaves `target` untouched for in-memory documents. Use
+    /// [`Self::node_ref`] to access the element after the builder is gone.
+    pub fn with_ref(self, target: &mut Option<T::Element>) -> Self
+    where
+        T::Element: Clone,
+    {
+        if let DomNode::Web(node) = &self.node.node {
+            *target = Some(node.unchecked_ref::<T::Element>().clone());
+        }
         self
     }
+}
+
+impl<M> TagBuilder<M> {
+    /// The node of the element.
+    #[inline]
+    pub fn dom_node(&self) -> &DomNode {
+        &self.node.node
+    }
+
+    /// Change the element type.
+    ///
+    /// The element must be a `U`, otherwise [`Self::elem`] returns an
+    /// invalid reference.
+    pub fn cast<U>(self) -> TagBuilder<U> {
+        TagBuilder {
+            node: self.node,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Forget the element type.
+    #[inline]
+    pub fn into_untyped(self) -> TagBuilder {
+        self.cast()
+    }
 
     pub fn spawn_ui<F: Future<Output = ()> + 'static>(&mut self, f: F) {
         let guard = AppContext::spawn_custom_executor_abortable(f);
@@ -138,6 +227,48 @@ impl TagBuilder<()> {
         self
     }
 
+    /// Run `f` once the element has been inserted into the document.
+    ///
+    /// Callbacks run after the current update has been rendered, so the
+    /// whole view is in the document and can be focused or measured. This
+    /// also applies to elements inserted later, for example by
+    /// [`Self::signal`] or [`Self::signal_vec`]. Elements that are removed
+    /// before they are inserted never run the callback, and neither do
+    /// server rendered views.
+    pub fn add_on_mount<F: FnOnce() + 'static>(&mut self, f: F) {
+        let callback: MountCallback = Rc::new(RefCell::new(Some(Box::new(f))));
+        AppContext::queue_mount(self.node.node.clone(), &callback);
+        self.add_bind(callback);
+    }
+
+    #[inline]
+    pub fn on_mount<F: FnOnce() + 'static>(mut self, f: F) -> Self {
+        self.add_on_mount(f);
+        self
+    }
+
+    /// Run `f` when the element is removed, if it was mounted before.
+    ///
+    /// Unlike [`Self::after_remove`], the callback doesn't run for elements
+    /// that never made it into the document. Parents are unmounted before
+    /// their children.
+    pub fn add_on_unmount<F: FnOnce() + 'static>(&mut self, f: F) {
+        let mounted = Rc::new(Cell::new(false));
+        let flag = mounted.clone();
+        self.add_on_mount(move || flag.set(true));
+        self.add_after_remove(move || {
+            if mounted.get() {
+                f();
+            }
+        });
+    }
+
+    #[inline]
+    pub fn on_unmount<F: FnOnce() + 'static>(mut self, f: F) -> Self {
+        self.add_on_unmount(f);
+        self
+    }
+
     pub fn add_bind<V: 'static>(&mut self, value: V) {
         self.add_after_remove(move || {
             std::mem::drop(value);
@@ -152,24 +283,29 @@ impl TagBuilder<()> {
     // Attributes.
 
     pub fn add_attr<'a, I: Into<DomStr<'a>>>(&mut self, attr: Attr, value: I) {
-        set_attribute(self.elem(), attr, value.into());
+        set_attribute(&self.node.node, attr, value.into());
     }
 
     pub fn attr<'a, I: Into<DomStr<'a>>>(self, attr: Attr, value: I) -> Self {
-        set_attribute(self.elem(), attr, value.into());
+        set_attribute(&self.node.node, attr, value.into());
         self
     }
 
-    pub fn attr_toggle(self, attr: Attr) -> Self {
-        self.attr(attr, empty_string())
+    /// Add a boolean attribute (an attribute with an empty value).
+    pub fn add_attr_toggle(&mut self, attr: Attr) {
+        set_attribute_empty(&self.node.node, attr);
+    }
+
+    pub fn attr_toggle(mut self, attr: Attr) -> Self {
+        self.add_attr_toggle(attr);
+        self
     }
 
-    pub fn attr_toggle_if(self, flag: bool, attr: Attr) -> Self {
+    pub fn attr_toggle_if(mut self, flag: bool, attr: Attr) -> Self {
         if flag {
-            self.attr(attr, empty_string())
-        } else {
-            self
+            self.add_attr_toggle(attr);
         }
+        self
     }
 
     pub fn add_attr_signal<V, S>(&mut self, attr: Attr, signal: S)
@@ -177,7 +313,7 @@ impl TagBuilder<()> {
         V: Into<DomStr<'static>>,
         S: Signal<Item = V> + 'static,
     {
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
         let f = signal.for_each(move |value| {
             set_attribute(&elem, attr, value.into());
             async {}
@@ -190,7 +326,7 @@ impl TagBuilder<()> {
         V: Into<DomStr<'static>>,
         S: Signal<Item = Option<V>> + 'static,
     {
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
         let mut is_added = false;
         let f = signal.for_each(move |opt| {
             if let Some(value) = opt {
@@ -218,11 +354,10 @@ impl TagBuilder<()> {
     where
         S: Signal<Item = bool> + 'static,
     {
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
         let f = signal.for_each(move |flag| {
             if flag {
-                // TODO: use cached empty string.
-                set_attribute(&elem, attr, empty_string().into());
+                set_attribute_empty(&elem, attr);
             } else {
                 remove_attr(&elem, attr);
             }
@@ -240,6 +375,57 @@ impl TagBuilder<()> {
         self
     }
 
+    // Properties.
+
+    /// Set a DOM property, like `value` or `checked`.
+    ///
+    /// Unlike attributes, properties reflect the current state of an
+    /// element, so setting them also updates controls the user changed.
+    /// `None` sets the property to `null`.
+    pub fn set_prop<N, V>(&mut self, name: N, value: V)
+    where
+        N: Into<PropName>,
+        V: Into<PropValue>,
+    {
+        set_property(&self.node.node, name.into(), value.into());
+    }
+
+    #[inline]
+    pub fn prop<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<PropName>,
+        V: Into<PropValue>,
+    {
+        self.set_prop(name, value);
+        self
+    }
+
+    pub fn add_prop_signal<N, V, S>(&mut self, name: N, signal: S)
+    where
+        N: Into<PropName>,
+        V: Into<PropValue>,
+        S: Signal<Item = V> + 'static,
+    {
+        let elem = self.node.node.clone();
+        let name = name.into();
+        let f = signal.for_each(move |value| {
+            set_property(&elem, name, value.into());
+            async {}
+        });
+        self.spawn_ui(f);
+    }
+
+    #[inline]
+    pub fn prop_signal<N, V, S>(mut self, name: N, signal: S) -> Self
+    where
+        N: Into<PropName>,
+        V: Into<PropValue>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_prop_signal(name, signal);
+        self
+    }
+
     // Class.
 
     #[inline]
@@ -277,7 +463,7 @@ impl TagBuilder<()> {
         I: Into<DomStr<'a>>,
     {
         if let Some(class) = class {
-            elem_add_class(self.elem(), &class.into());
+            elem_add_class(&self.node.node, &class.into());
         }
     }
 
@@ -318,7 +504,7 @@ impl TagBuilder<()> {
         I: Into<DomStr<'static>>,
         S: Signal<Item = I> + 'static,
     {
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
         let mut current = None;
         self.spawn_ui(signal.for_each(move |value| {
             if let Some(current) = current.take() {
@@ -348,7 +534,7 @@ impl TagBuilder<()> {
     {
         let class = class.into();
 
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
         let mut is_added = false;
         self.spawn_ui(signal.for_each(move |flag| {
             if flag {
@@ -376,8 +562,7 @@ impl TagBuilder<()> {
         self
     }
 
-    #[inline]
-    pub fn add_classes_signal<'a, V, S>(&mut self, signal: S)
+    pub fn add_classes_signal<V, S>(&mut self, signal: S)
     where
         V: Into<DomStr<'static>>,
         S: SignalVec<Item = V> + 'static,
@@ -385,52 +570,98 @@ impl TagBuilder<()> {
         // TODO: we really want a custom ClassList signal implementation instead of
         // MutableVec.
 
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
+        // Mirror of the current values, needed to know which class to remove
+        // for index based diffs.
+        let mut current = Vec::<DomStr<'static>>::new();
 
         self.spawn_ui(signal.for_each(move |diff| {
             match diff {
                 VecDiff::Replace { values } => {
-                    elem_set_class_js(&elem, empty_string());
+                    for class in current.drain(..) {
+                        elem_remove_class(&elem, &class);
+                    }
                     for value in values {
-                        elem_add_class(&elem, &value.into());
+                        let class = value.into();
+                        elem_add_class(&elem, &class);
+                        current.push(class);
                     }
                 }
-                VecDiff::InsertAt { index: _, value } => {
-                    elem_add_class(&elem, &value.into());
+                VecDiff::InsertAt { index, value } => {
+                    let class = value.into();
+                    elem_add_class(&elem, &class);
+                    current.insert(index.min(current.len()), class);
                 }
-                VecDiff::UpdateAt { index: _, value: _ } => {
-                    unimplemented!()
+                VecDiff::UpdateAt { index, value } => {
+                    if index < current.len() {
+                        let class = value.into();
+                        let old = std::mem::replace(&mut current[index], class);
+                        elem_remove_class(&elem, &old);
+                        restore_classes(&elem, &current);
+                    } else {
+                        tracing::warn!("invalid VecDiff::UpdateAt: index {index} does not exist");
+                    }
                 }
-                VecDiff::RemoveAt { index: _ } => {
-                    unimplemented!()
+                VecDiff::RemoveAt { index } => {
+                    if index < current.len() {
+                        let old = current.remove(index);
+                        elem_remove_class(&elem, &old);
+                        restore_classes(&elem, &current);
+                    } else {
+                        tracing::warn!("invalid VecDiff::RemoveAt: index {index} does not exist");
+                    }
                 }
                 VecDiff::Move {
-                    old_index: _,
-                    new_index: _,
-                } => {}
+                    old_index,
+                    new_index,
+                } => {
+                    // The order of classes is irrelevant for the DOM, only
+                    // the mirror needs to be kept in sync.
+                    if old_index < current.len() && new_index < current.len() {
+                        let class = current.remove(old_index);
+                        current.insert(new_index, class);
+                    }
+                }
                 VecDiff::Push { value } => {
-                    elem_add_class(&elem, &value.into());
+                    let class = value.into();
+                    elem_add_class(&elem, &class);
+                    current.push(class);
                 }
                 VecDiff::Pop {} => {
-                    unimplemented!()
+                    if let Some(old) = current.pop() {
+                        elem_remove_class(&elem, &old);
+                        restore_classes(&elem, &current);
+                    }
                 }
                 VecDiff::Clear {} => {
-                    elem_set_class_js(&elem, empty_string());
+                    for class in current.drain(..) {
+                        elem_remove_class(&elem, &class);
+                    }
                 }
             }
             async {}
         }));
     }
 
+    #[inline]
+    pub fn classes_signal<V, S>(mut self, signal: S) -> Self
+    where
+        V: Into<DomStr<'static>>,
+        S: SignalVec<Item = V> + 'static,
+    {
+        self.add_classes_signal(signal);
+        self
+    }
+
     // Style.
 
     pub fn style_raw<'a, I: Into<DomStr<'a>>>(self, value: I) -> Self {
-        set_attribute(self.elem(), Attr::Style, value.into());
+        set_attribute(&self.node.node, Attr::Style, value.into());
         self
     }
 
     pub fn set_style<'a, I: Into<DomStr<'a>>>(&mut self, style: Style, value: I) {
-        set_style(self.elem(), style, value.into());
+        set_style(&self.node.node, style, value.into());
     }
 
     #[inline]
@@ -444,7 +675,7 @@ impl TagBuilder<()> {
         V: Into<DomStr<'static>>,
         S: Signal<Item = V> + 'static,
     {
-        let elem = self.elem().clone();
+        let elem = self.node.node.clone();
         let f = signal.for_each(move |value| {
             set_style(&elem, style, value.into());
             async {}
@@ -467,12 +698,12 @@ impl TagBuilder<()> {
         I: Into<DomStr<'a>>,
     {
         let value = class.into();
-        elem_add_class(self.elem(), &value);
+        elem_add_class(&self.node.node, &value);
     }
 
     pub fn add_text<'a>(&mut self, value: DomStr<'a>) {
         let text = create_text(value);
-        self.node.node.append_child(&text).unwrap();
+        self.node.node.append_child(&text);
     }
 
     #[inline]
@@ -489,8 +720,16 @@ impl TagBuilder<()> {
         V: Into<DomStr<'static>>,
         S: Signal<Item = V> + 'static,
     {
-        let text = create_text("".into());
-        self.node.node.append_child(&text).unwrap();
+        // While hydrating, the current value is needed to adopt the text node.
+        let mut signal = Box::pin(signal);
+        let initial = if hydration::is_hydrating() {
+            hydration::poll_now(signal.as_mut()).map(Into::into)
+        } else {
+            None
+        };
+
+        let text = create_text(initial.unwrap_or_else(|| "".into()));
+        self.node.node.append_child(&text);
 
         let f = signal.for_each(move |value| {
             set_text_data(&text, &value.into());
@@ -515,41 +754,71 @@ impl TagBuilder<()> {
     where
         F: FnMut(web_sys::Event) + 'static,
     {
-        let evref = AppContext::create_event_listener(
-            event,
-            handler,
-            self.elem().unchecked_ref::<web_sys::EventTarget>().clone(),
-        );
+        self.add_event_listener_with_options(event, EventOptions::new(), handler);
+    }
+
+    pub fn add_event_listener_with_options<F>(
+        &mut self,
+        event: Ev,
+        options: EventOptions,
+        handler: F,
+    ) where
+        F: FnMut(web_sys::Event) + 'static,
+    {
+        let evref =
+            AppContext::create_element_listener(event, options, handler, (&self.node.node).into());
         self.node.events.push(evref);
     }
 
-    pub fn add_event_listener_cast<E, F>(&mut self, event: Ev, mut handler: F)
+    pub fn add_event_listener_cast<E, F>(&mut self, event: Ev, handler: F)
     where
         E: AsRef<web_sys::Event> + JsCast,
         F: FnMut(E) + 'static,
     {
-        let wrapped_handler = move |raw_event: web_sys::Event| match raw_event.dyn_into::<E>() {
-            Ok(event) => {
+        self.add_event_listener_cast_with_options(event, EventOptions::new(), handler);
+    }
+
+    pub fn add_event_listener_cast_with_options<E, F>(
+        &mut self,
+        event: Ev,
+        options: EventOptions,
+        mut handler: F,
+    ) where
+        E: AsRef<web_sys::Event> + JsCast,
+        F: FnMut(E) + 'static,
+    {
+        let wrapped_handler = move |raw_event: web_sys::Event| match cast_event::<E>(raw_event) {
+            Some(event) => {
                 handler(event);
             }
-            Err(err) => {
+            None => {
                 tracing::error!(
-                    "Event handler received invalid invalid event type (Expected {}) - {:?}",
+                    "Event handler received invalid invalid event type (Expected {})",
                     std::any::type_name::<E>(),
-                    err
                 );
             }
         };
 
-        self.add_event_listener(event, wrapped_handler);
+        self.add_event_listener_with_options(event, options, wrapped_handler);
     }
 
-    pub fn add_dom_event_listener<E, F>(&mut self, mut handler: F)
+    pub fn add_dom_event_listener<E, F>(&mut self, handler: F)
     where
         E: DomEvent,
         F: FnMut(E) + 'static,
     {
-        self.add_event_listener(E::event_type(), move |raw_event| {
+        self.add_dom_event_listener_with_options(EventOptions::new(), handler);
+    }
+
+    pub fn add_dom_event_listener_with_options<E, F>(
+        &mut self,
+        options: EventOptions,
+        mut handler: F,
+    ) where
+        E: DomEvent,
+        F: FnMut(E) + 'static,
+    {
+        self.add_event_listener_with_options(E::event_type(), options, move |raw_event| {
             if let Some(event) = E::from_dom(raw_event) {
                 handler(event);
             }
@@ -565,6 +834,16 @@ impl TagBuilder<()> {
         self
     }
 
+    #[inline]
+    pub fn on_with_options<E, F>(mut self, options: EventOptions, handler: F) -> Self
+    where
+        E: DomEvent,
+        F: FnMut(E) + 'static,
+    {
+        self.add_dom_event_listener_with_options(options, handler);
+        self
+    }
+
     pub fn on_event<F>(mut self, event: Ev, handler: F) -> Self
     where
         F: Fn(web_sys::Event) + 'static,
@@ -573,11 +852,31 @@ impl TagBuilder<()> {
         self
     }
 
+    /// Add an event handler with listener options and modifiers.
+    ///
+    /// ```
+    /// use brass::dom::{builder::form, Ev, EventOptions};
+    ///
+    /// let (_app, _root) = brass::launch_memory(|| {
+    ///     form().on_event_with_options(Ev::Submit, EventOptions::new().prevent_default(), |_| {
+    ///         // Submit without reloading the page.
+    ///     })
+    /// });
+    /// ```
+    #[inline]
+    pub fn on_event_with_options<F>(mut self, event: Ev, options: EventOptions, handler: F) -> Self
+    where
+        F: FnMut(web_sys::Event) + 'static,
+    {
+        self.add_event_listener_with_options(event, options, handler);
+        self
+    }
+
     // Node.
 
     pub fn add_node(&mut self, node: Node) {
         // TODO use custom binding for efficiency?
-        self.node.node.append_child(&node.node).unwrap();
+        self.node.node.append_child(&node.node);
 
         // TODO: only keep nodes that have event handlers, otherwise add fold
         // them into the current node with code below.
@@ -588,11 +887,11 @@ impl TagBuilder<()> {
     }
 
     #[inline]
-    pub fn add_tag(&mut self, child: TagBuilder) {
+    pub fn add_tag<C>(&mut self, child: TagBuilder<C>) {
         self.add_node(child.node);
     }
 
-    pub fn tag(mut self, child: TagBuilder) -> Self {
+    pub fn tag<C>(mut self, child: TagBuilder<C>) -> Self {
         self.add_tag(child);
         self
     }
@@ -601,6 +900,7 @@ impl TagBuilder<()> {
 
     pub fn add_signal_view(&mut self, sig: SignalView) {
         sig.attach(self.node.node());
+        self.node.children.push(RetainedView::Signal(sig));
     }
 
     pub fn add_signal<T, S>(&mut self, signal: S)
@@ -609,9 +909,7 @@ impl TagBuilder<()> {
         S: Signal<Item = T> + 'static,
     {
         // TODO: add single-method constructor that already receives the parent?
-        let sig = SignalView::new(signal);
-        sig.attach(self.node.node());
-        self.node.children.push(RetainedView::Signal(sig));
+        self.add_signal_view(SignalView::new(signal));
     }
 
     #[inline]
@@ -624,6 +922,28 @@ impl TagBuilder<()> {
         self
     }
 
+    pub fn add_signal_with_transition<T, S>(&mut self, signal: S, transition: Transition)
+    where
+        T: Into<View>,
+        S: Signal<Item = T> + 'static,
+    {
+        let mut view = SignalView::new(signal);
+        view.set_transition(transition);
+        self.add_signal_view(view);
+    }
+
+    /// Like [`Self::signal`], but the previous view leaves and the new one
+    /// enters with a [`Transition`].
+    #[inline]
+    pub fn signal_with_transition<V, S>(mut self, signal: S, transition: Transition) -> Self
+    where
+        V: Into<View>,
+        S: Signal<Item = V> + 'static,
+    {
+        self.add_signal_with_transition(signal, transition);
+        self
+    }
+
     // SignalVec.
 
     pub fn add_signal_vec_view(&mut self, view: SignalVecView) {
@@ -666,6 +986,115 @@ impl TagBuilder<()> {
         self
     }
 
+    pub fn add_signal_vec_with_transition<T, S, O, R>(
+        &mut self,
+        signal: S,
+        render: R,
+        transition: Transition,
+    ) where
+        S: SignalVec<Item = T> + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        let mut view = SignalVecView::new(signal, render, None);
+        view.set_transition(transition);
+        self.add_signal_vec_view(view)
+    }
+
+    /// Like [`Self::signal_vec`], but children enter and leave with a
+    /// [`Transition`].
+    pub fn signal_vec_with_transition<T, S, O, R>(
+        mut self,
+        signal: S,
+        render: R,
+        transition: Transition,
+    ) -> Self
+    where
+        S: SignalVec<Item = T> + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        self.add_signal_vec_with_transition(signal, render, transition);
+        self
+    }
+
+    pub fn add_signal_vec_keyed<T, S, K, KF, O, R>(
+        &mut self,
+        signal: S,
+        key: KF,
+        render: R,
+        fallback: Option<View>,
+    ) where
+        S: SignalVec<Item = T> + 'static,
+        K: Eq + std::hash::Hash + 'static,
+        KF: Fn(&T) -> K + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        self.add_signal_vec_view(SignalVecView::new_keyed(signal, key, render, fallback))
+    }
+
+    /// Render a [`SignalVec`] with children identified by a key.
+    ///
+    /// When the whole list is replaced (eg with `MutableVec::replace_cloned`),
+    /// children with a key that is still present are retained and moved
+    /// instead of being rendered again, which preserves element state like
+    /// focus, scroll position and component state.
+    ///
+    /// Note that retained children are not re-rendered, even if their value
+    /// changed. Use signals inside the rendered view for values that can
+    /// change.
+    pub fn signal_vec_keyed<T, S, K, KF, O, R>(mut self, signal: S, key: KF, render: R) -> Self
+    where
+        S: SignalVec<Item = T> + 'static,
+        K: Eq + std::hash::Hash + 'static,
+        KF: Fn(&T) -> K + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        self.add_signal_vec_keyed(signal, key, render, None);
+        self
+    }
+
+    pub fn add_signal_vec_keyed_with_transition<T, S, K, KF, O, R>(
+        &mut self,
+        signal: S,
+        key: KF,
+        render: R,
+        transition: Transition,
+    ) where
+        S: SignalVec<Item = T> + 'static,
+        K: Eq + std::hash::Hash + 'static,
+        KF: Fn(&T) -> K + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        let mut view = SignalVecView::new_keyed(signal, key, render, None);
+        view.set_transition(transition);
+        self.add_signal_vec_view(view)
+    }
+
+    /// Like [`Self::signal_vec_keyed`], but children enter and leave with a
+    /// [`Transition`], and retained children that move can be animated with
+    /// [`Transition::moves`].
+    pub fn signal_vec_keyed_with_transition<T, S, K, KF, O, R>(
+        mut self,
+        signal: S,
+        key: KF,
+        render: R,
+        transition: Transition,
+    ) -> Self
+    where
+        S: SignalVec<Item = T> + 'static,
+        K: Eq + std::hash::Hash + 'static,
+        KF: Fn(&T) -> K + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        self.add_signal_vec_keyed_with_transition(signal, key, render, transition);
+        self
+    }
+
     // Component.
 
     pub fn add_component<C: Component>(&mut self, props: C::Properties) {
@@ -683,10 +1112,14 @@ impl TagBuilder<()> {
         match view {
             View::Empty => {}
             View::Node(n) => {
-                n.attach(self.elem());
+                n.attach(&self.node.node);
                 self.node.children.push(RetainedView::Node(n));
             }
-            View::Fragment(_) => todo!(),
+            View::Fragment(f) => {
+                for item in f.items {
+                    self.add_view(item);
+                }
+            }
             View::Signal(s) => {
                 self.add_signal_view(s);
             }
@@ -724,8 +1157,19 @@ impl TagBuilder<()> {
     }
 }
 
-impl From<TagBuilder> for View {
-    fn from(t: TagBuilder) -> Self {
+/// Re-add classes after a removal.
+///
+/// A class list can contain the same class multiple times, so removing one
+/// entry from the DOM might remove a class that is still present.
+/// Adding an existing class is a no-op.
+fn restore_classes(elem: &DomNode, classes: &[DomStr<'static>]) {
+    for class in classes {
+        elem_add_class(elem, class);
+    }
+}
+
+impl<T> From<TagBuilder<T>> for View {
+    fn from(t: TagBuilder<T>) -> Self {
         Self::Node(t.build())
     }
 }
@@ -735,47 +1179,47 @@ pub trait Render {
 }
 
 impl<R: Render> Apply for R {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_view(self.render());
     }
 }
 
 pub trait Apply {
-    fn apply(self, tag: &mut TagBuilder);
+    fn apply<T>(self, tag: &mut TagBuilder<T>);
 }
 
 impl<'a> Apply for &'a str {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text(self.into());
     }
 }
 
 impl<'a> Apply for &'a JsString {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text(self.into());
     }
 }
 
 impl Apply for JsString {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text(self.into());
     }
 }
 
 impl<'a> Apply for &'a String {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text(self.into());
     }
 }
 
 impl Apply for String {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text(self.into());
     }
 }
 
 impl<'a> Apply for DomStr<'a> {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text(self);
     }
 }
@@ -786,7 +1230,7 @@ impl Render for Node {
     }
 }
 
-impl Render for TagBuilder {
+impl<T> Render for TagBuilder<T> {
     fn render(self) -> View {
         self.node.render()
     }
@@ -798,16 +1242,14 @@ impl Render for View {
     }
 }
 
-impl Apply for Fragment {
-    fn apply(self, tag: &mut TagBuilder) {
-        for item in self.items {
-            item.apply(tag);
-        }
+impl Render for Fragment {
+    fn render(self) -> View {
+        View::Fragment(self)
     }
 }
 
 impl<'a> Apply for &'a Mutable<String> {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         // TODO: possible to avoid cloning?
         tag.add_text_signal(self.signal_cloned());
     }
@@ -820,7 +1262,7 @@ where
     S: Signal<Item = O> + 'static,
     O: Into<DomStr<'static>>,
 {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_text_signal(self.0)
     }
 }
@@ -829,7 +1271,7 @@ impl<I> Apply for Option<I>
 where
     I: Apply,
 {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         if let Some(inner) = self {
             inner.apply(tag);
         }
@@ -837,7 +1279,7 @@ where
 }
 
 // impl <A, I> Apply for I where A: Apply, I: IntoIterator<Item = A> {
-//     fn apply(self, tag: &mut TagBuilder) {
+//     fn apply<T>(self, tag: &mut TagBuilder<T>) {
 //         for item in self {
 //             item.apply(tag);
 //         }
@@ -851,7 +1293,7 @@ where
     S: Signal<Item = I> + 'static,
     I: Into<View>,
 {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         tag.add_signal(self.0);
     }
 }
@@ -862,17 +1304,19 @@ impl<F> Apply for ApplyFuture<F>
 where
     F: std::future::Future<Output = TagBuilder> + 'static,
 {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         let mut wrapper = builder::div();
-        let elem = wrapper.elem().clone();
+        let elem = wrapper.dom_node().clone();
         let keeper: Rc<RefCell<Option<TagBuilder>>> = std::rc::Rc::new(RefCell::new(None));
         wrapper.add_bind(keeper.clone());
 
         let f = self.0;
+        let pending = super::PendingGuard::new();
         wrapper.spawn_ui(async move {
             let child = f.await;
-            elem.append_child(child.elem()).ok();
+            elem.append_child(child.dom_node());
             *keeper.borrow_mut() = Some(child);
+            drop(pending);
         });
 
         tag.add_tag(wrapper);
@@ -886,14 +1330,14 @@ where
 // }
 
 impl<A1: Apply, A2: Apply> Apply for (A1, A2) {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         self.0.apply(tag);
         self.1.apply(tag);
     }
 }
 
 impl<A1: Apply, A2: Apply, A3: Apply> Apply for (A1, A2, A3) {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         self.0.apply(tag);
         self.1.apply(tag);
         self.2.apply(tag);
@@ -901,7 +1345,7 @@ impl<A1: Apply, A2: Apply, A3: Apply> Apply for (A1, A2, A3) {
 }
 
 impl<A1: Apply, A2: Apply, A3: Apply, A4: Apply> Apply for (A1, A2, A3, A4) {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         self.0.apply(tag);
         self.1.apply(tag);
         self.2.apply(tag);
@@ -910,7 +1354,7 @@ impl<A1: Apply, A2: Apply, A3: Apply, A4: Apply> Apply for (A1, A2, A3, A4) {
 }
 
 impl<A1: Apply, A2: Apply, A3: Apply, A4: Apply, A5: Apply> Apply for (A1, A2, A3, A4, A5) {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         self.0.apply(tag);
         self.1.apply(tag);
         self.2.apply(tag);
@@ -922,7 +1366,7 @@ impl<A1: Apply, A2: Apply, A3: Apply, A4: Apply, A5: Apply> Apply for (A1, A2, A
 impl<A1: Apply, A2: Apply, A3: Apply, A4: Apply, A5: Apply, A6: Apply> Apply
     for (A1, A2, A3, A4, A5, A6)
 {
-    fn apply(self, tag: &mut TagBuilder) {
+    fn apply<T>(self, tag: &mut TagBuilder<T>) {
         self.0.apply(tag);
         self.1.apply(tag);
         self.2.apply(tag);
@@ -933,11 +1377,11 @@ impl<A1: Apply, A2: Apply, A3: Apply, A4: Apply, A5: Apply, A6: Apply> Apply
 }
 
 pub trait AttrValueApply<M> {
-    fn attr_apply(self, attr: Attr, b: &mut TagBuilder);
+    fn attr_apply<T>(self, attr: Attr, b: &mut TagBuilder<T>);
 }
 
 impl<V: Into<DomStr<'static>>> AttrValueApply<DomStr<'static>> for V {
-    fn attr_apply(self, attr: Attr, b: &mut TagBuilder) {
+    fn attr_apply<T>(self, attr: Attr, b: &mut TagBuilder<T>) {
         b.add_attr(attr, self)
     }
 }
@@ -945,7 +1389,7 @@ impl<V: Into<DomStr<'static>>> AttrValueApply<DomStr<'static>> for V {
 impl<V: Into<DomStr<'static>>, S: Signal<Item = V> + 'static> AttrValueApply<(S, DomStr<'static>)>
     for S
 {
-    fn attr_apply(self, attr: Attr, b: &mut TagBuilder) {
+    fn attr_apply<T>(self, attr: Attr, b: &mut TagBuilder<T>) {
         b.add_attr_signal(attr, self)
     }
 }
@@ -953,21 +1397,42 @@ impl<V: Into<DomStr<'static>>, S: Signal<Item = V> + 'static> AttrValueApply<(S,
 impl<V: Into<DomStr<'static>>, S: Signal<Item = Option<V>> + 'static>
     AttrValueApply<(S, Option<DomStr<'static>>)> for S
 {
-    fn attr_apply(self, attr: Attr, b: &mut TagBuilder) {
+    fn attr_apply<T>(self, attr: Attr, b: &mut TagBuilder<T>) {
         b.add_attr_signal_opt(attr, self)
     }
 }
 
+pub trait PropValueApply<M> {
+    fn prop_apply<T>(self, name: PropName, b: &mut TagBuilder<T>);
+}
+
+impl<V: Into<PropValue>> PropValueApply<PropValue> for V {
+    fn prop_apply<T>(self, name: PropName, b: &mut TagBuilder<T>) {
+        b.set_prop(name, self)
+    }
+}
+
+impl<V: Into<PropValue>, S: Signal<Item = V> + 'static> PropValueApply<(S, PropValue)> for S {
+    fn prop_apply<T>(self, name: PropName, b: &mut TagBuilder<T>) {
+        b.add_prop_signal(name, self)
+    }
+}
+
 pub trait EventHandlerApply<V> {
-    fn event_handler_apply(self, event: Ev, target: &mut TagBuilder);
+    fn event_handler_apply<T>(self, event: Ev, options: EventOptions, target: &mut TagBuilder<T>);
 }
 
 impl<F> EventHandlerApply<fn()> for F
 where
     F: FnMut() + 'static,
 {
-    fn event_handler_apply(mut self, event: Ev, target: &mut TagBuilder) {
-        target.add_event_listener(event, move |_| self())
+    fn event_handler_apply<T>(
+        mut self,
+        event: Ev,
+        options: EventOptions,
+        target: &mut TagBuilder<T>,
+    ) {
+        target.add_event_listener_with_options(event, options, move |_| self())
     }
 }
 
@@ -976,12 +1441,17 @@ where
     F: FnMut(E) + 'static,
     E: AsRef<web_sys::Event> + JsCast,
 {
-    fn event_handler_apply(self, event: Ev, target: &mut TagBuilder) {
-        target.add_event_listener_cast(event, self)
+    fn event_handler_apply<T>(self, event: Ev, options: EventOptions, target: &mut TagBuilder<T>) {
+        target.add_event_listener_cast_with_options(event, options, self)
     }
 }
 
 pub mod builder {
+    use web_sys::{
+        HtmlAnchorElement, HtmlFormElement, HtmlImageElement, HtmlInputElement, HtmlLabelElement,
+        HtmlOptionElement, HtmlSelectElement, HtmlTextAreaElement,
+    };
+
     use super::{Tag, TagBuilder};
 
     #[inline]
@@ -1005,12 +1475,47 @@ pub mod builder {
     }
 
     #[inline]
-    pub fn input() -> TagBuilder {
-        TagBuilder::new(Tag::Input)
+    pub fn p() -> TagBuilder {
+        TagBuilder::new(Tag::P)
+    }
+
+    #[inline]
+    pub fn a() -> TagBuilder<HtmlAnchorElement> {
+        TagBuilder::new_typed(Tag::A)
     }
 
     #[inline]
-    pub fn p() -> TagBuilder {
-        TagBuilder::new(Tag::P)
+    pub fn img() -> TagBuilder<HtmlImageElement> {
+        TagBuilder::new_typed(Tag::Img)
+    }
+
+    #[inline]
+    pub fn form() -> TagBuilder<HtmlFormElement> {
+        TagBuilder::new_typed(Tag::Form)
+    }
+
+    #[inline]
+    pub fn label() -> TagBuilder<HtmlLabelElement> {
+        TagBuilder::new_typed(Tag::Label)
+    }
+
+    #[inline]
+    pub fn input() -> TagBuilder<HtmlInputElement> {
+        TagBuilder::new_typed(Tag::Input)
+    }
+
+    #[inline]
+    pub fn textarea() -> TagBuilder<HtmlTextAreaElement> {
+        TagBuilder::new_typed(Tag::TextArea)
+    }
+
+    #[inline]
+    pub fn select() -> TagBuilder<HtmlSelectElement> {
+        TagBuilder::new_typed(Tag::Select)
+    }
+
+    #[inline]
+    pub fn option() -> TagBuilder<HtmlOptionElement> {
+        TagBuilder::new_typed(Tag::Option)
     }
 }
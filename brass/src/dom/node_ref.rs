@@ -0,0 +1,105 @@
+use std::marker::PhantomData;
+
+use futures_signals::signal::{Mutable, Signal};
+use wasm_bindgen::JsCast;
+
+use crate::web::DomNode;
+
+/// A handle to the element of a [`super::TagBuilder`].
+///
+/// The reference is filled when the element is created and cleared when it
+/// is removed. It can be cloned and stored in components or event handlers
+/// to access the element later, for example to focus it, measure it or
+/// mount third-party widgets into it.
+///
+/// ```
+/// use brass::dom::{builder::input, NodeRef};
+///
+/// let input_ref = NodeRef::new();
+/// let (_app, _root) = brass::launch_memory(|| input().node_ref(&input_ref));
+/// assert!(input_ref.is_set());
+/// ```
+pub struct NodeRef<E = web_sys::Element> {
+    node: Mutable<Option<DomNode>>,
+    _marker: PhantomData<E>,
+}
+
+impl<E> Clone for NodeRef<E> {
+    fn clone(&self) -> Self {
+        Self {
+            node: self.node.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E> Default for NodeRef<E> {
+    fn default() -> Self {
+        Self {
+            node: Mutable::new(None),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E> std::fmt::Debug for NodeRef<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("NodeRef")
+            .field(&*self.node.lock_ref())
+            .finish()
+    }
+}
+
+impl<E: JsCast> NodeRef<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the element currently exists.
+    pub fn is_set(&self) -> bool {
+        self.node.lock_ref().is_some()
+    }
+
+    /// The browser DOM element.
+    ///
+    /// Returns `None` if the element doesn't exist, isn't an `E`, or belongs
+    /// to an in-memory document. Use [`Self::dom_node`] for those.
+    pub fn get(&self) -> Option<E> {
+        self.node.lock_ref().as_ref().and_then(web_element)
+    }
+
+    /// The node of the element, in any document.
+    pub fn dom_node(&self) -> Option<DomNode> {
+        self.node.get_cloned()
+    }
+
+    /// A signal of the browser DOM element, see [`Self::get`].
+    pub fn signal(&self) -> impl Signal<Item = Option<E>> {
+        self.node
+            .signal_ref(|node| node.as_ref().and_then(web_element))
+    }
+
+    /// A signal of the node of the element, in any document.
+    pub fn dom_node_signal(&self) -> impl Signal<Item = Option<DomNode>> {
+        self.node.signal_cloned()
+    }
+
+    pub(crate) fn set(&self, node: DomNode) {
+        self.node.set(Some(node));
+    }
+
+    /// Clear the reference, unless it was already moved to another node.
+    pub(crate) fn clear(&self, node: &DomNode) {
+        let mut current = self.node.lock_mut();
+        if current
+            .as_ref()
+            .is_some_and(|current| current.is_same(node))
+        {
+            *current = None;
+        }
+    }
+}
+
+fn web_element<E: JsCast>(node: &DomNode) -> Option<E> {
+    node.as_web().and_then(|node| node.clone().dyn_into().ok())
+}
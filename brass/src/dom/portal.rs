@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+use wasm_bindgen::JsCast;
+
+use crate::{
+    context::AppContext,
+    web::{hydration, window, DomNode},
+};
+
+use super::{Node, Render, View};
+
+/// Where a [`Portal`] renders its content.
+#[derive(Clone, Debug)]
+pub enum PortalTarget {
+    /// `document.body`.
+    Body,
+    /// The first element matching a CSS selector, looked up when the portal
+    /// is rendered.
+    Selector(Cow<'static, str>),
+    Node(DomNode),
+}
+
+impl PortalTarget {
+    /// Find the target element.
+    ///
+    /// In-memory documents have no body and can't be queried, so only
+    /// [`Self::Node`] targets are found there.
+    fn resolve(&self, marker: &DomNode) -> Option<DomNode> {
+        match self {
+            Self::Node(node) => Some(node.clone()),
+            _ if marker.as_web().is_none() => None,
+            Self::Body => {
+                let body = window().document()?.body()?;
+                Some(DomNode::Web(body.unchecked_into()))
+            }
+            Self::Selector(selector) => {
+                let elem = window().document()?.query_selector(selector).ok()??;
+                Some(DomNode::Web(elem.unchecked_into()))
+            }
+        }
+    }
+}
+
+impl From<DomNode> for PortalTarget {
+    fn from(node: DomNode) -> Self {
+        Self::Node(node)
+    }
+}
+
+impl From<web_sys::Element> for PortalTarget {
+    fn from(elem: web_sys::Element) -> Self {
+        Self::Node(DomNode::Web(elem.unchecked_into()))
+    }
+}
+
+/// A view that renders its content into a different parent, like
+/// `document.body`.
+///
+/// Used for modals, tooltips and dropdowns that must not be clipped by
+/// `overflow: hidden` ancestors. The content is owned by the portal: it is
+/// appended to the target when the portal is rendered, and removed when the
+/// portal is removed. Event handlers, signals and lifecycle hooks of the
+/// content work as if it was rendered in place, but DOM events bubble up
+/// through the ancestors of the target.
+///
+/// The portal itself only leaves an empty placeholder node in place. If the
+/// target doesn't exist, the content is not rendered.
+///
+/// ```
+/// use brass::{dom::{builder::{div, p}, Portal, PortalTarget}, web::memory::MemoryNode};
+///
+/// let overlay = MemoryNode::new_element("div");
+/// let target = PortalTarget::Node(overlay.clone().into());
+/// let (_app, root) = brass::launch_memory(|| {
+///     div().and(Portal::new(target, || p().text("Hello")))
+/// });
+/// assert_eq!(overlay.inner_html(), "<p>Hello</p>");
+/// assert_eq!(root.find_element("p"), None);
+/// ```
+pub struct Portal {
+    target: PortalTarget,
+    content: Box<dyn FnOnce() -> View>,
+}
+
+impl Portal {
+    /// The content is rendered lazily, when the portal is rendered.
+    pub fn new<T, R, F>(target: T, content: F) -> Self
+    where
+        T: Into<PortalTarget>,
+        R: Render,
+        F: FnOnce() -> R + 'static,
+    {
+        Self {
+            target: target.into(),
+            content: Box::new(move || content().render()),
+        }
+    }
+
+    /// Render `content` into `document.body`.
+    pub fn body<R, F>(content: F) -> Self
+    where
+        R: Render,
+        F: FnOnce() -> R + 'static,
+    {
+        Self::new(PortalTarget::Body, content)
+    }
+
+    /// Render `content` into the first element matching `selector`.
+    pub fn selector<R, F>(selector: impl Into<Cow<'static, str>>, content: F) -> Self
+    where
+        R: Render,
+        F: FnOnce() -> R + 'static,
+    {
+        Self::new(PortalTarget::Selector(selector.into()), content)
+    }
+}
+
+impl Render for Portal {
+    fn render(self) -> View {
+        let mut marker = Node::new_placeholder();
+        let Some(target) = self.target.resolve(marker.node()) else {
+            if marker.node().as_web().is_some() {
+                tracing::warn!("portal target {:?} not found", self.target);
+            }
+            return marker.into();
+        };
+
+        if let DomNode::Web(node) = &target {
+            AppContext::add_delegation_root(node);
+        }
+
+        // The content isn't part of the markup that is being hydrated.
+        let content = hydration::detached(|| (self.content)().into_retained());
+        content.append_to(&target);
+
+        marker.add_after_remove(move || {
+            content.remove_from_parent(&target);
+        });
+        marker.into()
+    }
+}
+
+impl From<Portal> for View {
+    fn from(portal: Portal) -> Self {
+        portal.render()
+    }
+}
@@ -0,0 +1,182 @@
+//! DOM properties.
+//!
+//! Properties are distinct from attributes: the `value` attribute of an input
+//! only sets the initial value, while the `value` property reflects what is
+//! currently shown. Properties are set with `js_sys::Reflect`, so any custom
+//! JS property can be set as well.
+
+use wasm_bindgen::JsValue;
+
+make_str_enum! {
+    Prop {
+        Value = "value",
+        Checked = "checked",
+        Selected = "selected",
+        SelectedIndex = "selectedIndex",
+        Indeterminate = "indeterminate",
+        Disabled = "disabled",
+        Hidden = "hidden",
+        Open = "open",
+        DefaultValue = "defaultValue",
+        DefaultChecked = "defaultChecked",
+        ValueAsNumber = "valueAsNumber",
+        ScrollTop = "scrollTop",
+        ScrollLeft = "scrollLeft",
+        Muted = "muted",
+        Volume = "volume",
+        CurrentTime = "currentTime",
+        PlaybackRate = "playbackRate",
+    }
+}
+
+/// The name of a property, either a common one or any custom name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PropName {
+    Known(Prop),
+    Custom(&'static str),
+}
+
+impl PropName {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Known(prop) => prop.as_str(),
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+impl From<Prop> for PropName {
+    fn from(prop: Prop) -> Self {
+        Self::Known(prop)
+    }
+}
+
+impl From<&'static str> for PropName {
+    fn from(name: &'static str) -> Self {
+        Self::Custom(name)
+    }
+}
+
+/// The value of a property.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Js(JsValue),
+}
+
+impl PropValue {
+    /// Convert a JS value, keeping objects as [`PropValue::Js`].
+    pub fn from_js(value: JsValue) -> Self {
+        if value.is_null() || value.is_undefined() {
+            Self::Null
+        } else if let Some(value) = value.as_bool() {
+            Self::Bool(value)
+        } else if let Some(value) = value.as_f64() {
+            Self::Number(value)
+        } else if let Some(value) = value.as_string() {
+            Self::Str(value)
+        } else {
+            Self::Js(value)
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn into_js(self) -> JsValue {
+        match self {
+            Self::Null => JsValue::NULL,
+            Self::Bool(value) => JsValue::from_bool(value),
+            Self::Number(value) => JsValue::from_f64(value),
+            Self::Str(value) => JsValue::from_str(&value),
+            Self::Js(value) => value,
+        }
+    }
+
+    /// The value as it would appear in an attribute.
+    ///
+    /// Returns `None` for `null`, `false` and JS values.
+    pub(crate) fn to_attribute(&self) -> Option<String> {
+        match self {
+            Self::Null | Self::Bool(false) | Self::Js(_) => None,
+            Self::Bool(true) => Some(String::new()),
+            Self::Number(value) => Some(value.to_string()),
+            Self::Str(value) => Some(value.clone()),
+        }
+    }
+}
+
+impl From<bool> for PropValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for PropValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i32> for PropValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<u32> for PropValue {
+    fn from(value: u32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<String> for PropValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<&String> for PropValue {
+    fn from(value: &String) -> Self {
+        Self::Str(value.clone())
+    }
+}
+
+impl From<&str> for PropValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<JsValue> for PropValue {
+    fn from(value: JsValue) -> Self {
+        Self::Js(value)
+    }
+}
+
+impl<V: Into<PropValue>> From<Option<V>> for PropValue {
+    fn from(value: Option<V>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
@@ -1,10 +1,18 @@
+use std::collections::HashMap;
+
 use futures::future::{AbortHandle, Abortable};
 use futures_signals::signal_vec::{SignalVec, SignalVecExt, VecDiff};
-use wasm_bindgen::{JsCast, JsValue};
 
-use crate::{context::AppContext, web::create_empty_node};
+use crate::{
+    context::AppContext,
+    web::{create_empty_node, hydration, DomNode},
+};
 
-use super::{view::RetainedView, AbortGuard, Render, View};
+use super::{
+    transition::{Leaving, Transition},
+    view::RetainedView,
+    AbortGuard, Render, View,
+};
 
 /// A view that is backed by a [`Signal`].
 ///
@@ -15,53 +23,93 @@ pub struct SignalVecView(Box<Inner>);
 
 struct Inner {
     fallback: Option<RetainedView>,
-    marker: web_sys::Node,
+    fallback_visible: bool,
+    marker: DomNode,
     children: Vec<RetainedView>,
     _abort: AbortGuard,
-    parent: Option<web_sys::Node>,
+    parent: Option<DomNode>,
+    transition: Option<Transition>,
+    /// Removed children that wait for their leave transition.
+    leaving: Vec<Leaving>,
+    /// Whether the initial values were rendered.
+    rendered: bool,
 }
 
 impl SignalVecView {
-    pub(crate) fn replace_with(self, parent: &web_sys::Node, new_view: View) -> RetainedView {
+    /// Animate inserted, removed and moved children, see [`Transition`].
+    pub(crate) fn set_transition(&mut self, transition: Transition) {
+        self.0.transition = Some(transition);
+    }
+
+    fn finish_leaving(&self) {
+        for leaving in &self.0.leaving {
+            leaving.finish();
+        }
+    }
+
+    pub(crate) fn replace_with(self, parent: &DomNode, new_view: View) -> RetainedView {
+        self.finish_leaving();
         let new = new_view.into_retained();
         new.insert_before(parent, &self.0.marker);
-        parent.remove_child(&self.0.marker).unwrap();
-        for child in self.0.children {
-            child.remove_from_parent(parent);
-        }
+        self.remove_from_parent(parent);
         new
     }
 
-    pub(crate) fn remove_from_parent(&self, parent: &web_sys::Node) {
-        parent.remove_child(&self.0.marker).unwrap();
+    pub(crate) fn remove_from_parent(&self, parent: &DomNode) {
+        self.finish_leaving();
+        parent.remove_child(&self.0.marker);
         for child in &self.0.children {
             child.remove_from_parent(parent);
         }
+        if let Some(fb) = self.visible_fallback() {
+            fb.remove_from_parent(parent);
+        }
     }
 
-    pub fn replace(&self, parent: &web_sys::Node, old_node: &web_sys::Node) {
-        for child in self.0.children.iter().rev() {
+    fn visible_fallback(&self) -> Option<&RetainedView> {
+        if self.0.fallback_visible {
+            self.0.fallback.as_ref()
+        } else {
+            None
+        }
+    }
+
+    pub fn replace(&self, parent: &DomNode, old_node: &DomNode) {
+        for child in &self.0.children {
             child.insert_before(parent, old_node);
         }
-        parent.replace_child(&self.0.marker, old_node).unwrap();
+        parent.replace_child(&self.0.marker, old_node);
     }
 
-    pub(crate) fn attach(&self, parent: &web_sys::Node) {
-        debug_assert!(self.0.children.is_empty());
-        parent.append_child(&self.0.marker).unwrap();
+    pub(crate) fn attach(&self, parent: &DomNode) {
+        for child in &self.0.children {
+            child.append_to(parent);
+        }
+        if let Some(fb) = self.visible_fallback() {
+            fb.append_to(parent);
+        }
+        parent.append_child(&self.0.marker);
     }
 
-    pub(crate) fn insert_before(&self, parent: &web_sys::Node, before: &web_sys::Node) {
-        for child in self.0.children.iter().rev() {
+    pub(crate) fn parent_node(&self) -> Option<DomNode> {
+        self.0.marker.parent_node()
+    }
+
+    pub(crate) fn insert_before(&self, parent: &DomNode, before: &DomNode) {
+        self.finish_leaving();
+        for child in &self.0.children {
             child.insert_before(parent, before);
         }
-        parent.insert_before(&self.0.marker, Some(before)).unwrap();
+        if let Some(fb) = self.visible_fallback() {
+            fb.insert_before(parent, before);
+        }
+        parent.insert_before(&self.0.marker, Some(before));
     }
 
-    pub(crate) fn prepend_before_self(&self, parent: &web_sys::Node, new: &RetainedView) {
+    pub(crate) fn prepend_before_self(&self, parent: &DomNode, new: &RetainedView) {
         if let Some(child) = self.0.children.first() {
             child.prepend_before_self(parent, new);
-        } else if let Some(fb) = &self.0.fallback {
+        } else if let Some(fb) = self.visible_fallback() {
             fb.prepend_before_self(parent, new);
         } else {
             new.insert_before(parent, &self.0.marker);
@@ -77,16 +125,158 @@ impl SignalVecView {
         S: SignalVec<Item = T> + 'static,
         R: Fn(&T) -> O + 'static,
         O: Render,
+    {
+        Self::spawn(signal, fallback, move |state, parent, patch| {
+            let render = |value: &T| render(value).render();
+
+            match patch {
+                VecDiff::Replace { values } => {
+                    state.replace_children(
+                        parent,
+                        values.iter().map(|v| render(v).into_retained()).collect(),
+                    );
+                }
+                VecDiff::InsertAt { index, value } => {
+                    state.insert_child(parent, index, render(&value).into_retained());
+                }
+                VecDiff::UpdateAt { index, value } => {
+                    state.update_child(parent, index, render(&value));
+                }
+                VecDiff::RemoveAt { index } => {
+                    state.remove_child(parent, index);
+                }
+                VecDiff::Move {
+                    old_index,
+                    new_index,
+                } => {
+                    state.move_child(parent, old_index, new_index);
+                }
+                VecDiff::Push { value } => {
+                    let len = state.children.len();
+                    state.insert_child(parent, len, render(&value).into_retained());
+                }
+                VecDiff::Pop {} => {
+                    if let Some(index) = state.children.len().checked_sub(1) {
+                        state.remove_child(parent, index);
+                    }
+                }
+                VecDiff::Clear {} => {
+                    state.replace_children(parent, Vec::new());
+                }
+            }
+        })
+    }
+
+    /// Create a keyed view.
+    ///
+    /// Each child is identified by the key returned by `key`.
+    /// On [`VecDiff::Replace`] the existing children are reused for keys
+    /// that are still present, and only new keys are rendered.
+    /// Note that reused children are not re-rendered, even if the value
+    /// changed.
+    pub(crate) fn new_keyed<T, S, K, KF, O, R>(
+        signal: S,
+        key: KF,
+        render: R,
+        fallback: Option<View>,
+    ) -> Self
+    where
+        S: SignalVec<Item = T> + 'static,
+        K: Eq + std::hash::Hash + 'static,
+        KF: Fn(&T) -> K + 'static,
+        R: Fn(&T) -> O + 'static,
+        O: Render,
+    {
+        // Keys of the current children.
+        // Always has the same length as `Inner::children`.
+        let mut keys = Vec::<K>::new();
+
+        Self::spawn(signal, fallback, move |state, parent, patch| {
+            let render = |value: &T| render(value).render();
+
+            match patch {
+                VecDiff::Replace { values } => {
+                    let new_keys: Vec<K> = values.iter().map(&key).collect();
+                    state.reconcile_keyed(parent, &keys, &new_keys, |index| {
+                        render(&values[index]).into_retained()
+                    });
+                    keys = new_keys;
+                }
+                VecDiff::InsertAt { index, value } => {
+                    let index = state.insert_child(parent, index, render(&value).into_retained());
+                    keys.insert(index, key(&value));
+                }
+                VecDiff::UpdateAt { index, value } => {
+                    if state.update_child(parent, index, render(&value)) {
+                        keys[index] = key(&value);
+                    }
+                }
+                VecDiff::RemoveAt { index } => {
+                    if state.remove_child(parent, index) {
+                        keys.remove(index);
+                    }
+                }
+                VecDiff::Move {
+                    old_index,
+                    new_index,
+                } => {
+                    if state.move_child(parent, old_index, new_index) {
+                        let k = keys.remove(old_index);
+                        keys.insert(new_index, k);
+                    }
+                }
+                VecDiff::Push { value } => {
+                    let len = state.children.len();
+                    state.insert_child(parent, len, render(&value).into_retained());
+                    keys.push(key(&value));
+                }
+                VecDiff::Pop {} => {
+                    if let Some(index) = state.children.len().checked_sub(1) {
+                        state.remove_child(parent, index);
+                        keys.pop();
+                    }
+                }
+                VecDiff::Clear {} => {
+                    state.replace_children(parent, Vec::new());
+                    keys.clear();
+                }
+            }
+        })
+    }
+
+    fn spawn<T, S, F>(signal: S, fallback: Option<View>, mut apply: F) -> Self
+    where
+        S: SignalVec<Item = T> + 'static,
+        F: FnMut(&mut Inner, &DomNode, VecDiff<T>) + 'static,
     {
         let (handle, reg) = AbortHandle::new_pair();
         let mut inner = Box::new(Inner {
-            marker: create_empty_node(),
+            marker: DomNode::null(),
             children: Vec::new(),
-            fallback: fallback.map(|f| f.into_retained()),
+            // The fallback is not necessarily inserted, so it must not adopt
+            // nodes while hydrating.
+            fallback: hydration::detached(|| fallback.map(|f| f.into_retained())),
+            fallback_visible: false,
             _abort: AbortGuard::new(handle),
             parent: None,
+            transition: None,
+            leaving: Vec::new(),
+            rendered: false,
         });
 
+        // While hydrating, the current values are rendered immediately to
+        // adopt the nodes in the same order they were created on the server,
+        // where the marker is inserted after the children.
+        // The parent is not known yet, the nodes are already in place though.
+        let mut signal = Box::pin(signal);
+        if hydration::is_hydrating() {
+            if let Some(diff) = hydration::poll_vec_now(signal.as_mut()) {
+                apply(&mut inner, &DomNode::null(), diff);
+                inner.rendered = true;
+            }
+        }
+        inner.marker = create_empty_node();
+
         let f = {
             // SAFETY:
             // Creates a 'static reference to the inner data.
@@ -98,14 +288,13 @@ impl SignalVecView {
             // dropped (since it will be aborted) beforehand.
             let state = unsafe { &mut *(inner.as_mut() as *mut Inner) };
 
-            let mut fallback_visible = false;
-
             signal.for_each(move |patch| {
-                let parent = if let Some(p) = &state.parent {
+                // The parent is temporarily taken out of the state to allow
+                // passing it to the mutating methods.
+                let parent = if let Some(p) = state.parent.take() {
                     p
                 } else if let Some(p) = state.marker.parent_node() {
-                    state.parent = Some(p);
-                    state.parent.as_ref().unwrap()
+                    p
                 } else {
                     tracing::error!(
                         "ViewSignalVec received an update but is not attached to a parent!"
@@ -113,120 +302,16 @@ impl SignalVecView {
                     return std::future::ready(());
                 };
 
-
-                // TODO use custom binding for efficiency?
-
-                match patch {
-                    VecDiff::Replace { values } => {
-                        state.children.drain(..).for_each(|child| {
-                            child.remove_from_parent(parent);
-                        });
-
-                        if values.is_empty() {
-                            if !fallback_visible {
-                                if let Some(e) = state.fallback.as_ref() {
-                                    e.insert_before(parent, &state.marker);
-                                    fallback_visible = true;
-                                }
-                            }
-                        } else {
-                            if fallback_visible {
-                                state.fallback.as_ref().unwrap().remove_from_parent(parent);
-                                fallback_visible = false;
-                            }
-
-                            for value in values {
-                                let rendered = render(&value).render().into_retained();
-                                rendered.insert_before(parent, &state.marker);
-                                state.children.push(rendered);
-                            }
-                        }
-                    }
-                    VecDiff::InsertAt { index, value } => {
-                        if fallback_visible {
-                            state.fallback.as_ref().unwrap().remove_from_parent(parent);
-                            fallback_visible = false;
-                        }
-
-                        let new_child = render(&value).render().into_retained();
-
-                        if let Some(current) = state.children.get(index) {
-                            current.prepend_before_self(parent, &new_child);
-                            state.children.insert(index, new_child);
-                        } else {
-                            tracing::warn!("VecDiff::InsertAt with invalid index {index} - exceeds current length of {}", state.children.len());
-                            new_child.insert_before(parent, &state.marker);
-                            state.children.push(new_child);
-                        }
-                    }
-                    VecDiff::UpdateAt { index, value } => {
-                        let new_child = render(&value).render();
-                        if let Some(old) = state.children.get_mut(index) {
-
-                            let mut tmp = RetainedView::Placeholder(JsValue::NULL.unchecked_into());
-                            std::mem::swap(old, &mut tmp);
-
-                            let retained = tmp.replace_with(parent, new_child);
-                            *old = retained;
-                        } else {
-                            tracing::warn!("invalid VecDiff::UpdateAt: index {index} does not exist");
-                        }
-                    }
-                    VecDiff::RemoveAt { index } => {
-                        if index < state.children.len() {
-                            let old = state.children.remove(index);
-                            old.remove_from_parent(parent);
-                        }
-
-                        if state.children.is_empty() && !fallback_visible {
-                            if let Some(e) = state.fallback.as_ref() {
-                                e.insert_before(parent, &state.marker);
-                                fallback_visible = true;
-                            }
-                        }
-                    }
-                    VecDiff::Move {
-                        old_index: _,
-                        new_index: _,
-                    } => {
-                        todo!();
-
-                    }
-                    VecDiff::Push { value } => {
-                        let child = render(&value).render().into_retained();
-                        child.insert_before(parent, &state.marker);
-                        state.children.push(child);
-
-                        if fallback_visible {
-                            state.fallback.as_ref().unwrap().remove_from_parent(parent);
-                            fallback_visible = false;
-                        }
-                    }
-                    VecDiff::Pop {} => {
-                        if let Some(old) = state.children.pop() {
-                            old.remove_from_parent(parent);
-                        }
-
-                        if state.children.is_empty() && !fallback_visible {
-                            if let Some(e) = state.fallback.as_ref() {
-                                e.insert_before(parent, &state.marker);
-                                fallback_visible = true;
-                            }
-                        }
-                    }
-                    VecDiff::Clear {} => {
-                        state.children.drain(..).for_each(|child| {
-                            child.remove_from_parent(parent);
-                        });
-
-                        if !fallback_visible {
-                            if let Some(e) = state.fallback.as_ref() {
-                                e.insert_before(parent, &state.marker);
-                                fallback_visible = true;
-                            }
-                        }
-                    }
+                let positions = state
+                    .transition
+                    .as_ref()
+                    .and_then(|t| t.measure(&state.children));
+                apply(state, &parent, patch);
+                if let (Some(transition), Some(positions)) = (&state.transition, positions) {
+                    transition.play_moves(positions);
                 }
+                state.rendered = true;
+                state.parent = Some(parent);
 
                 std::future::ready(())
             })
@@ -240,3 +325,273 @@ impl SignalVecView {
         Self(inner)
     }
 }
+
+impl Inner {
+    /// The transition for changes, if they are animated.
+    fn active_transition(&self) -> Option<&Transition> {
+        self.transition
+            .as_ref()
+            .filter(|t| self.rendered || t.animates_initial())
+    }
+
+    fn enter(&self, child: &RetainedView) {
+        if let Some(transition) = self.active_transition() {
+            transition.enter(child);
+        }
+    }
+
+    /// Remove a child, after its leave transition.
+    fn leave(&mut self, parent: &DomNode, child: RetainedView) {
+        match self.active_transition() {
+            Some(transition) => {
+                let leaving = transition.leave(parent, child);
+                self.leaving.retain(|leaving| !leaving.is_finished());
+                self.leaving.push(leaving);
+            }
+            None => child.remove_from_parent(parent),
+        }
+    }
+
+    fn show_fallback_if_empty(&mut self, parent: &DomNode) {
+        if self.children.is_empty() && !self.fallback_visible {
+            if let Some(e) = self.fallback.as_ref() {
+                e.insert_before(parent, &self.marker);
+                self.fallback_visible = true;
+            }
+        }
+    }
+
+    fn hide_fallback(&mut self, parent: &DomNode) {
+        if self.fallback_visible {
+            self.fallback.as_ref().unwrap().remove_from_parent(parent);
+            self.fallback_visible = false;
+        }
+    }
+
+    /// Insert `child` before the child at `index`.
+    ///
+    /// Returns the index the child was actually inserted at.
+    fn insert_child(&mut self, parent: &DomNode, index: usize, child: RetainedView) -> usize {
+        self.hide_fallback(parent);
+
+        self.enter(&child);
+        if let Some(current) = self.children.get(index) {
+            current.prepend_before_self(parent, &child);
+            self.children.insert(index, child);
+            index
+        } else {
+            if index > self.children.len() {
+                tracing::warn!(
+                    "VecDiff::InsertAt with invalid index {index} - exceeds current length of {}",
+                    self.children.len()
+                );
+            }
+            child.insert_before(parent, &self.marker);
+            self.children.push(child);
+            self.children.len() - 1
+        }
+    }
+
+    fn update_child(&mut self, parent: &DomNode, index: usize, view: View) -> bool {
+        if self.active_transition().is_some() && index < self.children.len() {
+            let new = view.into_retained();
+            self.children[index].prepend_before_self(parent, &new);
+            self.enter(&new);
+            let old = std::mem::replace(&mut self.children[index], new);
+            self.leave(parent, old);
+            true
+        } else if let Some(old) = self.children.get_mut(index) {
+            old.replace_with_mut(parent, view);
+            true
+        } else {
+            tracing::warn!("invalid VecDiff::UpdateAt: index {index} does not exist");
+            false
+        }
+    }
+
+    fn remove_child(&mut self, parent: &DomNode, index: usize) -> bool {
+        if index < self.children.len() {
+            let old = self.children.remove(index);
+            self.leave(parent, old);
+            self.show_fallback_if_empty(parent);
+            true
+        } else {
+            tracing::warn!("invalid VecDiff::RemoveAt: index {index} does not exist");
+            false
+        }
+    }
+
+    fn move_child(&mut self, parent: &DomNode, old_index: usize, new_index: usize) -> bool {
+        if old_index >= self.children.len() || new_index >= self.children.len() {
+            tracing::warn!(
+                "invalid VecDiff::Move: {old_index} => {new_index} exceeds current length of {}",
+                self.children.len()
+            );
+            return false;
+        }
+
+        let child = self.children.remove(old_index);
+
+        // Move the existing DOM nodes instead of re-rendering to retain
+        // element state like focus.
+        if let Some(next) = self.children.get(new_index) {
+            next.prepend_before_self(parent, &child);
+        } else {
+            child.insert_before(parent, &self.marker);
+        }
+        self.children.insert(new_index, child);
+        true
+    }
+
+    fn replace_children(&mut self, parent: &DomNode, children: Vec<RetainedView>) {
+        for child in std::mem::take(&mut self.children) {
+            self.leave(parent, child);
+        }
+
+        if !children.is_empty() {
+            self.hide_fallback(parent);
+        }
+        for child in &children {
+            self.enter(child);
+            child.insert_before(parent, &self.marker);
+        }
+        self.children = children;
+
+        self.show_fallback_if_empty(parent);
+    }
+
+    /// Replace the children with a new list of keyed children.
+    ///
+    /// Children with a key that is present in both lists are retained and
+    /// only moved if required.
+    /// `render` is only invoked for new keys.
+    fn reconcile_keyed<K, R>(
+        &mut self,
+        parent: &DomNode,
+        old_keys: &[K],
+        new_keys: &[K],
+        mut render: R,
+    ) where
+        K: Eq + std::hash::Hash,
+        R: FnMut(usize) -> RetainedView,
+    {
+        debug_assert_eq!(old_keys.len(), self.children.len());
+
+        // Map of keys to old indices.
+        // Duplicate keys are matched in order.
+        let mut old_indices = HashMap::<&K, Vec<usize>>::new();
+        for (index, key) in old_keys.iter().enumerate().rev() {
+            old_indices.entry(key).or_default().push(index);
+        }
+
+        let mut old_children: Vec<Option<RetainedView>> =
+            self.children.drain(..).map(Some).collect();
+
+        // For each new child: the old index if the child is retained.
+        let mut sources = Vec::<Option<usize>>::with_capacity(new_keys.len());
+        let mut children = Vec::with_capacity(new_keys.len());
+        for (index, key) in new_keys.iter().enumerate() {
+            let old_index = old_indices.get_mut(key).and_then(|indices| indices.pop());
+            if let Some(old_index) = old_index {
+                children.push(old_children[old_index].take().unwrap());
+            } else {
+                children.push(render(index));
+            }
+            sources.push(old_index);
+        }
+
+        for child in old_children.into_iter().flatten() {
+            self.leave(parent, child);
+        }
+
+        if !children.is_empty() {
+            self.hide_fallback(parent);
+        }
+
+        // Retained children that are part of the longest increasing
+        // subsequence of old indices are already in the correct relative
+        // order and don't need to be moved.
+        let stable = longest_increasing_subsequence(&sources);
+
+        for index in (0..children.len()).rev() {
+            if sources[index].is_some() && stable[index] {
+                continue;
+            }
+            let child = &children[index];
+            if sources[index].is_none() {
+                self.enter(child);
+            }
+            if let Some(next) = children.get(index + 1) {
+                next.prepend_before_self(parent, child);
+            } else {
+                child.insert_before(parent, &self.marker);
+            }
+        }
+
+        self.children = children;
+        self.show_fallback_if_empty(parent);
+    }
+}
+
+/// Compute the longest strictly increasing subsequence of the `Some` values.
+///
+/// Returns a flag for each input item that is `true` if the item is part of
+/// the subsequence.
+fn longest_increasing_subsequence(items: &[Option<usize>]) -> Vec<bool> {
+    // Index (into items) of the smallest tail value of subsequences of
+    // each length.
+    let mut tails = Vec::<usize>::new();
+    // Predecessor of each item in the subsequence.
+    let mut predecessors = vec![None; items.len()];
+
+    for (index, value) in items.iter().enumerate() {
+        let value = match value {
+            Some(v) => *v,
+            None => continue,
+        };
+        let pos = tails.partition_point(|tail| items[*tail].unwrap() < value);
+        if pos > 0 {
+            predecessors[index] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(index);
+        } else {
+            tails[pos] = index;
+        }
+    }
+
+    let mut flags = vec![false; items.len()];
+    let mut current = tails.last().copied();
+    while let Some(index) = current {
+        flags[index] = true;
+        current = predecessors[index];
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lis(items: &[Option<usize>]) -> Vec<usize> {
+        longest_increasing_subsequence(items)
+            .into_iter()
+            .zip(items)
+            .filter(|(flag, _)| *flag)
+            .map(|(_, item)| item.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(lis(&[]), Vec::<usize>::new());
+        assert_eq!(lis(&[None, None]), Vec::<usize>::new());
+        assert_eq!(lis(&[Some(0), Some(1), Some(2)]), vec![0, 1, 2]);
+        assert_eq!(lis(&[Some(2), Some(1), Some(0)]), vec![0]);
+        assert_eq!(lis(&[Some(1), Some(2), Some(0)]), vec![1, 2]);
+        assert_eq!(
+            lis(&[Some(3), None, Some(0), Some(1), None, Some(4), Some(2)]),
+            vec![0, 1, 2]
+        );
+    }
+}
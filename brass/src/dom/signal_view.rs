@@ -1,9 +1,18 @@
+use std::pin::Pin;
+
 use futures::future::{AbortHandle, Abortable};
 use futures_signals::signal::{Signal, SignalExt};
 
-use crate::context::AppContext;
+use crate::{
+    context::AppContext,
+    web::{hydration, DomNode},
+};
 
-use super::{view::RetainedView, AbortGuard, View};
+use super::{
+    transition::{Leaving, Transition},
+    view::RetainedView,
+    AbortGuard, View,
+};
 
 /// A view that is backed by a [`Signal`].
 ///
@@ -15,52 +24,113 @@ pub struct SignalView(Box<Inner>);
 struct Inner {
     current: RetainedView,
     _abort: AbortGuard,
-    parent: Option<web_sys::Node>,
+    transition: Option<Transition>,
+    /// Previous views that wait for their leave transition.
+    leaving: Vec<Leaving>,
+    /// Whether the initial value was rendered.
+    rendered: bool,
+}
+
+impl Inner {
+    fn finish_leaving(&mut self) {
+        for leaving in self.leaving.drain(..) {
+            leaving.finish();
+        }
+    }
 }
 
 impl SignalView {
-    pub(crate) fn replace_with(self, parent: &web_sys::Node, new_view: View) -> RetainedView {
+    pub(crate) fn replace_with(mut self, parent: &DomNode, new_view: View) -> RetainedView {
+        self.0.finish_leaving();
         self.0.current.replace_with(parent, new_view)
     }
 
-    pub(crate) fn replace(&self, parent: &web_sys::Node, old_node: &web_sys::Node) {
-        let placeholder = self.0.current.as_placeholder().unwrap();
-        parent.replace_child(placeholder, old_node).unwrap();
+    /// Animate changes of the view, see [`Transition`].
+    pub(crate) fn set_transition(&mut self, transition: Transition) {
+        self.0.transition = Some(transition);
+    }
+
+    pub(crate) fn current(&self) -> &RetainedView {
+        &self.0.current
     }
 
-    pub(crate) fn remove_from_parent(&self, parent: &web_sys::Node) {
+    pub(crate) fn replace(&self, parent: &DomNode, old_node: &DomNode) {
+        if let Some(placeholder) = self.0.current.as_placeholder() {
+            parent.replace_child(placeholder, old_node);
+        } else {
+            self.0.current.insert_before(parent, old_node);
+            parent.remove_child(old_node);
+        }
+    }
+
+    pub(crate) fn remove_from_parent(&self, parent: &DomNode) {
+        for leaving in &self.0.leaving {
+            leaving.finish();
+        }
         self.0.current.remove_from_parent(parent);
     }
 
-    pub(crate) fn prepend_before_self(&self, parent: &web_sys::Node, new: &RetainedView) {
+    pub(crate) fn prepend_before_self(&self, parent: &DomNode, new: &RetainedView) {
         self.0.current.prepend_before_self(parent, new);
     }
 
-    pub(crate) fn insert_before(&self, parent: &web_sys::Node, before: &web_sys::Node) {
+    pub(crate) fn insert_before(&self, parent: &DomNode, before: &DomNode) {
+        for leaving in &self.0.leaving {
+            leaving.finish();
+        }
         self.0.current.insert_before(parent, before);
     }
 
-    pub(crate) fn attach(&self, parent: &web_sys::Node) {
-        parent
-            .append_child(
-                self.0
-                    .current
-                    .as_placeholder()
-                    .expect("Can't attach a ViewSignal after it was initialized!"),
-            )
-            .unwrap();
+    pub(crate) fn attach(&self, parent: &DomNode) {
+        self.0.current.append_to(parent);
+    }
+
+    pub(crate) fn parent_node(&self) -> Option<DomNode> {
+        self.0.current.parent_node()
     }
 
     pub(crate) fn new<T, S>(signal: S) -> Self
+    where
+        T: Into<View>,
+        S: Signal<Item = T> + 'static,
+    {
+        // While hydrating, the current value is rendered immediately to adopt
+        // the nodes in the same order they were created on the server.
+        let mut signal = Box::pin(signal);
+        let current = if hydration::is_hydrating() {
+            hydration::poll_now(signal.as_mut()).map(|view| view.into().into_retained())
+        } else {
+            None
+        };
+        Self::from_parts(current, signal)
+    }
+
+    /// A view that starts out as `initial`, which was already rendered, and
+    /// is replaced by the values of `signal`.
+    ///
+    /// `signal` must not start with the value `initial` was rendered from.
+    pub(crate) fn with_initial<T, S>(initial: View, signal: S) -> Self
+    where
+        T: Into<View>,
+        S: Signal<Item = T> + 'static,
+    {
+        Self::from_parts(Some(initial.into_retained()), Box::pin(signal))
+    }
+
+    fn from_parts<T, S>(current: Option<RetainedView>, signal: Pin<Box<S>>) -> Self
     where
         T: Into<View>,
         S: Signal<Item = T> + 'static,
     {
         let (handle, reg) = AbortHandle::new_pair();
+
+        let rendered = current.is_some();
         let mut inner = Box::new(Inner {
-            current: RetainedView::new_placeholder(),
+            current: current.unwrap_or_else(RetainedView::new_placeholder),
             _abort: AbortGuard::new(handle),
-            parent: None,
+            transition: None,
+            leaving: Vec::new(),
+            rendered,
         });
 
         let f = {
@@ -75,20 +145,30 @@ impl SignalView {
             let state = unsafe { &mut *(inner.as_mut() as *mut Inner) };
 
             signal.for_each(move |view| {
-                let parent = if let Some(p) = &state.parent {
-                    p
-                } else if let Some(p) = state.current.as_placeholder().and_then(|p| p.parent_node())
-                {
-                    state.parent = Some(p);
-                    state.parent.as_ref().unwrap()
-                } else {
-                    tracing::error!(
-                        "ViewSignal received an update but is not attached to a parent!"
-                    );
+                // Not cached, since the view can be moved, for example out of
+                // a suspense that is pending again. Retained views always
+                // occupy a node, so the current one knows the parent.
+                let Some(parent) = state.current.parent_node() else {
+                    // Detached, for example inside of a pending suspense.
+                    // The new view is inserted once the view is attached.
+                    state.current = view.into().into_retained();
+                    state.rendered = true;
                     return std::future::ready(());
                 };
 
-                state.current.replace_with_mut(parent, view.into());
+                match &state.transition {
+                    Some(transition) if state.rendered || transition.animates_initial() => {
+                        // The old view stays in place until it left.
+                        let new = view.into().into_retained();
+                        state.current.prepend_before_self(&parent, &new);
+                        let old = std::mem::replace(&mut state.current, new);
+                        transition.enter(&state.current);
+                        state.leaving.retain(|leaving| !leaving.is_finished());
+                        state.leaving.push(transition.leave(&parent, old));
+                    }
+                    _ => state.current.replace_with_mut(&parent, view.into()),
+                }
+                state.rendered = true;
                 std::future::ready(())
             })
         };
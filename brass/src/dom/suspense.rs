@@ -0,0 +1,196 @@
+use std::rc::Rc;
+
+use futures_signals::signal::{Mutable, SignalExt};
+
+use crate::{
+    context::AppContext,
+    error::RenderError,
+    web::{hydration, DomNode},
+};
+
+use super::{builder, view::RetainedView, ErrorBoundary, Render, Style, View};
+
+/// The state of a [`Suspense`], shared with the async work inside of it.
+pub(crate) struct SuspenseState {
+    /// The number of live [`PendingGuard`]s.
+    pending: Mutable<usize>,
+}
+
+/// Marks the closest [`Suspense`] as loading until it is dropped.
+///
+/// Create one when starting async work that the view depends on, and move it
+/// into the future. Does nothing outside of a `Suspense`.
+/// [`super::ApplyFuture`] does this for its future.
+#[must_use = "the suspense is only pending while the guard is alive"]
+pub struct PendingGuard(Option<Rc<SuspenseState>>);
+
+impl PendingGuard {
+    pub fn new() -> Self {
+        Self::with_state(AppContext::scope().suspense)
+    }
+
+    /// Mark `state` as pending, for work that starts outside of the scope of
+    /// its suspense.
+    pub(crate) fn with_state(state: Option<Rc<SuspenseState>>) -> Self {
+        if let Some(state) = &state {
+            *state.pending.lock_mut() += 1;
+        }
+        Self(state)
+    }
+}
+
+impl Default for PendingGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if let Some(state) = &self.0 {
+            *state.pending.lock_mut() -= 1;
+        }
+    }
+}
+
+/// A view that shows a fallback while async work of its content is pending.
+///
+/// The content is rendered right away, so its futures start, but it is only
+/// shown once every [`PendingGuard`] created inside of it was dropped. If
+/// new work starts later, the fallback is shown again. Removing the view
+/// aborts the futures of the content.
+///
+/// The content and fallback are rendered into a `<div>` with
+/// `display: contents`. While hydrating, only the fallback adopts existing
+/// markup, so content that was already loaded on the server causes the app
+/// to be rendered from scratch.
+///
+/// ```
+/// use brass::dom::{builder::{div, p}, ApplyFuture, Suspense};
+/// use futures::channel::oneshot;
+///
+/// let (tx, rx) = oneshot::channel::<String>();
+/// let (app, root) = brass::launch_memory(|| {
+///     Suspense::new(
+///         || div().and(ApplyFuture(async { p().text(rx.await.unwrap()) })),
+///         || p().text("Loading..."),
+///     )
+/// });
+/// assert_eq!(root.find_element("p").unwrap().text_content(), "Loading...");
+///
+/// app.with(|| tx.send("Hello".to_string()).unwrap());
+/// assert_eq!(root.find_element("p").unwrap().text_content(), "Hello");
+/// ```
+pub struct Suspense {
+    content: Box<dyn FnOnce() -> View>,
+    fallback: Box<dyn Fn() -> View>,
+}
+
+impl Suspense {
+    pub fn new<C, R, F, FR>(content: C, fallback: F) -> Self
+    where
+        C: FnOnce() -> R + 'static,
+        R: Render,
+        F: Fn() -> FR + 'static,
+        FR: Render,
+    {
+        Self {
+            content: Box::new(move || content().render()),
+            fallback: Box::new(move || fallback().render()),
+        }
+    }
+
+    /// Replace the content with `fallback` when it fails, see
+    /// [`ErrorBoundary`].
+    pub fn on_error<F, FR>(self, fallback: F) -> Self
+    where
+        F: FnOnce(RenderError) -> FR + 'static,
+        FR: Render,
+    {
+        let content = self.content;
+        Self {
+            content: Box::new(move || ErrorBoundary::new(content, fallback).render()),
+            fallback: self.fallback,
+        }
+    }
+}
+
+/// The part of a [`Suspense`] that is currently shown.
+struct Slot {
+    parent: DomNode,
+    content: RetainedView,
+    content_shown: bool,
+    fallback: Option<RetainedView>,
+    render_fallback: Box<dyn Fn() -> View>,
+}
+
+impl Slot {
+    fn update(&mut self, pending: bool) {
+        if pending {
+            if self.content_shown {
+                self.content.remove_from_parent(&self.parent);
+                self.content_shown = false;
+            }
+            if self.fallback.is_none() {
+                let fallback = (self.render_fallback)().into_retained();
+                fallback.append_to(&self.parent);
+                self.fallback = Some(fallback);
+            }
+        } else {
+            if let Some(fallback) = self.fallback.take() {
+                fallback.remove_from_parent(&self.parent);
+            }
+            if !self.content_shown {
+                self.content.append_to(&self.parent);
+                self.content_shown = true;
+            }
+        }
+    }
+}
+
+impl Render for Suspense {
+    fn render(self) -> View {
+        let state = Rc::new(SuspenseState {
+            pending: Mutable::new(0),
+        });
+
+        let mut wrapper = builder::div().style(Style::Display, "contents");
+
+        let mut scope = AppContext::scope();
+        scope.suspense = Some(state.clone());
+        // The content is moved in and out of the document, so it can't adopt
+        // markup.
+        let content = AppContext::with_scope(scope, || {
+            hydration::detached(|| (self.content)().into_retained())
+        });
+
+        let mut slot = Slot {
+            parent: wrapper.dom_node().clone(),
+            content,
+            content_shown: false,
+            fallback: None,
+            render_fallback: self.fallback,
+        };
+        slot.update(state.pending.get() > 0);
+
+        // The slot owns the content, so it is dropped with the future when
+        // the wrapper is removed.
+        let f = state
+            .pending
+            .signal()
+            .map(|pending| pending > 0)
+            .dedupe()
+            .for_each(move |pending| {
+                slot.update(pending);
+                async {}
+            });
+        wrapper.spawn_ui(f);
+        wrapper.into()
+    }
+}
+
+impl From<Suspense> for View {
+    fn from(suspense: Suspense) -> Self {
+        suspense.render()
+    }
+}
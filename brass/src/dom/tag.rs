@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 use super::TagBuilder;
 
 make_str_enum! {
@@ -79,7 +81,7 @@ make_str_enum! {
         Source = "source",
         Canvas = "canvas",
         NoScript = "noscript",
-        Script = "Script",
+        Script = "script",
         Del = "del",
         Ins = "ins",
         Caption = "caption",
@@ -208,6 +210,36 @@ make_str_enum! {
         Style = "style",
         View = "view",
         Placeholder = "placeholder",
+        // MathML elements
+        Math = "math",
+        Annotation = "annotation",
+        AnnotationXml = "annotation-xml",
+        Merror = "merror",
+        Mfrac = "mfrac",
+        Mi = "mi",
+        Mmultiscripts = "mmultiscripts",
+        Mn = "mn",
+        Mo = "mo",
+        Mover = "mover",
+        Mpadded = "mpadded",
+        Mphantom = "mphantom",
+        Mprescripts = "mprescripts",
+        Mroot = "mroot",
+        Mrow = "mrow",
+        Ms = "ms",
+        Mspace = "mspace",
+        Msqrt = "msqrt",
+        Mstyle = "mstyle",
+        Msub = "msub",
+        Msubsup = "msubsup",
+        Msup = "msup",
+        Mtable = "mtable",
+        Mtd = "mtd",
+        Mtext = "mtext",
+        Mtr = "mtr",
+        Munder = "munder",
+        Munderover = "munderover",
+        Semantics = "semantics",
     }
 }
 
@@ -216,3 +248,98 @@ impl Tag {
         TagBuilder::new(self)
     }
 }
+
+impl Tag {
+    /// The namespace of elements with this tag, if it is the same everywhere.
+    ///
+    /// Returns `None` for tags that exist in multiple namespaces, like `a`
+    /// or `title`. Those take the namespace of their parent.
+    pub fn fixed_namespace(self) -> Option<Namespace> {
+        use Tag::*;
+        match self {
+            A | Font | Script | Style | Title => None,
+            Animate | AnimateColor | AnimateMotion | AnimateTransform | Discard | Mpath | Set
+            | Circle | Ellipse | Line | Polygon | Polyline | Rect | Mesh | Path | Defs | G
+            | Marker | Mask | MissingGlyph | Pattern | Svg | Switch | Symbol | Desc | Metadata
+            | FeBlend | FeColorMatrix | FeComponentTransfer | FeComposite | FeConvolveMatrix
+            | FeDiffuseLighting | FeDisplacementMap | FeDropShadow | FeFlood | FeFuncA
+            | FeFuncB | FeFuncG | FeFuncR | FeGaussianBlur | FeImage | FeMerge | FeMergeNode
+            | FeMorphology | FeOffset | FeSpecularLighting | FeTile | FeTurbulence
+            | FeDistantLight | FePointLight | FeSpotLight | FontFace | FontFaceFormat
+            | FontFaceName | FontFaceSrc | FontFaceUri | HKern | VKern | LinearGradient
+            | MeshGradient | RadialGradient | Stop | Image | Use | Hatch | SolidColor
+            | AltGlyph | AltGlyphDef | AltGlyphItem | Glyph | GlyphRef | TextPath | Text | TRef
+            | TSpan | ClipPath | ColorProfile | Cursor | Filter | ForeignObject | HatchPath
+            | MeshPatch | MeshRow | View => Some(Namespace::Svg),
+            Math | Annotation | AnnotationXml | Merror | Mfrac | Mi | Mmultiscripts | Mn | Mo
+            | Mover | Mpadded | Mphantom | Mprescripts | Mroot | Mrow | Ms | Mspace | Msqrt
+            | Mstyle | Msub | Msubsup | Msup | Mtable | Mtd | Mtext | Mtr | Munder | Munderover
+            | Semantics => Some(Namespace::MathMl),
+            _ => Some(Namespace::Html),
+        }
+    }
+
+    /// The namespace of an element with this tag inside a `parent` namespace.
+    pub fn namespace_in(self, parent: Namespace) -> Namespace {
+        self.fixed_namespace().unwrap_or(parent)
+    }
+
+    /// The namespace that children of an element with this tag inherit.
+    pub fn child_namespace(self, namespace: Namespace) -> Namespace {
+        match self {
+            // Embeds regular HTML content.
+            Tag::ForeignObject => Namespace::Html,
+            _ => namespace,
+        }
+    }
+}
+
+/// The XML namespace of an element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    pub fn uri(self) -> &'static str {
+        match self {
+            Namespace::Html => "http://www.w3.org/1999/xhtml",
+            Namespace::Svg => "http://www.w3.org/2000/svg",
+            Namespace::MathMl => "http://www.w3.org/1998/Math/MathML",
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_NAMESPACE: Cell<Namespace> = const { Cell::new(Namespace::Html) };
+}
+
+/// The namespace that elements created right now inherit.
+pub(crate) fn current_namespace() -> Namespace {
+    CURRENT_NAMESPACE.with(Cell::get)
+}
+
+/// Create elements inside `f` as descendants of an element in `namespace`.
+///
+/// Most tags always have the same namespace, so `svg` and `path` are created
+/// as SVG elements everywhere. Tags like `a`, `title` or `style` exist in
+/// multiple namespaces and need to know the namespace of their parent.
+/// The `view!` macro does this automatically for the children of `svg`,
+/// `math` and `foreignObject` elements.
+///
+/// ```
+/// use brass::dom::{builder::tag, with_namespace, Namespace, Tag};
+///
+/// let icon = tag(Tag::Svg).tag(with_namespace(Namespace::Svg, || {
+///     tag(Tag::A).attr(brass::dom::Attr::Href, "/home")
+/// }));
+/// ```
+pub fn with_namespace<R>(namespace: Namespace, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT_NAMESPACE.with(|current| current.replace(namespace));
+    let out = f();
+    CURRENT_NAMESPACE.with(|current| current.set(previous));
+    out
+}
@@ -0,0 +1,333 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use futures::{channel::oneshot, future::select};
+use wasm_bindgen::JsCast;
+
+use crate::{
+    context::AppContext,
+    effect::{AnimationFrameFuture, TimeoutFuture},
+    web::{backend::EventTarget, elem_add_class, elem_remove_class, DomNode, DomStr},
+};
+
+use super::{view::RetainedView, Ev, EventOptions};
+
+/// Enter and leave animations for the children of signal views.
+///
+/// Like Vue transitions, animations are defined in CSS with classes derived
+/// from the name. For a transition named `fade`:
+///
+/// * Inserted elements get `fade-enter-from` and `fade-enter-active`. On the
+///   next frame `fade-enter-from` is replaced by `fade-enter-to`, and both
+///   other classes are removed once the element fires `transitionend` or
+///   `animationend`.
+/// * Removed elements get `fade-leave-from`, `fade-leave-active` and then
+///   `fade-leave-to` in the same way, and are only removed from the document
+///   once their transition ended.
+/// * With [`Self::moves`], list items that changed their position are
+///   animated from the old position with `fade-move`.
+///
+/// The timeout removes elements that never fire an end event, for example
+/// because they have no transition.
+///
+/// Documents without an event loop, like in-memory documents, are never
+/// rendered, so there views are inserted and removed right away.
+///
+/// ```
+/// use brass::dom::{builder::{div, p}, Transition};
+/// use futures_signals::signal_vec::MutableVec;
+///
+/// let items = MutableVec::new_with_values(vec![1, 2]);
+/// let (app, root) = brass::launch_memory(|| {
+///     div().signal_vec_keyed_with_transition(
+///         items.signal_vec_cloned(),
+///         |item| *item,
+///         |item| p().text(item.to_string()),
+///         Transition::new("fade").moves(true),
+///     )
+/// });
+///
+/// app.with(|| items.lock_mut().remove(0));
+/// assert_eq!(root.inner_html(), "<div><p>2</p><!----></div>");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Transition {
+    name: Cow<'static, str>,
+    timeout: Duration,
+    appear: bool,
+    moves: bool,
+}
+
+impl Transition {
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            timeout: Duration::from_secs(1),
+            appear: false,
+            moves: false,
+        }
+    }
+
+    /// The maximum time to wait for the end of a transition, one second by
+    /// default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Also animate the children that are rendered initially.
+    pub fn appear(mut self, appear: bool) -> Self {
+        self.appear = appear;
+        self
+    }
+
+    /// Animate list items that move, with the `{name}-move` class.
+    ///
+    /// Only supported in the browser.
+    pub fn moves(mut self, moves: bool) -> Self {
+        self.moves = moves;
+        self
+    }
+
+    pub(crate) fn animates_initial(&self) -> bool {
+        self.appear
+    }
+
+    fn class(&self, stage: &str) -> String {
+        format!("{}-{stage}", self.name)
+    }
+
+    /// Animate a view that was just inserted.
+    pub(crate) fn enter(&self, view: &RetainedView) {
+        if !AppContext::backend().has_event_loop() {
+            return;
+        }
+        let elements = elements(view);
+        if elements.is_empty() {
+            return;
+        }
+
+        let [from, active, to] = ["enter-from", "enter-active", "enter-to"].map(|s| self.class(s));
+        add_class(&elements, &from);
+        add_class(&elements, &active);
+
+        let timeout = self.timeout;
+        AppContext::spawn_custom_executor_unguarded(async move {
+            next_frame().await;
+            remove_class(&elements, &from);
+            add_class(&elements, &to);
+            transitions_end(&elements, timeout).await;
+            remove_class(&elements, &active);
+            remove_class(&elements, &to);
+        });
+    }
+
+    /// Animate a view that is removed, and remove it from `parent`
+    /// afterwards.
+    pub(crate) fn leave(&self, parent: &DomNode, view: RetainedView) -> Leaving {
+        let elements = elements(&view);
+        let leaving = Leaving(Rc::new(RefCell::new(Some((parent.clone(), view)))));
+        if elements.is_empty() || !AppContext::backend().has_event_loop() {
+            leaving.finish();
+            return leaving;
+        }
+
+        let [from, active, to] = ["leave-from", "leave-active", "leave-to"].map(|s| self.class(s));
+        add_class(&elements, &from);
+        add_class(&elements, &active);
+
+        let timeout = self.timeout;
+        let slot = leaving.clone();
+        AppContext::spawn_custom_executor_unguarded(async move {
+            next_frame().await;
+            remove_class(&elements, &from);
+            add_class(&elements, &to);
+            transitions_end(&elements, timeout).await;
+            slot.finish();
+        });
+        leaving
+    }
+
+    /// Record the positions of the elements of `views`, if moves are
+    /// animated.
+    pub(crate) fn measure<'a>(
+        &self,
+        views: impl IntoIterator<Item = &'a RetainedView>,
+    ) -> Option<Positions> {
+        if !self.moves || !AppContext::backend().has_event_loop() {
+            return None;
+        }
+        let positions = views
+            .into_iter()
+            .flat_map(elements)
+            .filter_map(|node| {
+                let elem = node.as_web()?.dyn_ref::<web_sys::HtmlElement>()?.clone();
+                let rect = elem.get_bounding_client_rect();
+                Some((elem, rect.left(), rect.top()))
+            })
+            .collect();
+        Some(Positions(positions))
+    }
+
+    /// Animate the elements that moved since they were measured, by
+    /// transitioning from their old position to the new one.
+    pub(crate) fn play_moves(&self, positions: Positions) {
+        let mut moved = Vec::new();
+        for (elem, left, top) in positions.0 {
+            if !elem.is_connected() {
+                continue;
+            }
+            let rect = elem.get_bounding_client_rect();
+            let (dx, dy) = (left - rect.left(), top - rect.top());
+            if dx == 0.0 && dy == 0.0 {
+                continue;
+            }
+            let style = elem.style();
+            style
+                .set_property("transform", &format!("translate({dx}px, {dy}px)"))
+                .unwrap();
+            style.set_property("transition-duration", "0s").unwrap();
+            moved.push(elem);
+        }
+        let Some(first) = moved.first() else {
+            return;
+        };
+
+        // Force a layout, so the elements are rendered at the old position
+        // before transitioning to the new one.
+        first.get_bounding_client_rect();
+
+        // The move class defines the transition to the new position.
+        let class = self.class("move");
+        let elements: Vec<DomNode> = moved
+            .iter()
+            .map(|elem| DomNode::Web(elem.clone().unchecked_into()))
+            .collect();
+        add_class(&elements, &class);
+        for elem in &moved {
+            let style = elem.style();
+            style.remove_property("transform").unwrap();
+            style.remove_property("transition-duration").unwrap();
+        }
+
+        let timeout = self.timeout;
+        AppContext::spawn_custom_executor_unguarded(async move {
+            transitions_end(&elements, timeout).await;
+            remove_class(&elements, &class);
+        });
+    }
+}
+
+/// Element positions recorded by [`Transition::measure`].
+pub(crate) struct Positions(Vec<(web_sys::HtmlElement, f64, f64)>);
+
+/// A view that stays in the document until its leave transition ended.
+#[derive(Clone)]
+pub(crate) struct Leaving(Rc<RefCell<Option<(DomNode, RetainedView)>>>);
+
+impl Leaving {
+    /// Remove the view right away.
+    pub(crate) fn finish(&self) {
+        let leaving = self.0.borrow_mut().take();
+        if let Some((parent, view)) = leaving {
+            view.remove_from_parent(&parent);
+        }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.0.borrow().is_none()
+    }
+}
+
+/// The top level elements of a view.
+fn elements(view: &RetainedView) -> Vec<DomNode> {
+    fn collect(view: &RetainedView, out: &mut Vec<DomNode>) {
+        match view {
+            RetainedView::Node(node) if is_element(node.node()) => out.push(node.node().clone()),
+            RetainedView::Fragment(items) => {
+                for item in items {
+                    collect(item, out);
+                }
+            }
+            RetainedView::Signal(sig) => collect(sig.current(), out),
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(view, &mut out);
+    out
+}
+
+fn is_element(node: &DomNode) -> bool {
+    match node {
+        DomNode::Web(node) => node.node_type() == web_sys::Node::ELEMENT_NODE,
+        DomNode::Memory(node) => node.tag().is_some(),
+    }
+}
+
+fn add_class(elements: &[DomNode], class: &str) {
+    for elem in elements {
+        elem_add_class(elem, &DomStr::Str(class));
+    }
+}
+
+fn remove_class(elements: &[DomNode], class: &str) {
+    for elem in elements {
+        elem_remove_class(elem, &DomStr::Str(class));
+    }
+}
+
+/// Wait until the browser rendered the current classes.
+async fn next_frame() {
+    // The first frame can still run before the classes are rendered.
+    AnimationFrameFuture::new().await;
+    AnimationFrameFuture::new().await;
+}
+
+/// Wait until every element fired `transitionend` or `animationend`, or the
+/// timeout passed.
+async fn transitions_end(elements: &[DomNode], timeout: Duration) {
+    let (tx, rx) = oneshot::channel::<()>();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let remaining = Rc::new(Cell::new(elements.len()));
+
+    let mut _listeners = Vec::new();
+    for elem in elements {
+        let ended = Rc::new(Cell::new(false));
+        for event in [Ev::TransitionEnd, Ev::AnimationEnd] {
+            let target = EventTarget::from(elem);
+            let (elem, ended, remaining, tx) =
+                (elem.clone(), ended.clone(), remaining.clone(), tx.clone());
+            let callback = move |event: web_sys::Event| {
+                // End events of descendants bubble up.
+                if let Some(elem) = elem.as_web() {
+                    if event.target().as_ref() != Some(elem.as_ref()) {
+                        return;
+                    }
+                }
+                if ended.replace(true) {
+                    return;
+                }
+                remaining.set(remaining.get() - 1);
+                if remaining.get() == 0 {
+                    if let Some(tx) = tx.borrow_mut().take() {
+                        tx.send(()).ok();
+                    }
+                }
+            };
+            _listeners.push(AppContext::create_event_listener(
+                event,
+                EventOptions::new(),
+                callback,
+                target,
+            ));
+        }
+    }
+
+    select(rx, TimeoutFuture::new(timeout)).await;
+}
@@ -1,6 +1,4 @@
-use wasm_bindgen::{JsCast, JsValue};
-
-use crate::web::create_empty_node;
+use crate::web::{create_empty_node, DomNode};
 
 use super::{signal_vec_view::SignalVecView, signal_view::SignalView, Fragment, Node};
 
@@ -25,10 +23,12 @@ impl From<()> for View {
 }
 
 impl View {
-    pub(crate) fn attach(&self, parent: &web_sys::Element) {
+    pub(crate) fn attach(&self, parent: &DomNode) {
         match self {
             Self::Empty => {}
-            Self::Signal(_inner) => {}
+            Self::Signal(sig) => {
+                sig.attach(parent);
+            }
             Self::Node(n) => {
                 n.attach(parent);
             }
@@ -80,17 +80,38 @@ impl View {
         match self {
             View::Empty => RetainedView::Placeholder(create_empty_node()),
             View::Node(n) => RetainedView::Node(n),
-            View::Fragment(frag) => RetainedView::Fragment(frag),
+            View::Fragment(frag) => {
+                // Every retained view must occupy at least one DOM node, so
+                // empty items are dropped and an empty fragment is replaced
+                // with a placeholder.
+                let items: Vec<_> = frag
+                    .items
+                    .into_iter()
+                    .filter(|item| !item.is_empty())
+                    .map(|item| item.into_retained())
+                    .collect();
+                if items.is_empty() {
+                    RetainedView::Placeholder(create_empty_node())
+                } else {
+                    RetainedView::Fragment(items)
+                }
+            }
             View::Signal(sig) => RetainedView::Signal(sig),
             View::SignalVec(v) => RetainedView::SignalVec(v),
         }
     }
 }
 
+/// A view that has been (or is about to be) inserted into the DOM.
+///
+/// Every variant occupies at least one DOM node, which allows using the first
+/// node as an anchor for insertions and replacements.
 pub(crate) enum RetainedView {
-    Placeholder(web_sys::Node),
+    Placeholder(DomNode),
     Node(Node),
-    Fragment(Fragment),
+    /// The items of a [`Fragment`].
+    /// Never empty.
+    Fragment(Vec<RetainedView>),
     Signal(SignalView),
     SignalVec(SignalVecView),
 }
@@ -100,17 +121,17 @@ impl RetainedView {
         Self::Placeholder(create_empty_node())
     }
 
-    pub fn replace_with_mut(&mut self, parent: &web_sys::Node, new: View) {
-        let mut tmp = Self::Placeholder(JsValue::NULL.unchecked_into());
+    pub fn replace_with_mut(&mut self, parent: &DomNode, new: View) {
+        let mut tmp = Self::Placeholder(DomNode::null());
         std::mem::swap(&mut tmp, self);
         *self = tmp.replace_with(parent, new);
     }
 
-    pub fn replace_with(self, parent: &web_sys::Node, new: View) -> Self {
+    pub fn replace_with(self, parent: &DomNode, new: View) -> Self {
         match (self, new) {
             (p @ Self::Placeholder(_), View::Empty) => p,
             (Self::Placeholder(p), View::Node(n)) => {
-                parent.replace_child(n.node(), &p).unwrap();
+                parent.replace_child(n.node(), &p);
                 Self::Node(n)
             }
             (Self::Placeholder(p), View::Signal(sig)) => {
@@ -123,11 +144,11 @@ impl RetainedView {
             }
             (Self::Node(n), View::Empty) => {
                 let placeholder = create_empty_node();
-                parent.replace_child(&placeholder, n.node()).unwrap();
+                parent.replace_child(&placeholder, n.node());
                 Self::Placeholder(placeholder)
             }
             (Self::Node(old), View::Node(new)) => {
-                parent.replace_child(new.node(), old.node()).unwrap();
+                parent.replace_child(new.node(), old.node());
                 Self::Node(new)
             }
             (Self::Node(n), View::Signal(sig)) => {
@@ -140,21 +161,27 @@ impl RetainedView {
             }
             (Self::Signal(sig), new) => sig.replace_with(parent, new),
             (Self::SignalVec(svec), new) => svec.replace_with(parent, new),
-            (Self::Fragment(_), _) => todo!(),
-            (_, View::Fragment(_)) => todo!(),
+            (old @ Self::Fragment(_), new) | (old, new @ View::Fragment(_)) => {
+                let new = new.into_retained();
+                old.prepend_before_self(parent, &new);
+                old.remove_from_parent(parent);
+                new
+            }
         }
     }
 
-    pub fn remove_from_parent(&self, parent: &web_sys::Node) {
+    pub fn remove_from_parent(&self, parent: &DomNode) {
         match self {
             RetainedView::Placeholder(p) => {
-                parent.remove_child(&p).unwrap();
+                parent.remove_child(p);
             }
             RetainedView::Node(n) => {
-                parent.remove_child(&n.node()).unwrap();
+                parent.remove_child(n.node());
             }
-            RetainedView::Fragment(_f) => {
-                todo!();
+            RetainedView::Fragment(items) => {
+                for item in items {
+                    item.remove_from_parent(parent);
+                }
             }
             RetainedView::Signal(sig) => {
                 sig.remove_from_parent(parent);
@@ -165,15 +192,19 @@ impl RetainedView {
         }
     }
 
-    pub(crate) fn insert_before(&self, parent: &web_sys::Node, before: &web_sys::Node) {
+    pub(crate) fn insert_before(&self, parent: &DomNode, before: &DomNode) {
         match self {
             RetainedView::Placeholder(p) => {
-                parent.insert_before(p, Some(before)).unwrap();
+                parent.insert_before(p, Some(before));
             }
             RetainedView::Node(n) => {
-                parent.insert_before(n.node(), Some(before)).unwrap();
+                parent.insert_before(n.node(), Some(before));
+            }
+            RetainedView::Fragment(items) => {
+                for item in items {
+                    item.insert_before(parent, before);
+                }
             }
-            RetainedView::Fragment(_) => todo!(),
             RetainedView::Signal(sig) => {
                 sig.insert_before(parent, before);
             }
@@ -183,7 +214,42 @@ impl RetainedView {
         }
     }
 
-    pub(crate) fn prepend_before_self(&self, parent: &web_sys::Node, new: &RetainedView) {
+    /// Append all nodes of the view to `parent`.
+    pub(crate) fn append_to(&self, parent: &DomNode) {
+        match self {
+            RetainedView::Placeholder(p) => {
+                parent.append_child(p);
+            }
+            RetainedView::Node(n) => {
+                n.attach(parent);
+            }
+            RetainedView::Fragment(items) => {
+                for item in items {
+                    item.append_to(parent);
+                }
+            }
+            RetainedView::Signal(sig) => {
+                sig.attach(parent);
+            }
+            RetainedView::SignalVec(svec) => {
+                svec.attach(parent);
+            }
+        }
+    }
+
+    /// The parent of the DOM nodes of this view, if attached.
+    pub(crate) fn parent_node(&self) -> Option<DomNode> {
+        match self {
+            RetainedView::Placeholder(p) => p.parent_node(),
+            RetainedView::Node(n) => n.node().parent_node(),
+            // Fragments are never empty.
+            RetainedView::Fragment(items) => items[0].parent_node(),
+            RetainedView::Signal(sig) => sig.parent_node(),
+            RetainedView::SignalVec(svec) => svec.parent_node(),
+        }
+    }
+
+    pub(crate) fn prepend_before_self(&self, parent: &DomNode, new: &RetainedView) {
         match &self {
             RetainedView::Placeholder(p) => {
                 new.insert_before(parent, p);
@@ -191,7 +257,10 @@ impl RetainedView {
             RetainedView::Node(n) => {
                 new.insert_before(parent, n.node());
             }
-            RetainedView::Fragment(_) => todo!(),
+            RetainedView::Fragment(items) => {
+                // Fragments are never empty.
+                items[0].prepend_before_self(parent, new);
+            }
             RetainedView::Signal(sig) => {
                 sig.prepend_before_self(parent, new);
             }
@@ -201,7 +270,7 @@ impl RetainedView {
         }
     }
 
-    // pub fn replace(&self, parent: &web_sys::Node, old_node: &web_sys::Node) {
+    // pub fn replace(&self, parent: &DomNode, old_node: &DomNode) {
     //     match self {
     //         RetainedView::Placeholder(p) => {
     //             parent.replace_child(&p, old_node).unwrap();
@@ -214,7 +283,7 @@ impl RetainedView {
     //     }
     // }
 
-    pub fn as_placeholder(&self) -> Option<&web_sys::Node> {
+    pub fn as_placeholder(&self) -> Option<&DomNode> {
         if let Self::Placeholder(v) = self {
             Some(v)
         } else {
@@ -1,10 +1,13 @@
-use futures::Future;
+use std::{cell::RefCell, rc::Rc};
+
+use futures::{future::LocalBoxFuture, Future, FutureExt};
+use futures_signals::signal::{Mutable, Signal, SignalExt};
 use wasm_bindgen::{prelude::Closure, JsCast};
 
 use crate::{
     context::{AppContext, EventHandlerRef},
-    dom::AbortGuard,
-    web::window,
+    dom::{AbortGuard, PendingGuard, SuspenseState},
+    web::{backend::EventTarget, hydration, window},
 };
 
 #[must_use]
@@ -20,13 +23,16 @@ pub fn spawn_guarded<F: Future<Output = ()> + 'static>(f: F) -> EffectGuard {
 
 #[must_use]
 pub struct TimeoutGuard {
-    pub(crate) _closure: Closure<dyn FnMut()>,
+    /// `None` if the timeout was never scheduled, eg in an in-memory document.
+    pub(crate) _closure: Option<Closure<dyn FnMut()>>,
     pub(crate) id: i32,
 }
 
 impl Drop for TimeoutGuard {
     fn drop(&mut self) {
-        window().clear_timeout_with_handle(self.id);
+        if self._closure.is_some() {
+            window().clear_timeout_with_handle(self.id);
+        }
     }
 }
 
@@ -62,15 +68,74 @@ impl std::future::Future for TimeoutFuture {
     }
 }
 
+#[must_use]
+pub struct AnimationFrameGuard {
+    /// `None` if the callback was never scheduled, eg in an in-memory document.
+    pub(crate) _closure: Option<Closure<dyn FnMut()>>,
+    pub(crate) id: i32,
+}
+
+impl Drop for AnimationFrameGuard {
+    fn drop(&mut self) {
+        if self._closure.is_some() {
+            window().cancel_animation_frame(self.id).ok();
+        }
+    }
+}
+
+/// Run `f` before the browser renders the next frame.
+///
+/// Like timers, frames never happen in an in-memory document.
+pub fn request_animation_frame(f: impl FnOnce() + 'static) -> AnimationFrameGuard {
+    AppContext::create_animation_frame(f)
+}
+
+pub struct AnimationFrameFuture {
+    _guard: AnimationFrameGuard,
+    receiver: futures::channel::oneshot::Receiver<()>,
+}
+
+impl AnimationFrameFuture {
+    pub fn new() -> Self {
+        let (tx, receiver) = futures::channel::oneshot::channel();
+
+        let _guard = request_animation_frame(move || {
+            tx.send(()).ok();
+        });
+
+        Self { _guard, receiver }
+    }
+}
+
+impl Default for AnimationFrameFuture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::future::Future for AnimationFrameFuture {
+    type Output = ();
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        std::pin::Pin::new(&mut self.receiver).poll(cx).map(|_| ())
+    }
+}
+
 #[must_use]
 pub struct IntervalGuard {
-    pub(crate) _closure: Closure<dyn FnMut()>,
+    /// `None` if the interval was never scheduled, eg in an in-memory document.
+    pub(crate) _closure: Option<Closure<dyn FnMut()>>,
     pub(crate) id: i32,
 }
 
 impl Drop for IntervalGuard {
     fn drop(&mut self) {
-        window().clear_interval_with_handle(self.id);
+        if self._closure.is_some() {
+            window().clear_interval_with_handle(self.id);
+        }
     }
 }
 
@@ -86,6 +151,19 @@ impl EventSubscription {
         target: web_sys::EventTarget,
         event: crate::dom::Ev,
         callback: F,
+    ) -> Self {
+        Self::subscribe_with_options(target, event, crate::dom::EventOptions::new(), callback)
+    }
+
+    /// Like [`Self::subscribe`], with listener options and modifiers.
+    ///
+    /// A passive `scroll` or `touchmove` subscription on the window, for
+    /// example, doesn't block scrolling.
+    pub fn subscribe_with_options<E: wasm_bindgen::JsCast + 'static, F: Fn(E) + 'static>(
+        target: web_sys::EventTarget,
+        event: crate::dom::Ev,
+        options: crate::dom::EventOptions,
+        callback: F,
     ) -> Self {
         let wrapped = move |event: web_sys::Event| {
             if let Ok(typed_ev) = event.dyn_into::<E>() {
@@ -93,7 +171,192 @@ impl EventSubscription {
             }
         };
 
-        let r = AppContext::create_event_listener(event, wrapped, target);
+        let r =
+            AppContext::create_event_listener(event, options, wrapped, EventTarget::Web(target));
         Self(r)
     }
 }
+
+/// Async data that is fetched for the values of a source signal.
+///
+/// Whenever the source changes, the fetcher is called with the new value and
+/// a still running fetch for an older value is aborted. While a fetch runs,
+/// [`Self::loading`] is `true` and the closest
+/// [`Suspense`](crate::dom::Suspense) shows its fallback. The previous value
+/// stays available until the new one arrives.
+///
+/// Fetches are aborted when the last clone of the resource and its signals
+/// are dropped. Must be created while an app is active, like in a render
+/// function.
+///
+/// ```
+/// use brass::{dom::builder::p, effect::Resource};
+/// use futures_signals::signal::{Mutable, SignalExt};
+///
+/// let id = Mutable::new(1);
+/// let (app, root) = brass::launch_memory(|| {
+///     let user = Resource::new(id.signal(), |id| async move {
+///         Ok::<_, String>(format!("user {id}"))
+///     });
+///     p().text_signal(user.value().map(|user| user.unwrap_or_default()))
+/// });
+/// assert_eq!(root.inner_html(), "<p>user 1</p>");
+///
+/// app.with(|| id.set(2));
+/// assert_eq!(root.inner_html(), "<p>user 2</p>");
+/// ```
+pub struct Resource<T, E> {
+    loading: Mutable<bool>,
+    value: Mutable<Option<T>>,
+    error: Mutable<Option<E>>,
+    fetcher: Rc<Fetcher>,
+    driver: Rc<AbortGuard>,
+}
+
+type Fetch = Rc<dyn Fn() -> LocalBoxFuture<'static, ()>>;
+
+/// Starts the fetches of a [`Resource`].
+struct Fetcher {
+    /// Fetches the latest source value, `None` until the source has a value.
+    fetch: RefCell<Option<Fetch>>,
+    /// Aborts the running fetch when replaced.
+    in_flight: RefCell<Option<AbortGuard>>,
+    /// The suspense the resource was created in.
+    suspense: Option<Rc<SuspenseState>>,
+}
+
+impl Fetcher {
+    fn start(&self) {
+        let Some(fetch) = self.fetch.borrow().clone() else {
+            return;
+        };
+        let pending = PendingGuard::with_state(self.suspense.clone());
+        let f = fetch();
+        let guard = AppContext::spawn_external_abortable(async move {
+            f.await;
+            drop(pending);
+        });
+        *self.in_flight.borrow_mut() = Some(guard);
+    }
+}
+
+impl<T: 'static, E: 'static> Resource<T, E> {
+    pub fn new<S, Src, F, Fut>(source: Src, fetcher: F) -> Self
+    where
+        S: Clone + 'static,
+        Src: Signal<Item = S> + 'static,
+        F: Fn(S) -> Fut + 'static,
+        Fut: Future<Output = Result<T, E>> + 'static,
+    {
+        let loading = Mutable::new(false);
+        let value = Mutable::new(None);
+        let error = Mutable::new(None);
+        let state = Rc::new(Fetcher {
+            fetch: RefCell::new(None),
+            in_flight: RefCell::new(None),
+            suspense: AppContext::scope().suspense,
+        });
+
+        let fetcher = Rc::new(fetcher);
+        let update = {
+            let (loading, value, error) = (loading.clone(), value.clone(), error.clone());
+            let state = state.clone();
+            move |source: S| {
+                let (loading, value, error) = (loading.clone(), value.clone(), error.clone());
+                let fetcher = fetcher.clone();
+                let fetch = move || {
+                    loading.set_neq(true);
+                    let f = fetcher(source.clone());
+                    let (loading, value, error) = (loading.clone(), value.clone(), error.clone());
+                    async move {
+                        match f.await {
+                            Ok(new) => {
+                                value.set(Some(new));
+                                error.set(None);
+                            }
+                            Err(new) => error.set(Some(new)),
+                        }
+                        loading.set_neq(false);
+                    }
+                    .boxed_local()
+                };
+                *state.fetch.borrow_mut() = Some(Rc::new(fetch));
+                state.start();
+            }
+        };
+
+        // Start fetching the current value right away, so it is pending
+        // when the view is rendered.
+        let mut source = Box::pin(source);
+        if let Some(current) = hydration::poll_now(source.as_mut()) {
+            update(current);
+        }
+        let driver = AppContext::spawn_custom_executor_abortable(source.for_each(move |next| {
+            update(next);
+            async {}
+        }));
+
+        Self {
+            loading,
+            value,
+            error,
+            fetcher: state,
+            driver: Rc::new(driver),
+        }
+    }
+
+    /// Whether a fetch is running.
+    pub fn loading(&self) -> impl Signal<Item = bool> {
+        self.keep_alive(self.loading.signal())
+    }
+
+    /// The value of the last successful fetch.
+    pub fn value(&self) -> impl Signal<Item = Option<T>>
+    where
+        T: Clone,
+    {
+        self.keep_alive(self.value.signal_cloned())
+    }
+
+    /// The error of the last fetch, `None` if it succeeded.
+    pub fn error(&self) -> impl Signal<Item = Option<E>>
+    where
+        E: Clone,
+    {
+        self.keep_alive(self.error.signal_cloned())
+    }
+
+    /// Keep fetching while `signal` is alive.
+    fn keep_alive<S: Signal>(&self, signal: S) -> impl Signal<Item = S::Item> {
+        let driver = self.driver.clone();
+        signal.map(move |value| {
+            let _ = &driver;
+            value
+        })
+    }
+
+    /// Fetch the current source value again, aborting a running fetch.
+    pub fn refetch(&self) {
+        self.fetcher.start();
+    }
+
+    /// Change the value locally, for example to show the result of a
+    /// request before the server confirmed it.
+    ///
+    /// A running fetch still replaces the value when it completes.
+    pub fn mutate(&self, f: impl FnOnce(&mut Option<T>)) {
+        f(&mut self.value.lock_mut());
+    }
+}
+
+impl<T, E> Clone for Resource<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            loading: self.loading.clone(),
+            value: self.value.clone(),
+            error: self.error.clone(),
+            fetcher: self.fetcher.clone(),
+            driver: self.driver.clone(),
+        }
+    }
+}
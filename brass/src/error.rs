@@ -0,0 +1,201 @@
+//! Errors of views and event handlers.
+//!
+//! Render closures can return a `Result`, and event handlers or signal
+//! callbacks can panic. Such errors are reported to the hook set with
+//! [`set_error_hook`] and shown by the closest
+//! [`ErrorBoundary`](crate::dom::ErrorBoundary), while the rest of the app
+//! keeps working.
+//!
+//! Panics can only be caught on targets that unwind. On
+//! `wasm32-unknown-unknown` panics abort by default: the panic is still
+//! reported to the hook and the closest boundary, but the app stops working
+//! afterwards, so prefer returning errors there.
+
+use std::{any::Any, cell::RefCell, fmt, panic::AssertUnwindSafe, rc::Rc};
+
+use futures_signals::signal::Mutable;
+
+use crate::context::AppContext;
+
+/// An error of a render closure, signal callback or event handler.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RenderError {
+    message: Rc<str>,
+    panic: bool,
+}
+
+impl RenderError {
+    pub fn new(message: impl fmt::Display) -> Self {
+        Self {
+            message: message.to_string().into(),
+            panic: false,
+        }
+    }
+
+    fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).into()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.as_str().into()
+        } else {
+            "unknown panic".into()
+        };
+        Self {
+            message,
+            panic: true,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Whether the error was caused by a panic.
+    pub fn is_panic(&self) -> bool {
+        self.panic
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl fmt::Debug for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderError")
+            .field("message", &self.message)
+            .field("panic", &self.panic)
+            .finish()
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+type ErrorHook = Rc<dyn Fn(&RenderError)>;
+
+thread_local! {
+    /// The hook set with [`set_error_hook`]. Per thread, like the apps
+    /// whose errors it receives.
+    static ERROR_HOOK: RefCell<Option<ErrorHook>> = const { RefCell::new(None) };
+}
+
+/// Set a hook that is called with every error of the apps on the current
+/// thread, for example to report errors to a telemetry service.
+///
+/// The hook is called before the error is shown by an error boundary, and
+/// also for errors outside of any boundary. Replaces the previous hook.
+pub fn set_error_hook(hook: impl Fn(&RenderError) + 'static) {
+    ERROR_HOOK.with(|current| *current.borrow_mut() = Some(Rc::new(hook)));
+}
+
+/// Remove the hook set with [`set_error_hook`] on the current thread.
+pub fn clear_error_hook() {
+    ERROR_HOOK.with(|current| current.borrow_mut().take());
+}
+
+/// The state of an error boundary, which handles the errors of views and
+/// handlers created inside of it.
+pub(crate) struct Boundary {
+    pub(crate) error: Mutable<Option<RenderError>>,
+}
+
+impl Boundary {
+    pub(crate) fn new() -> Self {
+        Self {
+            error: Mutable::new(None),
+        }
+    }
+}
+
+/// Report an error to the hook and the current error boundary.
+pub(crate) fn report(error: RenderError) {
+    // Cloned, so that the hook can replace itself.
+    if let Some(hook) = ERROR_HOOK.with(|hook| hook.borrow().clone()) {
+        hook(&error);
+    }
+
+    match AppContext::scope().error_boundary {
+        Some(boundary) => {
+            let mut current = boundary.error.lock_mut();
+            // Only the first error is shown, later ones are usually caused
+            // by it.
+            if current.is_none() {
+                *current = Some(error);
+            }
+        }
+        None => tracing::error!("unhandled error: {error}"),
+    }
+}
+
+/// Run `f`, converting a panic into an error.
+pub(crate) fn catch<R>(f: impl FnOnce() -> R) -> Result<R, RenderError> {
+    std::panic::catch_unwind(AssertUnwindSafe(f))
+        .map_err(|payload| RenderError::from_panic(&*payload))
+}
+
+/// Run `f`, reporting a panic.
+pub(crate) fn catch_and_report<R>(f: impl FnOnce() -> R) -> Option<R> {
+    match catch(f) {
+        Ok(out) => Some(out),
+        Err(error) => {
+            report(error);
+            None
+        }
+    }
+}
+
+/// Report panics that can't be caught before the app aborts.
+///
+/// Installed once when an app is created. Only does something when panics
+/// abort, since otherwise they are reported by [`catch`].
+pub(crate) fn install_panic_hook() {
+    #[cfg(panic = "abort")]
+    {
+        static INSTALLED: std::sync::Once = std::sync::Once::new();
+        INSTALLED.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                report_abort(info.payload());
+                previous(info);
+            }));
+        });
+    }
+}
+
+/// Report a panic that aborts, and deactivate the app context that would
+/// otherwise stay active.
+#[cfg_attr(not(panic = "abort"), allow(dead_code))]
+fn report_abort(payload: &(dyn Any + Send)) {
+    report(RenderError::from_panic(payload));
+    AppContext::deactivate();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn test_report_abort() {
+        thread_local! {
+            static REPORTED: RefCell<Vec<RenderError>> = RefCell::new(Vec::new());
+        }
+        set_error_hook(|error| REPORTED.with(|r| r.borrow_mut().push(error.clone())));
+
+        let mut ctx = AppContext::new_memory();
+        let active = ctx.with(|| {
+            report_abort(&"boom");
+            AppContext::is_active()
+        });
+        clear_error_hook();
+        assert!(!active);
+
+        let reported = REPORTED.with(|r| r.take());
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].message(), "boom");
+        assert!(reported[0].is_panic());
+    }
+}
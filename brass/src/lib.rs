@@ -13,6 +13,12 @@ pub mod dom;
 
 pub mod effect;
 
+pub mod error;
+
+pub mod router;
+
+pub mod ssr;
+
 pub use futures_signals as signal;
 
 pub use self::web::DomStr;
@@ -23,7 +29,8 @@ pub use brass_macros::view;
 use component::{build_component, Component};
 
 use context::{AppContext, AppContextRef};
-use dom::Render;
+use dom::{Render, View};
+use web::memory::MemoryNode;
 
 pub fn launch_component<C: Component + 'static>(
     parent: web_sys::Element,
@@ -34,13 +41,131 @@ pub fn launch_component<C: Component + 'static>(
 
 pub fn launch<V: Render, F: FnOnce() -> V>(parent: web_sys::Element, render: F) -> AppContextRef {
     let mut ctx = AppContext::new();
-    let view = ctx.with(move || {
+    let view = render_into(&mut ctx, &parent, render);
+    std::mem::forget(view);
+
+    ctx.leak_ref()
+}
+
+/// Like [`launch`], but with event delegation.
+///
+/// Instead of adding a listener for every event handler of an element,
+/// bubbling events are handled by one listener per event type on `parent`.
+/// The handlers of an element are looked up when an event reaches `parent`,
+/// and invoked from the target up, stopping when a handler calls
+/// `stop_propagation`. This saves a lot of listeners for large lists.
+///
+/// Events that don't bubble, like `focus` or `mouseenter`, and handlers on
+/// other targets like the window still get their own listeners (see
+/// [`dom::Ev::bubbles`]). Note that delegated handlers run after native
+/// listeners on elements between the target and `parent`.
+pub fn launch_delegated<V: Render, F: FnOnce() -> V>(
+    parent: web_sys::Element,
+    render: F,
+) -> AppContextRef {
+    let mut ctx = AppContext::new();
+    ctx.enable_delegation(parent.clone().into());
+    let view = render_into(&mut ctx, &parent, render);
+    std::mem::forget(view);
+
+    ctx.leak_ref()
+}
+
+/// Launch an app that renders into an in-memory document.
+///
+/// Works on every target, which allows testing views with a plain
+/// `cargo test`. The returned root node can be inspected, and events can be
+/// triggered with [`MemoryNode::dispatch_event`]. Use [`AppContextRef::with`]
+/// to update signals, so that pending renders are processed.
+///
+/// ```
+/// use brass::{dom::{builder::button, Ev}, signal::signal::Mutable};
+///
+/// let count = Mutable::new(0);
+/// let (_app, root) = brass::launch_memory(|| {
+///     let counter = count.clone();
+///     button()
+///         .on_event(Ev::Click, move |_| *counter.lock_mut() += 1)
+///         .text_signal(count.signal_ref(|x| x.to_string()))
+/// });
+///
+/// root.find_element("button").unwrap().dispatch_event(Ev::Click);
+/// assert_eq!(count.get(), 1);
+/// assert_eq!(root.inner_html(), "<button>1</button>");
+/// ```
+pub fn launch_memory<V: Render, F: FnOnce() -> V>(render: F) -> (AppContextRef, MemoryNode) {
+    let mut ctx = AppContext::new_memory();
+    let root = MemoryNode::new_document();
+
+    let view = ctx.with(|| {
         let view = render().render();
-        view.attach(&parent);
+        view.attach(&root.clone().into());
         view
     });
     ctx.process_futures();
     std::mem::forget(view);
 
+    (ctx.leak_ref(), root)
+}
+
+/// Like [`launch_component`], but adopts existing markup (see [`hydrate`]).
+pub fn hydrate_component<C: Component + 'static>(
+    parent: web_sys::Element,
+    properties: C::Properties,
+) where
+    C::Properties: Clone,
+{
+    hydrate(parent, move || build_component::<C>(properties.clone()));
+}
+
+/// Attach a view to markup rendered on the server with
+/// [`ssr::render_to_string`].
+///
+/// Works like [`launch`], but instead of creating new DOM nodes, the
+/// existing children of `parent` are adopted in the order the view creates
+/// them. Event handlers and signals are bound to the adopted nodes.
+///
+/// Nodes must be created in document order, which is the case for views
+/// built with the `TagBuilder` methods or the `view!` macro. Nodes that are
+/// created later, for example by spawned futures, are not adopted.
+///
+/// If the view doesn't match the markup, the children of `parent` are
+/// removed and the view is rendered again from scratch. In debug builds the
+/// first mismatch is logged.
+pub fn hydrate<V: Render, F: Fn() -> V>(parent: web_sys::Element, render: F) -> AppContextRef {
+    let mut ctx = AppContext::new();
+
+    ctx.start_hydration(parent.clone().into());
+    let mut view = render_into(&mut ctx, &parent, &render);
+
+    if let Err(_error) = ctx.finish_hydration() {
+        #[cfg(debug_assertions)]
+        tracing::error!("Hydration failed, rendering from scratch: {_error}");
+        #[cfg(not(debug_assertions))]
+        tracing::warn!("Hydration failed, rendering from scratch");
+
+        ctx.with(move || std::mem::drop(view));
+        parent.set_inner_html("");
+        view = render_into(&mut ctx, &parent, render);
+    } else {
+        // Mount callbacks are held back while hydrating.
+        ctx.process_futures();
+    }
+    std::mem::forget(view);
+
     ctx.leak_ref()
 }
+
+fn render_into<V: Render, F: FnOnce() -> V>(
+    ctx: &mut Box<AppContext>,
+    parent: &web_sys::Element,
+    render: F,
+) -> View {
+    let view = ctx.with(move || {
+        let view = render().render();
+        view.attach(&parent.clone().into());
+        view
+    });
+    ctx.process_futures();
+    view
+}
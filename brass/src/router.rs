@@ -0,0 +1,899 @@
+//! Client-side routing.
+//!
+//! Routes are types that implement [`Route`], usually an enum with a derived
+//! implementation:
+//!
+//! ```
+//! use brass::{dom::builder::p, router::{self, Route, RouterMode, Switch}};
+//!
+//! #[derive(Route, Clone, PartialEq, Debug)]
+//! enum AppRoute {
+//!     #[route("/")]
+//!     Home,
+//!     #[route("/users/:id")]
+//!     User { id: u32 },
+//!     #[route("/files/*path")]
+//!     File(String),
+//! }
+//!
+//! assert_eq!(AppRoute::from_path("/users/42"), Some(AppRoute::User { id: 42 }));
+//! assert_eq!(AppRoute::File("docs/a b.txt".into()).to_path(), "/files/docs/a%20b.txt");
+//!
+//! let (app, root) = brass::launch_memory(|| {
+//!     router::init(RouterMode::Memory("/users/1".into()));
+//!     Switch::new(|route: AppRoute| match route {
+//!         AppRoute::Home => p().text("Home"),
+//!         AppRoute::User { id } => p().text(format!("User {id}")),
+//!         AppRoute::File(path) => p().text(path),
+//!     })
+//!     .not_found(|| p().text("Not found"))
+//! });
+//! assert_eq!(root.inner_html(), "<p>User 1</p>");
+//!
+//! app.with(|| router::navigate(&AppRoute::Home));
+//! assert_eq!(root.inner_html(), "<p>Home</p>");
+//! ```
+
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    future::Future,
+    rc::Rc,
+    str::FromStr,
+    task::{Context, Poll},
+};
+
+use futures::{future::LocalBoxFuture, FutureExt};
+use futures_signals::signal::{Mutable, Signal, SignalExt};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlAnchorElement;
+
+use crate::{
+    context::{AppContext, Scope},
+    dom::{builder, AbortGuard, Attr, Ev, PendingGuard, Render, SignalView, TagBuilder, View},
+    effect::EventSubscription,
+    web::{hydration, window},
+};
+
+/// A type that can be parsed from and formatted to a path.
+///
+/// Can be derived for enums with `#[derive(Route)]`. Every variant needs a
+/// `#[route("/pattern")]` attribute, see [`match_path`] for the syntax.
+/// The parameters of a pattern are the fields of the variant, matched by
+/// name for struct variants and by position for tuple variants. Fields are
+/// parsed with [`FromStr`] and formatted with [`Display`]. The first
+/// matching variant is used.
+///
+/// One field can be marked with `#[nested]` to hold a nested route, which
+/// is matched against the rest of the path after the pattern. See
+/// [`Switch`] for rendering nested routes.
+pub trait Route: Clone + PartialEq + 'static {
+    /// Parse a path without the query string.
+    fn from_path(path: &str) -> Option<Self>;
+
+    fn to_path(&self) -> String;
+
+    /// The path of the nested route, for routes that render a [`Switch`]
+    /// of their own.
+    fn nested_path(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether `self` and `other` only differ in their nested route, so the
+    /// view of the route is kept when navigating between them.
+    fn same_layout(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+#[cfg(feature = "macros")]
+pub use brass_macros::Route;
+
+/// Where the current path is stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RouterMode {
+    /// In the URL path, changed with `history.pushState`.
+    History,
+    /// In the URL fragment, like `#/users/1`. Works without server support.
+    Hash,
+    /// Only in memory, starting at the given path. Used for in-memory
+    /// documents and server side rendering.
+    Memory(String),
+}
+
+/// The current location of an app.
+pub(crate) struct Location {
+    mode: RouterMode,
+    /// The current path, including the query string.
+    path: Mutable<String>,
+    /// The previous paths in memory mode.
+    stack: RefCell<Vec<String>>,
+    _listener: Option<EventSubscription>,
+}
+
+impl Location {
+    fn new(mode: RouterMode) -> Self {
+        let (path, listener) = match &mode {
+            RouterMode::Memory(path) => (Mutable::new(path.clone()), None),
+            RouterMode::History | RouterMode::Hash => {
+                let path = Mutable::new(browser_path(&mode));
+                let event = if mode == RouterMode::Hash {
+                    Ev::HashChange
+                } else {
+                    Ev::PopState
+                };
+                let listener = {
+                    let (path, mode) = (path.clone(), mode.clone());
+                    EventSubscription::subscribe(
+                        window().clone().into(),
+                        event,
+                        move |_: web_sys::Event| path.set_neq(browser_path(&mode)),
+                    )
+                };
+                (path, Some(listener))
+            }
+        };
+        Self {
+            mode,
+            path,
+            stack: RefCell::new(Vec::new()),
+            _listener: listener,
+        }
+    }
+
+    fn push(&self, path: &str) {
+        match &self.mode {
+            RouterMode::History => {
+                let history = window().history().unwrap();
+                history
+                    .push_state_with_url(&JsValue::NULL, "", Some(path))
+                    .unwrap();
+            }
+            RouterMode::Hash => window().location().set_hash(path).unwrap(),
+            RouterMode::Memory(_) => self.stack.borrow_mut().push(self.path.get_cloned()),
+        }
+        self.path.set_neq(path.to_string());
+    }
+
+    fn replace(&self, path: &str) {
+        match &self.mode {
+            RouterMode::History => {
+                let history = window().history().unwrap();
+                history
+                    .replace_state_with_url(&JsValue::NULL, "", Some(path))
+                    .unwrap();
+            }
+            RouterMode::Hash => window().location().replace(&format!("#{path}")).unwrap(),
+            RouterMode::Memory(_) => {}
+        }
+        self.path.set_neq(path.to_string());
+    }
+
+    fn back(&self) {
+        match &self.mode {
+            // The listener updates the path.
+            RouterMode::History | RouterMode::Hash => window().history().unwrap().back().unwrap(),
+            RouterMode::Memory(_) => {
+                let previous = self.stack.borrow_mut().pop();
+                if let Some(previous) = previous {
+                    self.path.set_neq(previous);
+                }
+            }
+        }
+    }
+}
+
+/// Read the current path from the browser location.
+fn browser_path(mode: &RouterMode) -> String {
+    let location = window().location();
+    if *mode == RouterMode::Hash {
+        let hash = location.hash().unwrap_or_default();
+        let path = hash.trim_start_matches('#');
+        if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        }
+    } else {
+        let path = location.pathname().unwrap_or_default();
+        let query = location.search().unwrap_or_default();
+        format!("{path}{query}")
+    }
+}
+
+fn location() -> Rc<Location> {
+    if let Some(location) = AppContext::location() {
+        return location;
+    }
+    let mode = if AppContext::backend().has_event_loop() {
+        RouterMode::History
+    } else {
+        RouterMode::Memory("/".to_string())
+    };
+    init(mode);
+    AppContext::location().unwrap()
+}
+
+/// Set where the active app stores the current path.
+///
+/// Call it before routes are used. The default is [`RouterMode::History`]
+/// in the browser and [`RouterMode::Memory`] starting at `/` in in-memory
+/// documents.
+pub fn init(mode: RouterMode) {
+    AppContext::set_location(Rc::new(Location::new(mode)));
+}
+
+/// The current path, including the query string.
+pub fn path() -> impl Signal<Item = String> {
+    location().path.signal_cloned()
+}
+
+pub fn current_path() -> String {
+    location().path.get_cloned()
+}
+
+/// The current route, `None` if no route matches the path.
+pub fn route<R: Route>() -> impl Signal<Item = Option<R>> {
+    path()
+        .map(|path| R::from_path(strip_query(&path)))
+        .dedupe_cloned()
+}
+
+pub fn current_route<R: Route>() -> Option<R> {
+    R::from_path(strip_query(&current_path()))
+}
+
+/// Navigate to a route, adding an entry to the browser history.
+pub fn navigate<R: Route>(route: &R) {
+    navigate_path(&route.to_path());
+}
+
+pub fn navigate_path(path: &str) {
+    location().push(path);
+}
+
+/// Navigate to a route, replacing the current history entry.
+pub fn replace<R: Route>(route: &R) {
+    replace_path(&route.to_path());
+}
+
+pub fn replace_path(path: &str) {
+    location().replace(path);
+}
+
+/// Go back to the previous history entry.
+pub fn back() {
+    location().back();
+}
+
+/// Typed state stored in the query string of the current path.
+///
+/// `T` is (de)serialized as `application/x-www-form-urlencoded`, so it
+/// is usually a struct with fields of simple types. A query string that
+/// doesn't deserialize reads as `T::default()`. In
+/// [`RouterMode::Hash`] the query string is part of the fragment.
+///
+/// Changes are written with `history.replaceState` by default, see
+/// [`Self::push`].
+///
+/// ```
+/// use brass::router::{self, Query, RouterMode};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+/// struct Filter {
+///     search: String,
+///     page: Option<u32>,
+/// }
+///
+/// let (app, _root) = brass::launch_memory(|| {
+///     router::init(RouterMode::Memory("/users?search=a%26b&page=2".into()));
+///     brass::dom::View::Empty
+/// });
+/// app.with(|| {
+///     let query = Query::<Filter>::new();
+///     assert_eq!(query.get(), Filter { search: "a&b".into(), page: Some(2) });
+///
+///     query.update(|filter| filter.page = None);
+///     assert_eq!(router::current_path(), "/users?search=a%26b");
+/// });
+/// ```
+#[cfg(feature = "query")]
+pub struct Query<T> {
+    push: bool,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "query")]
+impl<T> Clone for Query<T> {
+    fn clone(&self) -> Self {
+        Self {
+            push: self.push,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "query")]
+impl<T> Query<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Clone + PartialEq + Default + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            push: false,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Add a history entry for every change, so the back button restores
+    /// the previous state.
+    pub fn push(mut self, push: bool) -> Self {
+        self.push = push;
+        self
+    }
+
+    /// The state, updated when the path changes, including on `popstate`.
+    pub fn signal(&self) -> impl Signal<Item = T> {
+        path().map(|path| parse_query(&path)).dedupe_cloned()
+    }
+
+    pub fn get(&self) -> T {
+        parse_query(&current_path())
+    }
+
+    /// Write `value` to the query string, keeping the path.
+    pub fn set(&self, value: &T) {
+        let path = current_path();
+        let query = serde_urlencoded::to_string(value).unwrap_or_else(|err| {
+            tracing::error!("could not serialize query: {err}");
+            String::new()
+        });
+        let path = match (strip_query(&path), query.as_str()) {
+            (path, "") => path.to_string(),
+            (path, query) => format!("{path}?{query}"),
+        };
+        if self.push {
+            navigate_path(&path);
+        } else {
+            replace_path(&path);
+        }
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        let mut value = self.get();
+        f(&mut value);
+        self.set(&value);
+    }
+}
+
+#[cfg(feature = "query")]
+impl<T> Default for Query<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Clone + PartialEq + Default + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "query")]
+fn parse_query<T: serde::de::DeserializeOwned + Default>(path: &str) -> T {
+    let query = path.split_once('?').map_or("", |(_, query)| query);
+    let query = query.split('#').next().unwrap_or_default();
+    serde_urlencoded::from_str(query).unwrap_or_else(|err| {
+        tracing::warn!("invalid query string {query:?}: {err}");
+        T::default()
+    })
+}
+
+/// The `href` of a link to `path`.
+pub fn href(path: &str) -> String {
+    if location().mode == RouterMode::Hash {
+        format!("#{path}")
+    } else {
+        path.to_string()
+    }
+}
+
+/// A link to `route`, see [`link_path`].
+pub fn link<R: Route>(route: &R) -> TagBuilder<HtmlAnchorElement> {
+    link_path(route.to_path())
+}
+
+/// A link that navigates to `path` without reloading the page.
+///
+/// Clicks that open the link in a new tab or window are left to the
+/// browser.
+pub fn link_path(path: impl Into<String>) -> TagBuilder<HtmlAnchorElement> {
+    let path = path.into();
+    let anchor = builder::a().attr(Attr::Href, href(&path));
+    let elem = anchor.dom_node().as_web().cloned();
+    anchor.on_event(Ev::Click, move |event| {
+        if let Some(elem) = &elem {
+            let event = event.unchecked_ref::<web_sys::MouseEvent>();
+            let target = elem
+                .unchecked_ref::<web_sys::Element>()
+                .get_attribute("target");
+            if event.button() != 0
+                || event.ctrl_key()
+                || event.meta_key()
+                || event.shift_key()
+                || event.alt_key()
+                || event.default_prevented()
+                || target.is_some_and(|target| target != "_self")
+            {
+                return;
+            }
+            event.prevent_default();
+        }
+        navigate_path(&path);
+    })
+}
+
+/// Where a guard sends the user instead of the requested route.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Redirect(String);
+
+impl Redirect {
+    /// Redirect to `route`. For nested routes, this has to be the route of
+    /// the top level.
+    pub fn to<R: Route>(route: &R) -> Self {
+        Self(route.to_path())
+    }
+
+    pub fn path(path: impl Into<String>) -> Self {
+        Self(path.into())
+    }
+}
+
+type RenderRoute<R> = Box<dyn Fn(R) -> LocalBoxFuture<'static, View>>;
+type Guard<R> = Box<dyn Fn(&R) -> Result<(), Redirect>>;
+
+/// A view that renders the current route.
+///
+/// A `Switch` rendered by a route with a nested route, see [`Route`], is
+/// the outlet of that route: it renders the nested route instead of the
+/// current path. The parent view is kept while only the nested route
+/// changes.
+///
+/// ```
+/// use brass::{
+///     dom::builder::{div, p},
+///     router::{self, Redirect, Route, RouterMode, Switch},
+/// };
+///
+/// #[derive(Route, Clone, PartialEq, Debug)]
+/// enum AppRoute {
+///     #[route("/")]
+///     Home,
+///     #[route("/admin")]
+///     Admin(#[nested] AdminRoute),
+/// }
+///
+/// #[derive(Route, Clone, PartialEq, Debug)]
+/// enum AdminRoute {
+///     #[route("/")]
+///     Dashboard,
+///     #[route("/settings")]
+///     Settings,
+/// }
+///
+/// let (app, root) = brass::launch_memory(|| {
+///     router::init(RouterMode::Memory("/admin/settings".into()));
+///     Switch::new(|route: AppRoute| match route {
+///         AppRoute::Home => p().text("Home"),
+///         AppRoute::Admin(_) => div().text("Admin: ").and(Switch::new(|route| match route {
+///             AdminRoute::Dashboard => p().text("Dashboard"),
+///             AdminRoute::Settings => p().text("Settings"),
+///         })),
+///     })
+///     .guard(|route| match route {
+///         AppRoute::Admin(AdminRoute::Settings) => Err(Redirect::to(&AppRoute::Home)),
+///         _ => Ok(()),
+///     })
+/// });
+/// assert_eq!(root.inner_html(), "<p>Home</p>");
+///
+/// app.with(|| router::navigate(&AppRoute::Admin(AdminRoute::Dashboard)));
+/// assert_eq!(root.inner_html(), "<div>Admin: <p>Dashboard</p></div>");
+/// ```
+pub struct Switch<R> {
+    render: RenderRoute<R>,
+    not_found: Option<Box<dyn Fn() -> View>>,
+    guards: Vec<Guard<R>>,
+}
+
+impl<R: Route> Switch<R> {
+    /// The view is rendered again when the route changes.
+    pub fn new<V, F>(render: F) -> Self
+    where
+        V: Render,
+        F: Fn(R) -> V + 'static,
+    {
+        Self::with_render(Box::new(move |route| {
+            let view = render(route).render();
+            Box::pin(std::future::ready(view))
+        }))
+    }
+
+    /// Like [`Self::new`], for routes that load data before they are
+    /// rendered.
+    ///
+    /// The previous route is shown until the future of the new route
+    /// resolved. If the route changes again before, the future is aborted.
+    /// A surrounding [`Suspense`](crate::dom::Suspense) shows its fallback
+    /// until the first route was loaded.
+    pub fn load<V, F, Fut>(load: F) -> Self
+    where
+        V: Render,
+        F: Fn(R) -> Fut + 'static,
+        Fut: Future<Output = V> + 'static,
+    {
+        Self::with_render(Box::new(move |route| {
+            Box::pin(load(route).map(|view| view.render()))
+        }))
+    }
+
+    fn with_render(render: RenderRoute<R>) -> Self {
+        Self {
+            render,
+            not_found: None,
+            guards: Vec::new(),
+        }
+    }
+
+    /// Render `not_found` if no route matches, instead of nothing.
+    pub fn not_found<V, F>(mut self, not_found: F) -> Self
+    where
+        V: Render,
+        F: Fn() -> V + 'static,
+    {
+        self.not_found = Some(Box::new(move || not_found().render()));
+        self
+    }
+
+    /// Check routes before they are rendered or loaded. If `guard` returns
+    /// a [`Redirect`], the current history entry is replaced with it.
+    ///
+    /// Guards run in the order they were added, whenever the route changes.
+    pub fn guard<F>(mut self, guard: F) -> Self
+    where
+        F: Fn(&R) -> Result<(), Redirect> + 'static,
+    {
+        self.guards.push(Box::new(guard));
+        self
+    }
+}
+
+/// The view of a [`Switch`], set once a route was rendered.
+struct Page {
+    view: RefCell<Option<View>>,
+    version: Mutable<usize>,
+}
+
+impl Page {
+    fn show(&self, view: View) {
+        *self.view.borrow_mut() = Some(view);
+        *self.version.lock_mut() += 1;
+    }
+}
+
+struct SwitchState<R> {
+    switch: Switch<R>,
+    /// The scope the switch was rendered in.
+    scope: Scope,
+    page: Rc<Page>,
+    /// The last route that passed the guards, `None` before the first.
+    current: Option<Option<R>>,
+    /// The path of the nested route, shared with the outlet of the current
+    /// view.
+    nested: Mutable<String>,
+    /// Aborts the running loader when replaced.
+    loading: Option<AbortGuard>,
+    rendered: bool,
+}
+
+impl<R: Route> SwitchState<R> {
+    fn update(&mut self, route: Option<R>) {
+        if let Some(route) = &route {
+            for guard in &self.switch.guards {
+                if let Err(Redirect(path)) = guard(route) {
+                    replace_path(&path);
+                    return;
+                }
+            }
+        }
+
+        let nested = route
+            .as_ref()
+            .and_then(Route::nested_path)
+            .unwrap_or_else(|| "/".to_string());
+        let same_layout = match (&self.current, &route) {
+            (Some(Some(current)), Some(route)) => current.same_layout(route),
+            (Some(None), None) => true,
+            _ => false,
+        };
+        if same_layout {
+            self.nested.set_neq(nested);
+            return;
+        }
+        self.current = Some(route.clone());
+        // The outlet of the previous view must not see paths of the next one.
+        self.nested = Mutable::new(nested);
+        self.loading = None;
+
+        let mut scope = self.scope.clone();
+        scope.nested_route = Some(self.nested.clone());
+        AppContext::with_scope(scope, || {
+            let mut future = match route {
+                Some(route) => (self.switch.render)(route),
+                None => {
+                    let view = self.switch.not_found.as_ref().map_or(View::Empty, |f| f());
+                    Box::pin(std::future::ready(view))
+                }
+            };
+
+            // Routes without loaders are rendered right away.
+            let waker = futures::task::noop_waker();
+            if let Poll::Ready(view) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                self.page.show(view);
+                self.rendered = true;
+                return;
+            }
+
+            let pending =
+                (!self.rendered).then(|| PendingGuard::with_state(self.scope.suspense.clone()));
+            let page = self.page.clone();
+            self.rendered = true;
+            self.loading = Some(AppContext::spawn_custom_executor_abortable(async move {
+                page.show(future.await);
+                drop(pending);
+            }));
+        });
+    }
+}
+
+impl<R: Route> Render for Switch<R> {
+    fn render(self) -> View {
+        let scope = AppContext::scope();
+        let path = match &scope.nested_route {
+            Some(nested) => nested.signal_cloned(),
+            None => location().path.signal_cloned(),
+        };
+        let page = Rc::new(Page {
+            view: RefCell::new(None),
+            version: Mutable::new(0),
+        });
+        let mut state = SwitchState {
+            switch: self,
+            scope,
+            page: page.clone(),
+            current: None,
+            nested: Mutable::new("/".to_string()),
+            loading: None,
+            rendered: false,
+        };
+
+        // The first route is rendered right away, so it can adopt markup
+        // while hydrating.
+        let mut routes = Box::pin(path.map(|path| R::from_path(strip_query(&path))));
+        if let Some(route) = hydration::poll_now(routes.as_mut()) {
+            state.update(route);
+        }
+        let driver = AppContext::spawn_custom_executor_abortable(routes.for_each(move |route| {
+            state.update(route);
+            async {}
+        }));
+
+        // The view owns the driver, so it stops when the view is removed.
+        let view = page.version.signal().map(move |_| {
+            let _driver = &driver;
+            page.view.borrow_mut().take().unwrap_or(View::Empty)
+        });
+        View::Signal(SignalView::new(view))
+    }
+}
+
+impl<R: Route> From<Switch<R>> for View {
+    fn from(switch: Switch<R>) -> Self {
+        switch.render()
+    }
+}
+
+/// Remove the query string and fragment from a path.
+pub fn strip_query(path: &str) -> &str {
+    path.split(['?', '#']).next().unwrap_or_default()
+}
+
+/// The parameters of a path matched by [`match_path`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    values: Vec<(String, String)>,
+}
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name)?.parse().ok()
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    strip_query(path).split('/').filter(|s| !s.is_empty())
+}
+
+/// Match `path` against a pattern like `/users/:id/files/*path`.
+///
+/// `:name` matches a single segment and `*name` all remaining segments,
+/// other segments must match exactly. Empty segments and the query string
+/// are ignored, and parameters are percent-decoded.
+///
+/// ```
+/// use brass::router::match_path;
+///
+/// let params = match_path("/users/:id/*rest", "/users/1/a/b%20c").unwrap();
+/// assert_eq!(params.parse::<u32>("id"), Some(1));
+/// assert_eq!(params.get("rest"), Some("a/b c"));
+/// assert_eq!(match_path("/users/:id", "/users"), None);
+/// ```
+pub fn match_path(pattern: &str, path: &str) -> Option<Params> {
+    let (params, rest) = match_prefix(pattern, path)?;
+    (rest == "/").then_some(params)
+}
+
+/// Like [`match_path`], but `pattern` only has to match the start of
+/// `path`. Returns the rest of the path, which is `/` if nothing is left.
+pub fn match_prefix(pattern: &str, path: &str) -> Option<(Params, String)> {
+    let mut params = Params::default();
+    let mut path = segments(path);
+    for segment in segments(pattern) {
+        if let Some(name) = segment.strip_prefix('*') {
+            let rest: Vec<_> = path.by_ref().map(decode).collect();
+            params.values.push((name.to_string(), rest.join("/")));
+            break;
+        }
+        let value = path.next()?;
+        if let Some(name) = segment.strip_prefix(':') {
+            params.values.push((name.to_string(), decode(value)));
+        } else if segment != value {
+            return None;
+        }
+    }
+    let rest: Vec<_> = path.collect();
+    Some((params, format!("/{}", rest.join("/"))))
+}
+
+/// Append the path of a nested route to the path of its parent.
+pub fn join_path(parent: &str, nested: &str) -> String {
+    let parent = parent.trim_end_matches('/');
+    match nested.trim_start_matches('/') {
+        "" if parent.is_empty() => "/".to_string(),
+        "" => parent.to_string(),
+        nested => format!("{parent}/{nested}"),
+    }
+}
+
+/// Format a path from a pattern of [`match_path`], replacing the
+/// parameters with `values` in order.
+///
+/// Values are percent-encoded, except for the `/` in `*name` parameters.
+pub fn format_path(pattern: &str, values: &[&dyn Display]) -> String {
+    let mut values = values.iter();
+    let mut path = String::new();
+    for segment in segments(pattern) {
+        path.push('/');
+        if segment.starts_with(':') {
+            let value = values.next().map(|v| v.to_string()).unwrap_or_default();
+            path.push_str(&encode(&value, is_path_char));
+        } else if segment.starts_with('*') {
+            let value = values.next().map(|v| v.to_string()).unwrap_or_default();
+            path.push_str(&encode(&value, |c| c == b'/' || is_path_char(c)));
+        } else {
+            path.push_str(segment);
+        }
+    }
+    if path.is_empty() {
+        path.push('/');
+    }
+    path
+}
+
+/// Characters that don't need to be encoded in a path segment.
+fn is_path_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&c)
+}
+
+/// Percent-encode all bytes except those for which `keep` returns `true`.
+pub(crate) fn encode(value: &str, keep: impl Fn(u8) -> bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for &byte in value.as_bytes() {
+        if keep(byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// Decode percent-encoded bytes. Invalid escapes are kept as they are.
+pub(crate) fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_path() {
+        assert_eq!(match_path("/", "/"), Some(Params::default()));
+        assert_eq!(match_path("/", "/?page=2"), Some(Params::default()));
+        assert_eq!(match_path("/a", "/a/"), Some(Params::default()));
+        assert_eq!(match_path("/a", "/b"), None);
+        assert_eq!(match_path("/a", "/a/b"), None);
+
+        let params = match_path("/a/:x/:y", "/a/1/%E2%9C%93").unwrap();
+        assert_eq!(params.get("x"), Some("1"));
+        assert_eq!(params.get("y"), Some("✓"));
+
+        let params = match_path("/files/*path", "/files").unwrap();
+        assert_eq!(params.get("path"), Some(""));
+    }
+
+    #[test]
+    fn test_match_prefix() {
+        let (params, rest) = match_prefix("/org/:org", "/org/a/users/1?x=1").unwrap();
+        assert_eq!(params.get("org"), Some("a"));
+        assert_eq!(rest, "/users/1");
+        assert_eq!(match_prefix("/org/:org", "/org/a").unwrap().1, "/");
+        assert_eq!(match_prefix("/org/:org", "/users"), None);
+
+        assert_eq!(join_path("/", "/"), "/");
+        assert_eq!(join_path("/admin", "/"), "/admin");
+        assert_eq!(join_path("/", "/users/1"), "/users/1");
+        assert_eq!(join_path("/admin/", "/users/1"), "/admin/users/1");
+    }
+
+    #[test]
+    fn test_format_path() {
+        assert_eq!(format_path("/", &[]), "/");
+        assert_eq!(format_path("/users/:id/edit", &[&7]), "/users/7/edit");
+        assert_eq!(format_path("/search/:q", &[&"a/b?"]), "/search/a%2Fb%3F");
+        assert_eq!(format_path("/files/*path", &[&"a/b c"]), "/files/a/b%20c");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("a%20b"), "a b");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%zz"), "%zz");
+        assert_eq!(decode("%+1"), "%+1");
+    }
+}
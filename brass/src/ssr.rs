@@ -0,0 +1,47 @@
+//! Server side rendering.
+//!
+//! Renders views to HTML strings without a browser, by building them in an
+//! in-memory document (see [`crate::web::memory`]).
+//!
+//! Signals are evaluated once with their current value. Event handlers are
+//! never invoked and timers never fire.
+
+use crate::{
+    component::{build_component, Component},
+    context::AppContext,
+    dom::Render,
+    web::memory::MemoryNode,
+};
+
+/// Render a view to an HTML string.
+///
+/// ```
+/// use brass::dom::builder::div;
+///
+/// let html = brass::ssr::render_to_string(|| div().class("a").text("1 < 2"));
+/// assert_eq!(html, r#"<div class="a">1 &lt; 2</div>"#);
+/// ```
+pub fn render_to_string<V: Render, F: FnOnce() -> V>(render: F) -> String {
+    let mut ctx = AppContext::new_memory();
+    let root = MemoryNode::new_fragment();
+
+    let view = ctx.with(|| {
+        let view = render().render();
+        view.attach(&root.clone().into());
+        view
+    });
+    // Renders the current value of all signals.
+    ctx.process_futures();
+
+    let html = root.inner_html();
+
+    // Cleanup callbacks may need the app context.
+    ctx.with(move || std::mem::drop(view));
+
+    html
+}
+
+/// Render a component to an HTML string.
+pub fn render_component_to_string<C: Component + 'static>(properties: C::Properties) -> String {
+    render_to_string(move || build_component::<C>(properties))
+}
@@ -1,7 +1,17 @@
 //! Helpers for interacting with the browser environment.
 
+use std::borrow::Cow;
+
 use js_sys::JsString;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::context::AppContext;
+
+pub mod backend;
+pub(crate) mod hydration;
+pub mod memory;
+
+use self::memory::MemoryNode;
 
 /// Defines an enum that maps to plain string values and provides a cache
 /// of `JsString`s for interaction with the dom.
@@ -13,10 +23,12 @@ use wasm_bindgen::JsCast;
 macro_rules! make_str_enum {
 
     (
+        $( #[$meta:meta] )*
         $enum_name:ident {
             $( $name:ident = $value:literal, )*
         }
     ) => {
+        $( #[$meta] )*
         #[repr(u16)]
         #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
         pub enum $enum_name {
@@ -49,6 +61,14 @@ macro_rules! make_str_enum {
 
                 }
             }
+
+            /// Parse a string, returns `None` for unknown values.
+            pub fn parse(value: &str) -> Option<Self> {
+                match value {
+                    $( $value => Some(Self::$name), )*
+                    _ => None,
+                }
+            }
         }
 
 
@@ -60,7 +80,7 @@ macro_rules! make_str_enum {
     };
 }
 
-use crate::dom::{Attr, Ev, Tag};
+use crate::dom::{Attr, Ev, EventOptions, PropName, PropValue, Tag};
 
 pub fn window() -> &'static web_sys::Window {
     static mut WINDOW: once_cell::unsync::Lazy<web_sys::Window> =
@@ -141,10 +161,44 @@ export function __brass_create_element(tag) {
     return document.createElement(tag);
 }
 
+export function __brass_create_element_ns(ns, tag) {
+    return document.createElementNS(ns, tag);
+}
+
 export function __brass_add_event_listener(elem, event, listener) {
     elem.addEventListener(event, listener);
 }
 
+export function __brass_add_event_listener_with_options(elem, event, listener, capture, passive, once) {
+    elem.addEventListener(event, listener, { capture, passive, once });
+}
+
+export function __brass_delegate_add(elem, event, id) {
+    const key = '__brass_' + event;
+    (elem[key] || (elem[key] = [])).push(id);
+}
+
+export function __brass_delegate_remove(elem, event, id) {
+    const ids = elem['__brass_' + event];
+    const index = ids ? ids.indexOf(id) : -1;
+    if (index !== -1) {
+        ids.splice(index, 1);
+    }
+}
+
+export function __brass_delegate_ids(elem, event) {
+    const ids = elem['__brass_' + event];
+    return ids && ids.length ? ids.slice() : null;
+}
+
+export function __brass_set_current_target(event, target) {
+    Object.defineProperty(event, 'currentTarget', { configurable: true, value: target });
+}
+
+export function __brass_reset_current_target(event) {
+    delete event.currentTarget;
+}
+
 export function __brass_create_empty_node() {
     return document.createComment('')
 }
@@ -223,12 +277,29 @@ extern "C" {
 
     fn __brass_create_element(tag: &js_sys::JsString) -> wasm_bindgen::JsValue;
 
+    fn __brass_create_element_ns(ns: &str, tag: &js_sys::JsString) -> wasm_bindgen::JsValue;
+
     fn __brass_add_event_listener(
         elem: &web_sys::EventTarget,
         event: &js_sys::JsString,
         listener: &js_sys::Function,
     );
 
+    fn __brass_add_event_listener_with_options(
+        elem: &web_sys::EventTarget,
+        event: &js_sys::JsString,
+        listener: &js_sys::Function,
+        capture: bool,
+        passive: bool,
+        once: bool,
+    );
+
+    fn __brass_delegate_add(elem: &web_sys::EventTarget, event: &js_sys::JsString, id: u32);
+    fn __brass_delegate_remove(elem: &web_sys::EventTarget, event: &js_sys::JsString, id: u32);
+    fn __brass_delegate_ids(elem: &JsValue, event: &js_sys::JsString) -> Option<Vec<u32>>;
+    fn __brass_set_current_target(event: &web_sys::Event, target: &JsValue);
+    fn __brass_reset_current_target(event: &web_sys::Event);
+
     fn __brass_create_empty_node() -> web_sys::Node;
 
     fn __brass_create_text_node_str(value: &str) -> web_sys::Text;
@@ -247,9 +318,151 @@ extern "C" {
     fn __brass_document_fullscreen_element() -> Option<web_sys::Element>;
 }
 
-pub fn create_empty_node() -> web_sys::Node {
-    // TODO: use cache of nodes!
-    __brass_create_empty_node()
+/// A node of the document that views are rendered into.
+#[derive(Clone, Debug)]
+pub enum DomNode {
+    /// A node of the browser DOM.
+    Web(web_sys::Node),
+    /// A node of an in-memory document.
+    Memory(MemoryNode),
+}
+
+impl From<web_sys::Node> for DomNode {
+    fn from(node: web_sys::Node) -> Self {
+        Self::Web(node)
+    }
+}
+
+impl From<web_sys::Element> for DomNode {
+    fn from(elem: web_sys::Element) -> Self {
+        Self::Web(elem.into())
+    }
+}
+
+impl From<MemoryNode> for DomNode {
+    fn from(node: MemoryNode) -> Self {
+        Self::Memory(node)
+    }
+}
+
+fn mixed_documents() -> ! {
+    panic!("can't combine nodes from different documents");
+}
+
+impl DomNode {
+    /// A node that doesn't refer to anything.
+    /// Only useful as a temporary value.
+    pub(crate) fn null() -> Self {
+        Self::Web(JsValue::NULL.unchecked_into())
+    }
+
+    /// Whether both refer to the same node.
+    pub fn is_same(&self, other: &DomNode) -> bool {
+        match (self, other) {
+            (Self::Web(a), Self::Web(b)) => a == b,
+            (Self::Memory(a), Self::Memory(b)) => a.ptr_eq(b),
+            _ => false,
+        }
+    }
+
+    pub fn as_web(&self) -> Option<&web_sys::Node> {
+        if let Self::Web(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_memory(&self) -> Option<&MemoryNode> {
+        if let Self::Memory(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn parent_node(&self) -> Option<DomNode> {
+        match self {
+            Self::Web(node) => node.parent_node().map(Self::Web),
+            Self::Memory(node) => node.parent().map(Self::Memory),
+        }
+    }
+
+    /// Whether the node is inside the document.
+    pub fn is_connected(&self) -> bool {
+        match self {
+            Self::Web(node) => node.is_connected(),
+            Self::Memory(node) => node.is_connected(),
+        }
+    }
+
+    pub fn append_child(&self, child: &DomNode) {
+        match (self, child) {
+            (Self::Web(parent), Self::Web(child)) => {
+                if !hydration::intercept_insert(parent, child, None) {
+                    parent.append_child(child).unwrap();
+                }
+            }
+            (Self::Memory(parent), Self::Memory(child)) => {
+                parent.append_child(child);
+            }
+            _ => mixed_documents(),
+        }
+    }
+
+    pub fn insert_before(&self, child: &DomNode, before: Option<&DomNode>) {
+        match (self, child, before) {
+            (Self::Web(parent), Self::Web(child), None) => {
+                if !hydration::intercept_insert(parent, child, None) {
+                    parent.insert_before(child, None).unwrap();
+                }
+            }
+            (Self::Web(parent), Self::Web(child), Some(Self::Web(before))) => {
+                if !hydration::intercept_insert(parent, child, Some(before)) {
+                    parent.insert_before(child, Some(before)).unwrap();
+                }
+            }
+            (Self::Memory(parent), Self::Memory(child), None) => {
+                parent.insert_before(child, None);
+            }
+            (Self::Memory(parent), Self::Memory(child), Some(Self::Memory(before))) => {
+                parent.insert_before(child, Some(before));
+            }
+            _ => mixed_documents(),
+        }
+    }
+
+    pub fn replace_child(&self, new: &DomNode, old: &DomNode) {
+        match (self, new, old) {
+            (Self::Web(parent), Self::Web(new), Self::Web(old)) => {
+                if !hydration::intercept_insert(parent, new, Some(old)) {
+                    parent.replace_child(new, old).unwrap();
+                }
+            }
+            (Self::Memory(parent), Self::Memory(new), Self::Memory(old)) => {
+                parent.replace_child(new, old);
+            }
+            _ => mixed_documents(),
+        }
+    }
+
+    pub fn remove_child(&self, child: &DomNode) {
+        match (self, child) {
+            (Self::Web(parent), Self::Web(child)) => {
+                if !hydration::intercept_remove() {
+                    parent.remove_child(child).unwrap();
+                }
+            }
+            (Self::Memory(parent), Self::Memory(child)) => {
+                parent.remove_child(child);
+            }
+            _ => mixed_documents(),
+        }
+    }
+}
+
+pub fn create_empty_node() -> DomNode {
+    AppContext::backend().create_placeholder()
 }
 
 static mut EMPTY_STRING: once_cell::unsync::OnceCell<JsString> = once_cell::unsync::OnceCell::new();
@@ -258,7 +471,7 @@ static mut EMPTY_STRING: once_cell::unsync::OnceCell<JsString> = once_cell::unsy
 pub fn empty_string() -> &'static JsString {
     // Safety: safe in single-threaded context.
     // TODO: add #[cfg] flag to disable otherwise.
-    unsafe { EMPTY_STRING.get_or_init(|| JsString::from("".to_string())) }
+    unsafe { (*std::ptr::addr_of!(EMPTY_STRING)).get_or_init(|| JsString::from("".to_string())) }
 }
 
 #[derive(Debug)]
@@ -269,6 +482,27 @@ pub enum DomStr<'a> {
     JsString(JsString),
 }
 
+impl DomStr<'_> {
+    /// Convert to a Rust string.
+    ///
+    /// Only copies if the value is a Javascript string.
+    pub fn to_cow(&self) -> Cow<'_, str> {
+        match self {
+            DomStr::Str(value) => Cow::Borrowed(value),
+            DomStr::String(value) => Cow::Borrowed(value.as_str()),
+            DomStr::JsStr(value) => Cow::Owned(String::from(*value)),
+            DomStr::JsString(value) => Cow::Owned(String::from(value)),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self {
+            DomStr::String(value) => value,
+            other => other.to_cow().into_owned(),
+        }
+    }
+}
+
 impl<'a> From<&'a str> for DomStr<'a> {
     fn from(value: &'a str) -> Self {
         Self::Str(value)
@@ -281,7 +515,7 @@ impl<'a> From<&'a String> for DomStr<'a> {
     }
 }
 
-impl<'a> From<String> for DomStr<'a> {
+impl From<String> for DomStr<'_> {
     fn from(value: String) -> Self {
         Self::String(value)
     }
@@ -293,7 +527,7 @@ impl<'a> From<&'a JsString> for DomStr<'a> {
     }
 }
 
-impl<'a> From<JsString> for DomStr<'a> {
+impl From<JsString> for DomStr<'_> {
     fn from(s: JsString) -> Self {
         Self::JsString(s)
     }
@@ -303,143 +537,97 @@ pub fn document_fullscreen_element() -> Option<web_sys::Element> {
     __brass_document_fullscreen_element()
 }
 
-//#[cfg(target = "wasm32-unknown-unknown")]
-pub fn set_attribute(elem: &web_sys::Element, attr: Attr, value: DomStr<'_>) {
-    match value {
-        DomStr::Str(value) => {
-            // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
-            __brass_elem_set_attr_str_value(elem, attr.as_js_string(), value);
-        }
-        DomStr::String(value) => {
-            // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
-            __brass_elem_set_attr_str_value(elem, attr.as_js_string(), &value);
-        }
-        DomStr::JsStr(value) => {
-            // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
-            __brass_elem_set_attr_js_value(elem, attr.as_js_string(), value);
-        }
-        DomStr::JsString(value) => {
-            __brass_elem_set_attr_js_value(elem, attr.as_js_string(), &value);
-        }
-    }
+pub fn set_attribute(elem: &DomNode, attr: Attr, value: DomStr<'_>) {
+    AppContext::backend().set_attribute(elem, attr, value);
 }
 
-pub fn set_style(elem: &web_sys::Element, style: crate::dom::Style, value: DomStr<'_>) {
-    match value {
-        DomStr::Str(value) => {
-            // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
-            __brass_elem_set_style_str_value(elem, style.as_js_string(), value);
-        }
-        DomStr::String(value) => {
-            // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
-            __brass_elem_set_style_str_value(elem, style.as_js_string(), &value);
-        }
-        DomStr::JsStr(value) => {
-            // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
-            __brass_elem_set_style_js_value(elem, style.as_js_string(), value);
-        }
-        DomStr::JsString(value) => {
-            __brass_elem_set_style_js_value(elem, style.as_js_string(), &value);
-        }
-    }
+/// Set an attribute to an empty value, as used for boolean attributes like
+/// `disabled`.
+pub fn set_attribute_empty(elem: &DomNode, attr: Attr) {
+    AppContext::backend().set_attribute(elem, attr, DomStr::Str(""));
+}
+
+pub fn set_style(elem: &DomNode, style: crate::dom::Style, value: DomStr<'_>) {
+    AppContext::backend().set_style(elem, style, value);
 }
 
 pub fn elem_set_class_js(elem: &web_sys::Element, value: &JsString) {
     __brass_class_set_js(elem, value);
 }
 
-pub fn elem_set_class<'a, I>(elem: &web_sys::Element, value: I)
+pub fn elem_set_class<'a, I>(elem: &DomNode, value: I)
 where
     I: Into<DomStr<'a>>,
 {
-    match value.into() {
-        DomStr::Str(value) => {
-            elem.set_class_name(value);
-        }
-        DomStr::String(value) => {
-            elem.set_class_name(&value);
-        }
-        DomStr::JsStr(value) => {
-            elem_set_class_js(elem, value);
-        }
-        DomStr::JsString(value) => {
-            elem_set_class_js(elem, &value);
-        }
-    }
+    AppContext::backend().set_class(elem, value.into());
 }
 
 pub fn elem_add_class_js(elem: &web_sys::Element, value: &JsString) {
     __brass_class_list_add_js(elem, value);
 }
 
-pub fn elem_add_class(elem: &web_sys::Element, value: &DomStr<'_>) {
-    match value {
-        DomStr::Str(value) => {
-            __brass_class_list_add_str(elem, value);
-        }
-        DomStr::String(value) => {
-            __brass_class_list_add_str(elem, &value);
-        }
-        DomStr::JsStr(value) => {
-            __brass_class_list_add_js(elem, value);
-        }
-        DomStr::JsString(value) => {
-            __brass_class_list_add_js(elem, &value);
-        }
-    }
+pub fn elem_add_class(elem: &DomNode, value: &DomStr<'_>) {
+    AppContext::backend().add_class(elem, value);
 }
 
 pub fn elem_remove_class_js(elem: &web_sys::Element, value: &JsString) {
     __brass_class_list_remove_js(elem, value);
 }
 
-pub fn elem_remove_class(elem: &web_sys::Element, value: &DomStr<'_>) {
-    match value {
-        DomStr::Str(value) => {
-            __brass_class_list_remove_str(elem, value);
-        }
-        DomStr::String(value) => {
-            __brass_class_list_remove_str(elem, &value);
-        }
-        DomStr::JsStr(value) => {
-            __brass_class_list_remove_js(elem, value);
-        }
-        DomStr::JsString(value) => {
-            __brass_class_list_remove_js(elem, &value);
-        }
-    }
+pub fn elem_remove_class(elem: &DomNode, value: &DomStr<'_>) {
+    AppContext::backend().remove_class(elem, value);
 }
 
-pub fn remove_attr(elem: &web_sys::Element, attr: Attr) {
-    __brass_elem_remove_attr(elem, attr.as_js_string());
+pub fn remove_attr(elem: &DomNode, attr: Attr) {
+    AppContext::backend().remove_attribute(elem, attr);
 }
 
-pub fn create_element(tag: Tag) -> web_sys::Element {
-    __brass_create_element(tag.as_js_string()).unchecked_into()
+/// Create an element.
+///
+/// The namespace is derived from the tag, see [`crate::dom::with_namespace`].
+pub fn create_element(tag: Tag) -> DomNode {
+    let namespace = tag.namespace_in(crate::dom::current_namespace());
+    AppContext::backend().create_element(tag, namespace)
 }
 
-pub fn create_text(value: DomStr<'_>) -> web_sys::Text {
-    match value {
-        DomStr::String(value) => __brass_create_text_node_str(&value).unchecked_into(),
-        DomStr::Str(value) => __brass_create_text_node_str(&value).unchecked_into(),
-        DomStr::JsStr(value) => __brass_create_text_node_js(value).unchecked_into(),
-        DomStr::JsString(value) => __brass_create_text_node_js(&value).unchecked_into(),
-    }
+pub fn create_text(value: DomStr<'_>) -> DomNode {
+    AppContext::backend().create_text(value)
 }
 
-pub fn set_text_data(text: &web_sys::Text, value: &DomStr<'_>) {
-    match value {
-        DomStr::String(v) => {
-            text.set_data(&v);
-        }
-        DomStr::Str(v) => {
-            text.set_data(v);
-        }
-        DomStr::JsStr(v) => __brass_set_text_data(text, v),
-        DomStr::JsString(v) => {
-            __brass_set_text_data(text, v);
-        }
-    }
+pub fn set_text_data(text: &DomNode, value: &DomStr<'_>) {
+    AppContext::backend().set_text(text, value);
+}
+
+pub fn set_property(elem: &DomNode, name: PropName, value: PropValue) {
+    AppContext::backend().set_property(elem, name, value);
+}
+
+pub fn get_property(elem: &DomNode, name: PropName) -> PropValue {
+    AppContext::backend().get_property(elem, name)
+}
+
+/// Register a delegated handler on an element, see [`crate::launch_delegated`].
+pub(crate) fn delegate_add(elem: &web_sys::EventTarget, event: Ev, id: u32) {
+    __brass_delegate_add(elem, event.as_js_string(), id);
+}
+
+pub(crate) fn delegate_remove(elem: &web_sys::EventTarget, event: Ev, id: u32) {
+    __brass_delegate_remove(elem, event.as_js_string(), id);
+}
+
+/// The delegated handlers of `target`, in registration order.
+pub(crate) fn delegated_ids(target: &JsValue, event: Ev) -> Vec<u32> {
+    __brass_delegate_ids(target, event.as_js_string()).unwrap_or_default()
+}
+
+/// Make handlers see `target` as `event.currentTarget`, like with direct
+/// listeners.
+pub(crate) fn set_current_target(event: &web_sys::Event, target: &JsValue) {
+    __brass_set_current_target(event, target);
+}
+
+pub(crate) fn reset_current_target(event: &web_sys::Event) {
+    __brass_reset_current_target(event);
 }
 
 #[allow(unused)]
@@ -448,3 +636,25 @@ pub fn add_event_lister(target: &web_sys::EventTarget, event: Ev, listener: &js_
         __brass_add_event_listener(target, event.as_js_string(), listener);
     }
 }
+
+/// Add an event listener with the `capture`, `passive` and `once` flags of
+/// `options`.
+pub fn add_event_listener_with_options(
+    target: &web_sys::EventTarget,
+    event: Ev,
+    listener: &js_sys::Function,
+    options: EventOptions,
+) {
+    if !options.has_listener_flags() {
+        add_event_lister(target, event, listener);
+        return;
+    }
+    __brass_add_event_listener_with_options(
+        target,
+        event.as_js_string(),
+        listener,
+        options.capture,
+        options.passive,
+        options.once,
+    );
+}
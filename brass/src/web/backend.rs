@@ -0,0 +1,440 @@
+//! Backends that views are rendered into.
+//!
+//! All node creation and manipulation goes through the [`Backend`] of the
+//! active app. The [`WebBackend`] renders into the browser DOM, the
+//! [`MemoryBackend`] into an in-memory document that works on every target,
+//! which allows testing views with a plain `cargo test`.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::dom::{Attr, Ev, EventOptions, Namespace, PropName, PropValue, Style, Tag};
+
+use super::{hydration, memory::MemoryNode, DomNode, DomStr};
+
+/// A target that event listeners can be added to.
+#[derive(Clone, Debug)]
+pub enum EventTarget {
+    Web(web_sys::EventTarget),
+    Memory(MemoryNode),
+}
+
+impl From<&DomNode> for EventTarget {
+    fn from(node: &DomNode) -> Self {
+        match node {
+            DomNode::Web(node) => Self::Web(node.clone().into()),
+            DomNode::Memory(node) => Self::Memory(node.clone()),
+        }
+    }
+}
+
+pub(crate) type MemoryListener = Rc<RefCell<Box<dyn FnMut(web_sys::Event)>>>;
+
+/// An event listener created by a [`Backend`].
+pub enum EventListener {
+    Web(Closure<dyn FnMut(web_sys::Event)>),
+    Memory(MemoryListener),
+}
+
+/// The operations used to build and update views.
+///
+/// The backend of an app is chosen when it is launched and applies to all
+/// nodes created by the app. Passing nodes of a different backend panics.
+pub trait Backend {
+    fn create_element(&self, tag: Tag, namespace: Namespace) -> DomNode;
+    fn create_text(&self, value: DomStr<'_>) -> DomNode;
+    /// Create an empty node that marks a position in the document.
+    fn create_placeholder(&self) -> DomNode;
+
+    fn set_attribute(&self, elem: &DomNode, attr: Attr, value: DomStr<'_>);
+    fn remove_attribute(&self, elem: &DomNode, attr: Attr);
+    fn set_style(&self, elem: &DomNode, style: Style, value: DomStr<'_>);
+    fn set_class(&self, elem: &DomNode, value: DomStr<'_>);
+    fn add_class(&self, elem: &DomNode, value: &DomStr<'_>);
+    fn remove_class(&self, elem: &DomNode, value: &DomStr<'_>);
+    fn set_text(&self, text: &DomNode, value: &DomStr<'_>);
+    fn set_property(&self, elem: &DomNode, name: PropName, value: PropValue);
+    fn get_property(&self, elem: &DomNode, name: PropName) -> PropValue;
+
+    fn create_event_listener(&self, callback: Box<dyn FnMut(web_sys::Event)>) -> EventListener;
+    fn add_event_listener(
+        &self,
+        target: &EventTarget,
+        event: Ev,
+        listener: &EventListener,
+        options: EventOptions,
+    );
+    /// Remove a listener, `options` must be the ones it was added with.
+    fn remove_event_listener(
+        &self,
+        target: &EventTarget,
+        event: Ev,
+        listener: &EventListener,
+        options: EventOptions,
+    );
+    /// Apply `prevent_default` and `stop_propagation` to an event before
+    /// the handler is invoked.
+    fn apply_event_options(&self, event: &web_sys::Event, options: EventOptions);
+
+    /// Returns `true` if timers and external futures are driven by an event
+    /// loop.
+    ///
+    /// Without an event loop timers never fire, and external futures run on
+    /// the app executor.
+    fn has_event_loop(&self) -> bool;
+
+    /// Returns `true` if event handlers receive DOM events.
+    ///
+    /// In-memory documents pass a placeholder instead, which can't be
+    /// inspected or cast to a specific event type.
+    fn has_dom_events(&self) -> bool;
+}
+
+fn wrong_backend() -> ! {
+    panic!("node was not created by the backend of the active app");
+}
+
+/// Renders into the browser DOM.
+pub struct WebBackend;
+
+impl WebBackend {
+    fn elem(node: &DomNode) -> &web_sys::Element {
+        match node {
+            DomNode::Web(node) => node.unchecked_ref(),
+            DomNode::Memory(_) => wrong_backend(),
+        }
+    }
+
+    fn target(target: &EventTarget) -> &web_sys::EventTarget {
+        match target {
+            EventTarget::Web(target) => target,
+            EventTarget::Memory(_) => wrong_backend(),
+        }
+    }
+
+    fn prop_key(name: PropName) -> wasm_bindgen::JsValue {
+        match name {
+            PropName::Known(prop) => prop.as_js_string().clone().into(),
+            PropName::Custom(name) => wasm_bindgen::JsValue::from_str(name),
+        }
+    }
+
+    fn closure(listener: &EventListener) -> &js_sys::Function {
+        match listener {
+            EventListener::Web(closure) => closure.as_ref().unchecked_ref(),
+            EventListener::Memory(_) => wrong_backend(),
+        }
+    }
+}
+
+impl Backend for WebBackend {
+    fn create_element(&self, tag: Tag, namespace: Namespace) -> DomNode {
+        if let Some(node) = hydration::claim_element(tag, namespace) {
+            return DomNode::Web(node);
+        }
+        let elem = match namespace {
+            Namespace::Html => super::__brass_create_element(tag.as_js_string()),
+            _ => super::__brass_create_element_ns(namespace.uri(), tag.as_js_string()),
+        };
+        DomNode::Web(elem.unchecked_into())
+    }
+
+    fn create_text(&self, value: DomStr<'_>) -> DomNode {
+        if hydration::is_hydrating() {
+            if let Some(node) = hydration::claim_text(&value.to_cow()) {
+                return DomNode::Web(node);
+            }
+        }
+        let text: web_sys::Text = match value {
+            DomStr::String(value) => super::__brass_create_text_node_str(&value),
+            DomStr::Str(value) => super::__brass_create_text_node_str(value),
+            DomStr::JsStr(value) => super::__brass_create_text_node_js(value),
+            DomStr::JsString(value) => super::__brass_create_text_node_js(&value),
+        };
+        DomNode::Web(text.into())
+    }
+
+    fn create_placeholder(&self) -> DomNode {
+        match hydration::claim_comment() {
+            Some(node) => DomNode::Web(node),
+            // TODO: use cache of nodes!
+            None => DomNode::Web(super::__brass_create_empty_node()),
+        }
+    }
+
+    fn set_attribute(&self, elem: &DomNode, attr: Attr, value: DomStr<'_>) {
+        let elem = Self::elem(elem);
+        if let Some(namespace) = attr.namespace() {
+            elem.set_attribute_ns(Some(namespace), attr.as_str(), &value.to_cow())
+                .unwrap();
+            return;
+        }
+        match value {
+            DomStr::Str(value) => {
+                // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
+                super::__brass_elem_set_attr_str_value(elem, attr.as_js_string(), value);
+            }
+            DomStr::String(value) => {
+                // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
+                super::__brass_elem_set_attr_str_value(elem, attr.as_js_string(), &value);
+            }
+            DomStr::JsStr(value) => {
+                // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
+                super::__brass_elem_set_attr_js_value(elem, attr.as_js_string(), value);
+            }
+            DomStr::JsString(value) => {
+                super::__brass_elem_set_attr_js_value(elem, attr.as_js_string(), &value);
+            }
+        }
+    }
+
+    fn remove_attribute(&self, elem: &DomNode, attr: Attr) {
+        let elem = Self::elem(elem);
+        match attr.namespace() {
+            Some(namespace) => elem
+                .remove_attribute_ns(Some(namespace), attr.local_name())
+                .unwrap(),
+            None => super::__brass_elem_remove_attr(elem, attr.as_js_string()),
+        }
+    }
+
+    fn set_style(&self, elem: &DomNode, style: Style, value: DomStr<'_>) {
+        let elem = Self::elem(elem);
+        match value {
+            DomStr::Str(value) => {
+                // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
+                super::__brass_elem_set_style_str_value(elem, style.as_js_string(), value);
+            }
+            DomStr::String(value) => {
+                // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
+                super::__brass_elem_set_style_str_value(elem, style.as_js_string(), &value);
+            }
+            DomStr::JsStr(value) => {
+                // TODO: use existing JsValue if `Str` is `Str::Repr::Interned`.
+                super::__brass_elem_set_style_js_value(elem, style.as_js_string(), value);
+            }
+            DomStr::JsString(value) => {
+                super::__brass_elem_set_style_js_value(elem, style.as_js_string(), &value);
+            }
+        }
+    }
+
+    fn set_class(&self, elem: &DomNode, value: DomStr<'_>) {
+        let elem = Self::elem(elem);
+        match value {
+            DomStr::Str(value) => elem.set_class_name(value),
+            DomStr::String(value) => elem.set_class_name(&value),
+            DomStr::JsStr(value) => super::__brass_class_set_js(elem, value),
+            DomStr::JsString(value) => super::__brass_class_set_js(elem, &value),
+        }
+    }
+
+    fn add_class(&self, elem: &DomNode, value: &DomStr<'_>) {
+        let elem = Self::elem(elem);
+        match value {
+            DomStr::Str(value) => super::__brass_class_list_add_str(elem, value),
+            DomStr::String(value) => super::__brass_class_list_add_str(elem, value),
+            DomStr::JsStr(value) => super::__brass_class_list_add_js(elem, value),
+            DomStr::JsString(value) => super::__brass_class_list_add_js(elem, value),
+        }
+    }
+
+    fn remove_class(&self, elem: &DomNode, value: &DomStr<'_>) {
+        let elem = Self::elem(elem);
+        match value {
+            DomStr::Str(value) => super::__brass_class_list_remove_str(elem, value),
+            DomStr::String(value) => super::__brass_class_list_remove_str(elem, value),
+            DomStr::JsStr(value) => super::__brass_class_list_remove_js(elem, value),
+            DomStr::JsString(value) => super::__brass_class_list_remove_js(elem, value),
+        }
+    }
+
+    fn set_text(&self, text: &DomNode, value: &DomStr<'_>) {
+        let text = match text {
+            DomNode::Web(node) => node.unchecked_ref::<web_sys::Text>(),
+            DomNode::Memory(_) => wrong_backend(),
+        };
+        match value {
+            DomStr::String(v) => text.set_data(v),
+            DomStr::Str(v) => text.set_data(v),
+            DomStr::JsStr(v) => super::__brass_set_text_data(text, v),
+            DomStr::JsString(v) => super::__brass_set_text_data(text, v),
+        }
+    }
+
+    fn set_property(&self, elem: &DomNode, name: PropName, value: PropValue) {
+        let elem = Self::elem(elem);
+        js_sys::Reflect::set(elem, &Self::prop_key(name), &value.into_js()).unwrap();
+    }
+
+    fn get_property(&self, elem: &DomNode, name: PropName) -> PropValue {
+        let elem = Self::elem(elem);
+        PropValue::from_js(js_sys::Reflect::get(elem, &Self::prop_key(name)).unwrap())
+    }
+
+    fn create_event_listener(&self, callback: Box<dyn FnMut(web_sys::Event)>) -> EventListener {
+        EventListener::Web(Closure::wrap(callback))
+    }
+
+    fn add_event_listener(
+        &self,
+        target: &EventTarget,
+        event: Ev,
+        listener: &EventListener,
+        options: EventOptions,
+    ) {
+        super::add_event_listener_with_options(
+            Self::target(target),
+            event,
+            Self::closure(listener),
+            options,
+        );
+    }
+
+    fn remove_event_listener(
+        &self,
+        target: &EventTarget,
+        event: Ev,
+        listener: &EventListener,
+        options: EventOptions,
+    ) {
+        Self::target(target)
+            .remove_event_listener_with_callback_and_bool(
+                event.as_str(),
+                Self::closure(listener),
+                options.capture,
+            )
+            .ok();
+    }
+
+    fn apply_event_options(&self, event: &web_sys::Event, options: EventOptions) {
+        if options.prevent_default {
+            event.prevent_default();
+        }
+        if options.stop_propagation {
+            event.stop_propagation();
+        }
+    }
+
+    fn has_event_loop(&self) -> bool {
+        true
+    }
+
+    fn has_dom_events(&self) -> bool {
+        true
+    }
+}
+
+/// Renders into an in-memory document.
+///
+/// Events are only dispatched with [`MemoryNode::dispatch_event`], timers
+/// never fire.
+pub struct MemoryBackend;
+
+impl MemoryBackend {
+    fn node(node: &DomNode) -> &MemoryNode {
+        match node {
+            DomNode::Memory(node) => node,
+            DomNode::Web(_) => wrong_backend(),
+        }
+    }
+
+    fn parts<'a>(
+        target: &'a EventTarget,
+        listener: &'a EventListener,
+    ) -> (&'a MemoryNode, &'a MemoryListener) {
+        match (target, listener) {
+            (EventTarget::Memory(node), EventListener::Memory(listener)) => (node, listener),
+            _ => wrong_backend(),
+        }
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn create_element(&self, tag: Tag, namespace: Namespace) -> DomNode {
+        DomNode::Memory(MemoryNode::new_element_ns(tag.as_str(), namespace))
+    }
+
+    fn create_text(&self, value: DomStr<'_>) -> DomNode {
+        DomNode::Memory(MemoryNode::new_text(value.into_string()))
+    }
+
+    fn create_placeholder(&self) -> DomNode {
+        DomNode::Memory(MemoryNode::new_comment(""))
+    }
+
+    fn set_attribute(&self, elem: &DomNode, attr: Attr, value: DomStr<'_>) {
+        Self::node(elem).set_attribute(attr.as_str(), value.into_string());
+    }
+
+    fn remove_attribute(&self, elem: &DomNode, attr: Attr) {
+        Self::node(elem).remove_attribute(attr.as_str());
+    }
+
+    fn set_style(&self, elem: &DomNode, style: Style, value: DomStr<'_>) {
+        Self::node(elem).set_style_property(style.as_str(), &value.to_cow());
+    }
+
+    fn set_class(&self, elem: &DomNode, value: DomStr<'_>) {
+        Self::node(elem).set_attribute("class", value.into_string());
+    }
+
+    fn add_class(&self, elem: &DomNode, value: &DomStr<'_>) {
+        Self::node(elem).add_class(&value.to_cow());
+    }
+
+    fn remove_class(&self, elem: &DomNode, value: &DomStr<'_>) {
+        Self::node(elem).remove_class(&value.to_cow());
+    }
+
+    fn set_text(&self, text: &DomNode, value: &DomStr<'_>) {
+        Self::node(text).set_data(value.to_cow().into_owned());
+    }
+
+    fn set_property(&self, elem: &DomNode, name: PropName, value: PropValue) {
+        Self::node(elem).set_property(name.as_str(), &value);
+    }
+
+    fn get_property(&self, elem: &DomNode, name: PropName) -> PropValue {
+        Self::node(elem).property(name.as_str())
+    }
+
+    fn create_event_listener(&self, callback: Box<dyn FnMut(web_sys::Event)>) -> EventListener {
+        EventListener::Memory(Rc::new(RefCell::new(callback)))
+    }
+
+    fn add_event_listener(
+        &self,
+        target: &EventTarget,
+        event: Ev,
+        listener: &EventListener,
+        options: EventOptions,
+    ) {
+        let (node, listener) = Self::parts(target, listener);
+        node.add_listener(event, listener.clone(), options);
+    }
+
+    fn remove_event_listener(
+        &self,
+        target: &EventTarget,
+        event: Ev,
+        listener: &EventListener,
+        _options: EventOptions,
+    ) {
+        let (node, listener) = Self::parts(target, listener);
+        node.remove_listener(event, listener);
+    }
+
+    /// There is no event to apply options to, `stop_propagation` is handled
+    /// by [`MemoryNode::dispatch_event`] instead.
+    fn apply_event_options(&self, _event: &web_sys::Event, _options: EventOptions) {}
+
+    fn has_event_loop(&self) -> bool {
+        false
+    }
+
+    fn has_dom_events(&self) -> bool {
+        false
+    }
+}
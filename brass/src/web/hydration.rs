@@ -0,0 +1,310 @@
+//! Hydration of server rendered markup.
+//!
+//! While hydrating, nodes are not created but adopted from the existing
+//! markup in the order they are created. Since the same view code produced
+//! the markup on the server, this is the order of the nodes in the document.
+//!
+//! Insertions of adopted nodes are skipped, since they are already in place.
+//! If the rendered view turns out to differ from the markup, hydration is
+//! marked as failed and the view is rendered from scratch (see
+//! [`crate::hydrate`]).
+
+use std::{pin::Pin, task::Poll};
+
+use futures_signals::{
+    signal::Signal,
+    signal_vec::{SignalVec, VecDiff},
+};
+
+use crate::{
+    context::AppContext,
+    dom::{Namespace, Tag},
+};
+
+pub(crate) struct Hydration {
+    root: web_sys::Node,
+    /// The next node to adopt, in document order.
+    next: Option<web_sys::Node>,
+    /// The first node that could not be adopted.
+    ///
+    /// Not necessarily an error, since nodes can be created without being
+    /// inserted into the document.
+    first_mismatch: Option<String>,
+    /// Set once the view is known to differ from the markup.
+    error: Option<String>,
+    /// Depth of [`detached`] scopes.
+    detached: usize,
+}
+
+enum Expected<'a> {
+    Element(Tag, Namespace),
+    Text(&'a str),
+    Comment,
+}
+
+impl std::fmt::Display for Expected<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expected::Element(tag, _) => write!(f, "element <{}>", tag.as_str()),
+            Expected::Text(value) => write!(f, "text {value:?}"),
+            Expected::Comment => write!(f, "placeholder comment"),
+        }
+    }
+}
+
+fn describe(node: &web_sys::Node) -> String {
+    match node.node_type() {
+        web_sys::Node::ELEMENT_NODE => {
+            format!("element <{}>", node.node_name().to_ascii_lowercase())
+        }
+        web_sys::Node::TEXT_NODE => format!("text {:?}", node.text_content().unwrap_or_default()),
+        web_sys::Node::COMMENT_NODE => "comment".to_string(),
+        _ => node.node_name(),
+    }
+}
+
+fn is_empty_text(node: &web_sys::Node) -> bool {
+    node.node_type() == web_sys::Node::TEXT_NODE
+        && node.text_content().map(|t| t.is_empty()).unwrap_or(true)
+}
+
+impl Hydration {
+    pub fn new(root: web_sys::Node) -> Self {
+        Self {
+            next: root.first_child(),
+            root,
+            first_mismatch: None,
+            error: None,
+            detached: 0,
+        }
+    }
+
+    /// Move the cursor to the node following `node` in document order.
+    fn advance(&mut self, node: &web_sys::Node) {
+        if let Some(child) = node.first_child() {
+            self.next = Some(child);
+            return;
+        }
+        let mut current = node.clone();
+        self.next = loop {
+            if let Some(sibling) = current.next_sibling() {
+                break Some(sibling);
+            }
+            match current.parent_node() {
+                Some(parent) if parent != self.root => current = parent,
+                _ => break None,
+            }
+        };
+    }
+
+    fn claim(&mut self, expected: Expected<'_>) -> Option<web_sys::Node> {
+        if self.error.is_some() || self.detached > 0 {
+            return None;
+        }
+
+        let next = self.next.clone();
+        let matches = next.as_ref().is_some_and(|next| match &expected {
+            Expected::Element(tag, namespace) => {
+                next.node_type() == web_sys::Node::ELEMENT_NODE
+                    && next.node_name().eq_ignore_ascii_case(tag.as_str())
+                    && wasm_bindgen::JsCast::unchecked_ref::<web_sys::Element>(next)
+                        .namespace_uri()
+                        .as_deref()
+                        == Some(namespace.uri())
+            }
+            Expected::Text(value) => {
+                next.node_type() == web_sys::Node::TEXT_NODE
+                    && next
+                        .text_content()
+                        .is_some_and(|data| data.starts_with(value))
+            }
+            Expected::Comment => next.node_type() == web_sys::Node::COMMENT_NODE,
+        });
+
+        let Some(next) = next.filter(|_| matches) else {
+            if self.first_mismatch.is_none() {
+                let found = self
+                    .next
+                    .as_ref()
+                    .map_or_else(|| "the end of the markup".to_string(), describe);
+                self.first_mismatch = Some(format!("expected {expected}, found {found}"));
+            }
+            return None;
+        };
+
+        if let Expected::Text(value) = expected {
+            // Adjacent text nodes are merged in the markup, so only the
+            // expected part is adopted.
+            let len = value.encode_utf16().count() as u32;
+            if next
+                .text_content()
+                .unwrap_or_default()
+                .encode_utf16()
+                .count() as u32
+                > len
+            {
+                wasm_bindgen::JsCast::unchecked_ref::<web_sys::Text>(&next)
+                    .split_text(len)
+                    .unwrap();
+            }
+        }
+
+        self.advance(&next);
+        Some(next)
+    }
+
+    /// Returns `true` if the insertion should be skipped.
+    fn insert(
+        &mut self,
+        parent: &web_sys::Node,
+        child: &web_sys::Node,
+        before: Option<&web_sys::Node>,
+    ) -> bool {
+        // The view will be discarded anyway.
+        if self.error.is_some() {
+            return true;
+        }
+        // The parent isn't known yet, the nodes are inserted again once the
+        // view is attached.
+        if parent.is_null() {
+            return true;
+        }
+        // Adopted nodes are already in place.
+        if child.parent_node().as_ref() == Some(parent) {
+            return true;
+        }
+
+        let parent_adopted = self.root.contains(Some(parent));
+        let child_adopted = self.root.contains(Some(child));
+        if !parent_adopted && !child_adopted {
+            // Building a new subtree outside of the document.
+            return false;
+        }
+
+        if parent_adopted && !child_adopted && is_empty_text(child) {
+            // Empty text doesn't show up in the markup, so it is inserted at
+            // the current position instead.
+            let before = before.or_else(|| {
+                self.next
+                    .as_ref()
+                    .filter(|next| next.parent_node().as_ref() == Some(parent))
+            });
+            parent.insert_before(child, before).unwrap();
+            return true;
+        }
+
+        let found = if child_adopted {
+            format!("{} in a different position", describe(child))
+        } else {
+            format!("no matching node for {}", describe(child))
+        };
+        self.fail(format!("{found} inside {}", describe(parent)));
+        true
+    }
+
+    fn fail(&mut self, detail: String) {
+        if self.error.is_some() {
+            return;
+        }
+        let error = match self.first_mismatch.take() {
+            Some(mismatch) => format!("{mismatch} ({detail})"),
+            None => detail,
+        };
+        self.error = Some(error);
+    }
+
+    /// Finish hydration.
+    ///
+    /// Returns an error describing the first mismatch if the view didn't
+    /// match the markup.
+    pub fn finish(mut self) -> Result<(), String> {
+        if let Some(next) = self.next.take() {
+            if self.error.is_none() {
+                self.fail(format!(
+                    "found {} which was not rendered by the view",
+                    describe(&next)
+                ));
+            }
+        }
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns `true` while the active app is hydrating markup.
+pub(crate) fn is_hydrating() -> bool {
+    AppContext::hydration().is_some()
+}
+
+pub(crate) fn claim_element(tag: Tag, namespace: Namespace) -> Option<web_sys::Node> {
+    AppContext::hydration()?.claim(Expected::Element(tag, namespace))
+}
+
+pub(crate) fn claim_text(value: &str) -> Option<web_sys::Node> {
+    if value.is_empty() {
+        // Empty text is not present in the markup.
+        return None;
+    }
+    AppContext::hydration()?.claim(Expected::Text(value))
+}
+
+pub(crate) fn claim_comment() -> Option<web_sys::Node> {
+    AppContext::hydration()?.claim(Expected::Comment)
+}
+
+/// Returns `true` if the insertion should be skipped.
+pub(crate) fn intercept_insert(
+    parent: &web_sys::Node,
+    child: &web_sys::Node,
+    before: Option<&web_sys::Node>,
+) -> bool {
+    AppContext::hydration().is_some_and(|h| h.insert(parent, child, before))
+}
+
+/// Returns `true` if removals should be skipped.
+pub(crate) fn intercept_remove() -> bool {
+    AppContext::hydration().is_some_and(|h| h.error.is_some())
+}
+
+/// Create nodes without adopting them from the markup.
+///
+/// Used for nodes that are created, but not necessarily inserted into the
+/// document.
+pub(crate) fn detached<R>(f: impl FnOnce() -> R) -> R {
+    if let Some(h) = AppContext::hydration() {
+        h.detached += 1;
+        let out = f();
+        // Re-acquire, since `f` might have accessed the context.
+        if let Some(h) = AppContext::hydration() {
+            h.detached -= 1;
+        }
+        out
+    } else {
+        f()
+    }
+}
+
+/// Poll the current value of a signal without waiting.
+///
+/// Used to render signals immediately while hydrating, so nodes are created
+/// in the same order as when rendering on the server.
+pub(crate) fn poll_now<S: Signal>(signal: Pin<&mut S>) -> Option<S::Item> {
+    let waker = futures::task::noop_waker();
+    let mut cx = std::task::Context::from_waker(&waker);
+    match signal.poll_change(&mut cx) {
+        Poll::Ready(value) => value,
+        Poll::Pending => None,
+    }
+}
+
+/// Like [`poll_now`], for [`SignalVec`]s.
+pub(crate) fn poll_vec_now<S: SignalVec>(signal: Pin<&mut S>) -> Option<VecDiff<S::Item>> {
+    let waker = futures::task::noop_waker();
+    let mut cx = std::task::Context::from_waker(&waker);
+    match signal.poll_vec_change(&mut cx) {
+        Poll::Ready(diff) => diff,
+        Poll::Pending => None,
+    }
+}
@@ -0,0 +1,653 @@
+//! An in-memory document that works without a browser.
+//!
+//! Views are rendered into memory nodes when no browser DOM is available,
+//! for example for server side rendering (see [`crate::ssr`]) or in tests
+//! (see [`crate::launch_memory`]).
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::dom::{Ev, EventOptions, Namespace, PropValue};
+
+use super::backend::MemoryListener;
+
+/// Elements that never have children and are serialized without a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements with text content that is serialized without escaping, see
+/// [`escape_raw_text`].
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// A node of an in-memory document.
+///
+/// This is a cheap reference counted handle, clones refer to the same node.
+#[derive(Clone)]
+pub struct MemoryNode(Rc<RefCell<NodeData>>);
+
+struct NodeData {
+    parent: Weak<RefCell<NodeData>>,
+    kind: NodeKind,
+    children: Vec<MemoryNode>,
+    listeners: Vec<(Ev, MemoryListener, EventOptions)>,
+    /// Set for the root of a document, see [`MemoryNode::new_document`].
+    is_document: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A container without markup of its own.
+    /// Only the children are serialized.
+    Fragment,
+    Element {
+        tag: &'static str,
+        namespace: Namespace,
+        attributes: Vec<(&'static str, String)>,
+    },
+    Text(String),
+    Comment(String),
+}
+
+impl MemoryNode {
+    fn new(kind: NodeKind) -> Self {
+        Self(Rc::new(RefCell::new(NodeData {
+            parent: Weak::new(),
+            kind,
+            children: Vec::new(),
+            listeners: Vec::new(),
+            is_document: false,
+        })))
+    }
+
+    pub fn new_fragment() -> Self {
+        Self::new(NodeKind::Fragment)
+    }
+
+    /// A fragment that acts as a document.
+    ///
+    /// Nodes inside it are connected (see [`Self::is_connected`]), so mount
+    /// callbacks of views rendered into it run.
+    pub fn new_document() -> Self {
+        let node = Self::new(NodeKind::Fragment);
+        node.0.borrow_mut().is_document = true;
+        node
+    }
+
+    pub fn new_element(tag: &'static str) -> Self {
+        Self::new_element_ns(tag, Namespace::Html)
+    }
+
+    pub fn new_element_ns(tag: &'static str, namespace: Namespace) -> Self {
+        Self::new(NodeKind::Element {
+            tag,
+            namespace,
+            attributes: Vec::new(),
+        })
+    }
+
+    pub fn new_text(value: impl Into<String>) -> Self {
+        Self::new(NodeKind::Text(value.into()))
+    }
+
+    pub fn new_comment(value: impl Into<String>) -> Self {
+        Self::new(NodeKind::Comment(value.into()))
+    }
+
+    /// Returns `true` if both handles refer to the same node.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.borrow().kind.clone()
+    }
+
+    /// The tag name, if this node is an element.
+    pub fn tag(&self) -> Option<&'static str> {
+        match &self.0.borrow().kind {
+            NodeKind::Element { tag, .. } => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// The namespace, if this node is an element.
+    pub fn namespace(&self) -> Option<Namespace> {
+        match &self.0.borrow().kind {
+            NodeKind::Element { namespace, .. } => Some(*namespace),
+            _ => None,
+        }
+    }
+
+    pub fn parent(&self) -> Option<MemoryNode> {
+        self.0.borrow().parent.upgrade().map(MemoryNode)
+    }
+
+    /// Whether the node is inside a document.
+    pub fn is_connected(&self) -> bool {
+        let mut node = self.clone();
+        while let Some(parent) = node.parent() {
+            node = parent;
+        }
+        let is_document = node.0.borrow().is_document;
+        is_document
+    }
+
+    pub fn children(&self) -> Vec<MemoryNode> {
+        self.0.borrow().children.clone()
+    }
+
+    /// Find the first descendant element with the given tag, in document
+    /// order.
+    pub fn find_element(&self, tag: &str) -> Option<MemoryNode> {
+        for child in self.children() {
+            if child.tag() == Some(tag) {
+                return Some(child);
+            }
+            if let Some(found) = child.find_element(tag) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    // Tree manipulation.
+
+    fn detach(&self) {
+        if let Some(parent) = self.parent() {
+            parent.0.borrow_mut().children.retain(|c| !c.ptr_eq(self));
+            self.0.borrow_mut().parent = Weak::new();
+        }
+    }
+
+    fn position(&self, child: &MemoryNode) -> Option<usize> {
+        self.0
+            .borrow()
+            .children
+            .iter()
+            .position(|c| c.ptr_eq(child))
+    }
+
+    pub fn append_child(&self, child: &MemoryNode) {
+        self.insert_before(child, None);
+    }
+
+    /// Insert `child` before `before`, or at the end if `before` is `None`.
+    ///
+    /// The child is removed from its current parent first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `before` is not a child of this node.
+    pub fn insert_before(&self, child: &MemoryNode, before: Option<&MemoryNode>) {
+        child.detach();
+        let index = match before {
+            Some(before) => self
+                .position(before)
+                .expect("insert_before: reference node is not a child of this node"),
+            None => self.0.borrow().children.len(),
+        };
+        child.0.borrow_mut().parent = Rc::downgrade(&self.0);
+        self.0.borrow_mut().children.insert(index, child.clone());
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `old` is not a child of this node.
+    pub fn replace_child(&self, new: &MemoryNode, old: &MemoryNode) {
+        if new.ptr_eq(old) {
+            return;
+        }
+        self.insert_before(new, Some(old));
+        self.remove_child(old);
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `child` is not a child of this node.
+    pub fn remove_child(&self, child: &MemoryNode) {
+        let index = self
+            .position(child)
+            .expect("remove_child: node is not a child of this node");
+        self.0.borrow_mut().children.remove(index);
+        child.0.borrow_mut().parent = Weak::new();
+    }
+
+    // Attributes.
+
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        match &self.0.borrow().kind {
+            NodeKind::Element { attributes, .. } => attributes
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn set_attribute(&self, name: &'static str, value: String) {
+        if let NodeKind::Element { attributes, .. } = &mut self.0.borrow_mut().kind {
+            if let Some(attr) = attributes.iter_mut().find(|(n, _)| *n == name) {
+                attr.1 = value;
+            } else {
+                attributes.push((name, value));
+            }
+        }
+    }
+
+    pub fn remove_attribute(&self, name: &str) {
+        if let NodeKind::Element { attributes, .. } = &mut self.0.borrow_mut().kind {
+            attributes.retain(|(n, _)| *n != name);
+        }
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.attribute("class")
+            .map(|classes| classes.split_ascii_whitespace().any(|c| c == class))
+            .unwrap_or(false)
+    }
+
+    pub fn add_class(&self, class: &str) {
+        if class.is_empty() || self.has_class(class) {
+            return;
+        }
+        let value = match self.attribute("class") {
+            Some(classes) if !classes.trim().is_empty() => format!("{} {}", classes.trim(), class),
+            _ => class.to_string(),
+        };
+        self.set_attribute("class", value);
+    }
+
+    pub fn remove_class(&self, class: &str) {
+        if let Some(classes) = self.attribute("class") {
+            let value = classes
+                .split_ascii_whitespace()
+                .filter(|c| *c != class)
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.set_attribute("class", value);
+        }
+    }
+
+    /// Set a property of the `style` attribute.
+    ///
+    /// An empty value removes the property, like `style.setProperty` does.
+    pub fn set_style_property(&self, name: &str, value: &str) {
+        let current = self.attribute("style").unwrap_or_default();
+        let mut properties: Vec<(String, String)> = current
+            .split(';')
+            .filter_map(|decl| {
+                let (name, value) = decl.split_once(':')?;
+                Some((name.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+
+        let value = value.trim();
+        if let Some(index) = properties.iter().position(|(n, _)| n == name) {
+            if value.is_empty() {
+                properties.remove(index);
+            } else {
+                properties[index].1 = value.to_string();
+            }
+        } else if !value.is_empty() {
+            properties.push((name.to_string(), value.to_string()));
+        }
+
+        let style = properties
+            .iter()
+            .map(|(name, value)| format!("{name}: {value};"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.set_attribute("style", style);
+    }
+
+    // Properties.
+
+    /// Set a DOM property.
+    ///
+    /// There are no properties in markup, so properties that are reflected
+    /// by attributes or content in the browser update those instead. Other
+    /// properties are ignored.
+    pub fn set_property(&self, name: &str, value: &PropValue) {
+        let tag = self.tag();
+        match name {
+            "value" if tag == Some("textarea") => {
+                self.set_text(&value.to_attribute().unwrap_or_default());
+            }
+            "value" if tag == Some("select") => {
+                let value = value.to_attribute();
+                for option in self.options() {
+                    let selected = value.as_deref() == Some(option.option_value().as_str());
+                    option.set_flag("selected", selected);
+                }
+            }
+            "value" | "defaultValue" => match value.to_attribute() {
+                Some(value) => self.set_attribute("value", value),
+                None => self.remove_attribute("value"),
+            },
+            "selectedIndex" => {
+                let index = match value {
+                    PropValue::Number(index) => *index as i64,
+                    _ => -1,
+                };
+                for (i, option) in self.options().iter().enumerate() {
+                    option.set_flag("selected", i as i64 == index);
+                }
+            }
+            "checked" | "defaultChecked" => {
+                self.set_flag("checked", matches!(value, PropValue::Bool(true)));
+            }
+            "selected" | "disabled" | "hidden" | "open" | "muted" => {
+                let attr = match name {
+                    "selected" => "selected",
+                    "disabled" => "disabled",
+                    "hidden" => "hidden",
+                    "open" => "open",
+                    _ => "muted",
+                };
+                self.set_flag(attr, matches!(value, PropValue::Bool(true)));
+            }
+            _ => {}
+        }
+    }
+
+    /// Read a DOM property, see [`Self::set_property`].
+    ///
+    /// Unknown properties are `null`. Unlike browsers, in-memory documents
+    /// don't select the first option of a `<select>` by default.
+    pub fn property(&self, name: &str) -> PropValue {
+        let tag = self.tag();
+        match name {
+            "value" if tag == Some("textarea") => PropValue::Str(self.text_content()),
+            "value" if tag == Some("select") => PropValue::Str(
+                self.options()
+                    .iter()
+                    .find(|option| option.attribute("selected").is_some())
+                    .map(|option| option.option_value())
+                    .unwrap_or_default(),
+            ),
+            "value" | "defaultValue" => PropValue::Str(self.attribute("value").unwrap_or_default()),
+            "selectedIndex" => {
+                let index = self
+                    .options()
+                    .iter()
+                    .position(|option| option.attribute("selected").is_some());
+                PropValue::Number(index.map_or(-1.0, |index| index as f64))
+            }
+            "checked" | "defaultChecked" => PropValue::Bool(self.attribute("checked").is_some()),
+            "selected" | "disabled" | "hidden" | "open" | "muted" => {
+                PropValue::Bool(self.attribute(name).is_some())
+            }
+            _ => PropValue::Null,
+        }
+    }
+
+    fn set_flag(&self, name: &'static str, flag: bool) {
+        if flag {
+            self.set_attribute(name, String::new());
+        } else {
+            self.remove_attribute(name);
+        }
+    }
+
+    /// Replace all children with a single text node.
+    fn set_text(&self, value: &str) {
+        for child in self.children() {
+            self.remove_child(&child);
+        }
+        self.append_child(&MemoryNode::new_text(value));
+    }
+
+    /// All `<option>` descendants, in document order.
+    fn options(&self) -> Vec<MemoryNode> {
+        let mut out = Vec::new();
+        self.collect_options(&mut out);
+        out
+    }
+
+    fn collect_options(&self, out: &mut Vec<MemoryNode>) {
+        for child in self.children() {
+            if child.tag() == Some("option") {
+                out.push(child.clone());
+            }
+            child.collect_options(out);
+        }
+    }
+
+    fn option_value(&self) -> String {
+        self.attribute("value")
+            .unwrap_or_else(|| self.text_content())
+    }
+
+    // Text.
+
+    /// Set the content of a text or comment node.
+    pub fn set_data(&self, value: String) {
+        match &mut self.0.borrow_mut().kind {
+            NodeKind::Text(data) | NodeKind::Comment(data) => *data = value,
+            _ => {}
+        }
+    }
+
+    /// The concatenated content of all descendant text nodes.
+    pub fn text_content(&self) -> String {
+        let mut out = String::new();
+        self.write_text_content(&mut out);
+        out
+    }
+
+    fn write_text_content(&self, out: &mut String) {
+        let data = self.0.borrow();
+        match &data.kind {
+            NodeKind::Text(text) => out.push_str(text),
+            NodeKind::Comment(_) => {}
+            NodeKind::Fragment | NodeKind::Element { .. } => {
+                for child in &data.children {
+                    child.write_text_content(out);
+                }
+            }
+        }
+    }
+
+    // Events.
+
+    pub(crate) fn add_listener(&self, event: Ev, listener: MemoryListener, options: EventOptions) {
+        self.0
+            .borrow_mut()
+            .listeners
+            .push((event, listener, options));
+    }
+
+    pub(crate) fn remove_listener(&self, event: Ev, listener: &MemoryListener) {
+        self.0
+            .borrow_mut()
+            .listeners
+            .retain(|(ev, l, _)| !(*ev == event && Rc::ptr_eq(l, listener)));
+    }
+
+    /// Dispatch an event to the listeners of this node and its ancestors.
+    ///
+    /// Capture listeners are invoked first, from the root down to this node,
+    /// then the other listeners from this node up to the root. Listeners
+    /// with [`EventOptions::stop_propagation`] stop the dispatch after the
+    /// listeners of their node, and [`EventOptions::once`] listeners are
+    /// removed before they run.
+    ///
+    /// There is no browser event in an in-memory document, so handlers
+    /// receive an empty placeholder. Handlers that ignore the event, like
+    /// `on_click(move || ...)`, work as expected, while accessing the event
+    /// panics on native targets.
+    ///
+    /// Must be called outside of [`AppContextRef::with`](crate::context::AppContextRef::with),
+    /// since handlers enter the app context themselves.
+    pub fn dispatch_event(&self, event: Ev) {
+        let mut path = Vec::new();
+        let mut current = Some(self.clone());
+        while let Some(node) = current {
+            current = node.parent();
+            path.push(node);
+        }
+
+        let capture = path.iter().rev().map(|node| (node, true));
+        let bubble = path.iter().map(|node| (node, false));
+        for (node, capture) in capture.chain(bubble) {
+            if node.invoke_listeners(event, capture) {
+                break;
+            }
+        }
+    }
+
+    /// Invoke the listeners of one phase, returns whether propagation was
+    /// stopped.
+    fn invoke_listeners(&self, event: Ev, capture: bool) -> bool {
+        // Listeners may modify the tree, so they are collected first.
+        let listeners: Vec<_> = self
+            .0
+            .borrow()
+            .listeners
+            .iter()
+            .filter(|(ev, _, options)| *ev == event && options.capture == capture)
+            .map(|(_, l, options)| (l.clone(), *options))
+            .collect();
+
+        let mut stopped = false;
+        for (listener, options) in listeners {
+            if options.once {
+                self.remove_listener(event, &listener);
+            }
+            let placeholder = JsValue::UNDEFINED.unchecked_into::<web_sys::Event>();
+            match listener.try_borrow_mut() {
+                Ok(mut listener) => (listener)(placeholder),
+                Err(_) => tracing::warn!("recursive dispatch of {} ignored", event.as_str()),
+            }
+            stopped |= options.stop_propagation;
+        }
+        stopped
+    }
+
+    // Serialization.
+
+    /// Serialize this node, including its own markup, to HTML.
+    pub fn outer_html(&self) -> String {
+        let mut out = String::new();
+        self.write_html(&mut out, false);
+        out
+    }
+
+    /// Serialize the children of this node to HTML.
+    pub fn inner_html(&self) -> String {
+        let data = self.0.borrow();
+        let raw_text = matches!(
+            &data.kind,
+            NodeKind::Element { tag, namespace: Namespace::Html, .. } if RAW_TEXT_ELEMENTS.contains(tag)
+        );
+        let mut out = String::new();
+        for child in &data.children {
+            child.write_html(&mut out, raw_text);
+        }
+        out
+    }
+
+    fn write_html(&self, out: &mut String, raw_text: bool) {
+        let data = self.0.borrow();
+        match &data.kind {
+            NodeKind::Fragment => {
+                for child in &data.children {
+                    child.write_html(out, raw_text);
+                }
+            }
+            NodeKind::Element {
+                tag,
+                namespace,
+                attributes,
+            } => {
+                out.push('<');
+                out.push_str(tag);
+                for (name, value) in attributes {
+                    out.push(' ');
+                    out.push_str(name);
+                    out.push_str("=\"");
+                    escape_attribute(value, out);
+                    out.push('"');
+                }
+                out.push('>');
+
+                // Void and raw text elements only exist in HTML, foreign
+                // elements are parsed like XML.
+                let html = *namespace == Namespace::Html;
+                if html && VOID_ELEMENTS.contains(tag) {
+                    return;
+                }
+
+                let raw_text = html && RAW_TEXT_ELEMENTS.contains(tag);
+                for child in &data.children {
+                    child.write_html(out, raw_text);
+                }
+
+                out.push_str("</");
+                out.push_str(tag);
+                out.push('>');
+            }
+            NodeKind::Text(text) => {
+                if raw_text {
+                    escape_raw_text(text, out);
+                } else {
+                    escape_text(text, out);
+                }
+            }
+            NodeKind::Comment(text) => {
+                out.push_str("<!--");
+                out.push_str(text);
+                out.push_str("-->");
+            }
+        }
+    }
+}
+
+impl PartialEq for MemoryNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl std::fmt::Debug for MemoryNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MemoryNode")
+            .field(&self.outer_html())
+            .finish()
+    }
+}
+
+fn escape_text(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Keep the text of a raw text element from closing it, which would allow
+/// injecting markup. `<\/` means the same in scripts and styles.
+fn escape_raw_text(value: &str, out: &mut String) {
+    out.push_str(&value.replace("</", "<\\/"));
+}
+
+fn escape_attribute(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c => out.push(c),
+        }
+    }
+}
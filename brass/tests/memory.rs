@@ -0,0 +1,1565 @@
+use std::{cell::RefCell, rc::Rc};
+
+use futures_signals::{
+    signal::{Mutable, SignalExt},
+    signal_vec::MutableVec,
+};
+
+use brass::{
+    component::{msg::MsgComponent, Component, Context, Handle, ShouldRender},
+    context::{provide_context, use_context},
+    dom::{
+        builder::{a, button, div, form, input, option, p, select, span, tag, textarea},
+        with_namespace, ApplyFuture, Attr, ClickEvent, ErrorBoundary, Ev, EventOptions, Namespace,
+        NodeRef, PendingGuard, Portal, PortalTarget, Prop, Suspense, Tag, TagBuilder, Transition,
+        View,
+    },
+    effect::Resource,
+    launch_memory,
+    router::{self, Query, Redirect, Route, RouterMode, Switch},
+    view,
+    web::memory::MemoryNode,
+};
+
+#[test]
+fn test_memory_launch() {
+    let (_ctx, root) = launch_memory(|| div().attr(Attr::Id, "app").text("hello"));
+    assert_eq!(root.inner_html(), r#"<div id="app">hello</div>"#);
+}
+
+#[test]
+fn test_memory_signal() {
+    let text = Mutable::new("a".to_string());
+    let show = Mutable::new(false);
+
+    let (ctx, root) = launch_memory(|| {
+        div()
+            .attr_signal(Attr::Title, text.signal_cloned())
+            .text_signal(text.signal_cloned())
+            .signal(show.signal().map(|show| {
+                if show {
+                    div().text("shown").into()
+                } else {
+                    View::Empty
+                }
+            }))
+    });
+    assert_eq!(root.inner_html(), r#"<div title="a">a<!----></div>"#);
+
+    ctx.with(|| {
+        text.set("b".to_string());
+        show.set(true);
+    });
+    assert_eq!(
+        root.inner_html(),
+        r#"<div title="b">b<div>shown</div></div>"#
+    );
+
+    ctx.with(|| show.set(false));
+    assert_eq!(root.inner_html(), r#"<div title="b">b<!----></div>"#);
+}
+
+#[test]
+fn test_memory_signal_vec() {
+    let items = MutableVec::new_with_values(vec![1, 2]);
+
+    let (ctx, root) = launch_memory(|| {
+        tag(Tag::Ul).signal_vec(items.signal_vec(), |x| tag(Tag::Li).text(x.to_string()))
+    });
+    assert_eq!(root.inner_html(), "<ul><li>1</li><li>2</li><!----></ul>");
+
+    ctx.with(|| {
+        let mut lock = items.lock_mut();
+        lock.push(3);
+        lock.remove(0);
+    });
+    assert_eq!(root.inner_html(), "<ul><li>2</li><li>3</li><!----></ul>");
+
+    ctx.with(|| items.lock_mut().clear());
+    assert_eq!(root.inner_html(), "<ul><!----></ul>");
+}
+
+#[test]
+fn test_memory_events() {
+    let clicks = Mutable::new(0);
+
+    let (_ctx, root) = launch_memory(|| {
+        let inner = clicks.clone();
+        let outer = clicks.clone();
+        div()
+            // Events bubble to ancestors.
+            .on_event(Ev::Click, move |_| *outer.lock_mut() += 10)
+            .tag(
+                button()
+                    .on_event(Ev::Click, move |_| *inner.lock_mut() += 1)
+                    .text_signal(clicks.signal_ref(|x| x.to_string())),
+            )
+    });
+
+    let btn = root.find_element("button").unwrap();
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 11);
+    assert_eq!(btn.text_content(), "11");
+
+    // Other event types are ignored.
+    btn.dispatch_event(Ev::Input);
+    assert_eq!(clicks.get(), 11);
+
+    root.find_element("div").unwrap().dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 21);
+}
+
+#[test]
+fn test_memory_typed_events() {
+    let clicks = Mutable::new(0);
+
+    let (_ctx, root) = launch_memory(|| {
+        let typed = clicks.clone();
+        let cast = clicks.clone();
+        let click = move |_: web_sys::MouseEvent| *cast.lock_mut() += 10;
+        div()
+            .tag(button().on(move |_: ClickEvent| *typed.lock_mut() += 1))
+            .and(view! { p(onclick=click) })
+    });
+
+    // Typed handlers get the placeholder event of in-memory documents.
+    root.find_element("button")
+        .unwrap()
+        .dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 1);
+    root.find_element("p").unwrap().dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 11);
+}
+
+#[test]
+fn test_memory_removed_listeners() {
+    let show = Mutable::new(true);
+    let clicks = Mutable::new(0);
+
+    let (ctx, root) = launch_memory(|| {
+        let clicks = clicks.clone();
+        div().signal(show.signal().map(move |show| {
+            let clicks = clicks.clone();
+            if show {
+                button()
+                    .on_event(Ev::Click, move |_| *clicks.lock_mut() += 1)
+                    .into()
+            } else {
+                View::Empty
+            }
+        }))
+    });
+
+    let btn = root.find_element("button").unwrap();
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 1);
+
+    ctx.with(|| show.set(false));
+    assert!(root.find_element("button").is_none());
+
+    // The listener is removed together with the view.
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 1);
+}
+
+#[test]
+fn test_memory_event_options() {
+    let log = Mutable::new(Vec::<&'static str>::new());
+
+    let (_ctx, root) = launch_memory(|| {
+        let push = |entry: &'static str| {
+            let log = log.clone();
+            move |_| log.lock_mut().push(entry)
+        };
+        div()
+            .on_event(Ev::Click, push("outer"))
+            .on_event_with_options(Ev::Click, EventOptions::new().capture(), push("capture"))
+            .tag(
+                span()
+                    .on_event_with_options(
+                        Ev::Click,
+                        EventOptions::new().stop_propagation(),
+                        push("stop"),
+                    )
+                    .tag(
+                        button()
+                            .on_event(Ev::Click, push("button"))
+                            .on_event_with_options(
+                                Ev::Click,
+                                EventOptions::new().once(),
+                                push("once"),
+                            ),
+                    ),
+            )
+    });
+
+    let btn = root.find_element("button").unwrap();
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(log.get_cloned(), ["capture", "button", "once", "stop"]);
+
+    log.lock_mut().clear();
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(log.get_cloned(), ["capture", "button", "stop"]);
+}
+
+#[test]
+fn test_memory_view_event_modifiers() {
+    let clicks = Mutable::new(0);
+
+    let (_ctx, root) = launch_memory(|| {
+        let inner = clicks.clone();
+        let outer = clicks.clone();
+        view! {
+            div(onclick=move || *outer.lock_mut() += 10) [
+                button(onclick|stop|once=move || *inner.lock_mut() += 1)
+            ]
+        }
+    });
+
+    let btn = root.find_element("button").unwrap();
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 1);
+
+    // The handler is gone, so the click bubbles.
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 11);
+}
+
+#[test]
+fn test_memory_view_multi_word_events() {
+    let keys = Mutable::new(0);
+
+    let (_ctx, root) = launch_memory(|| {
+        let keys = keys.clone();
+        view! { input(onkeydown=move || *keys.lock_mut() += 1) }
+    });
+
+    let elem = root.find_element("input").unwrap();
+    elem.dispatch_event(Ev::KeyDown);
+    elem.dispatch_event(Ev::KeyUp);
+    assert_eq!(keys.get(), 1);
+}
+
+#[test]
+fn test_memory_portal() {
+    let show = Mutable::new(true);
+    let clicks = Mutable::new(0);
+    let overlay = MemoryNode::new_element("div");
+
+    let (ctx, root) = launch_memory(|| {
+        let clicks = clicks.clone();
+        let target = PortalTarget::Node(overlay.clone().into());
+        div().signal(show.signal().map(move |show| {
+            let clicks = clicks.clone();
+            let target = target.clone();
+            if show {
+                Portal::new(target, move || {
+                    let inner = clicks.clone();
+                    button()
+                        .on_event(Ev::Click, move |_| *inner.lock_mut() += 1)
+                        .text_signal(clicks.signal_ref(|x| x.to_string()))
+                })
+                .into()
+            } else {
+                View::Empty
+            }
+        }))
+    });
+
+    // Only a placeholder is rendered in place.
+    assert!(root.find_element("button").is_none());
+    let btn = overlay.find_element("button").unwrap();
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 1);
+    assert_eq!(overlay.inner_html(), "<button>1</button>");
+
+    ctx.with(|| show.set(false));
+    assert_eq!(overlay.inner_html(), "");
+
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(clicks.get(), 1);
+}
+
+#[test]
+fn test_memory_error_boundary() {
+    let reported = Rc::new(RefCell::new(Vec::new()));
+    let hook = reported.clone();
+    brass::error::set_error_hook(move |error| hook.borrow_mut().push(error.to_string()));
+
+    let input = Mutable::new("1".to_string());
+    let (ctx, root) = launch_memory(|| {
+        let input = input.clone();
+        div().and(ErrorBoundary::new(
+            move || {
+                span().signal(
+                    input
+                        .signal_cloned()
+                        .map(|value| value.parse::<u32>().map(|n| p().text(n.to_string()))),
+                )
+            },
+            |error| p().text(format!("failed: {error}")),
+        ))
+    });
+    assert_eq!(root.find_element("p").unwrap().text_content(), "1");
+
+    // Errors of signals rendered later replace the content.
+    ctx.with(|| input.set("x".to_string()));
+    assert_eq!(
+        root.inner_html(),
+        "<div><p>failed: invalid digit found in string</p></div>"
+    );
+
+    // Panicking handlers are reported, and the app keeps working.
+    let (ctx, root) = launch_memory(|| {
+        div()
+            .and(ErrorBoundary::new(
+                || button().on_event(Ev::Click, |_| panic!("handler failed")),
+                |error| p().text(format!("panic: {}", error.is_panic())),
+            ))
+            .tag(span().text_signal(input.signal_cloned()))
+    });
+    root.find_element("button")
+        .unwrap()
+        .dispatch_event(Ev::Click);
+    assert_eq!(
+        root.find_element("p").unwrap().text_content(),
+        "panic: true"
+    );
+    ctx.with(|| input.set("ok".to_string()));
+    assert_eq!(root.find_element("span").unwrap().text_content(), "ok");
+
+    // Errors outside of a boundary only go to the hook.
+    let (_ctx, root) = launch_memory(|| div().and(Err::<View, _>("no boundary")));
+    assert_eq!(root.inner_html(), "<div></div>");
+
+    brass::error::clear_error_hook();
+    assert_eq!(
+        *reported.borrow(),
+        [
+            "invalid digit found in string",
+            "handler failed",
+            "no boundary"
+        ]
+    );
+}
+
+#[test]
+fn test_memory_suspense() {
+    use futures::channel::oneshot;
+
+    let (first_tx, first_rx) = oneshot::channel::<&str>();
+    let (second_tx, second_rx) = oneshot::channel::<()>();
+    let show = Mutable::new(true);
+    let (ctx, root) = launch_memory(|| {
+        let mut first_rx = Some(first_rx);
+        let mut second_rx = Some(second_rx);
+        div().signal(show.signal().map(move |show| {
+            if !show {
+                return View::Empty;
+            }
+            let first_rx = first_rx.take().unwrap();
+            let second_rx = second_rx.take().unwrap();
+            Suspense::new(
+                move || {
+                    // Futures can register without ApplyFuture.
+                    let pending = PendingGuard::new();
+                    span()
+                        .and(ApplyFuture(
+                            async move { p().text(first_rx.await.unwrap()) },
+                        ))
+                        .spawn(async move {
+                            second_rx.await.ok();
+                            drop(pending);
+                        })
+                },
+                || p().text("loading"),
+            )
+            .into()
+        }))
+    });
+    assert_eq!(
+        root.inner_html(),
+        r#"<div><div style="display: contents;"><p>loading</p></div></div>"#
+    );
+
+    // The content is shown once all pending work is done.
+    ctx.with(|| first_tx.send("loaded").unwrap());
+    assert_eq!(root.find_element("p").unwrap().text_content(), "loading");
+    ctx.with(|| second_tx.send(()).unwrap());
+    assert_eq!(
+        root.inner_html(),
+        r#"<div><div style="display: contents;"><span><div><p>loaded</p></div></span></div></div>"#
+    );
+
+    // Removing the suspense aborts pending futures.
+    let (tx, rx) = oneshot::channel::<()>();
+    let show = Mutable::new(true);
+    let (ctx, root) = launch_memory(|| {
+        let mut rx = Some(rx);
+        div().signal(show.signal().map(move |show| {
+            let Some(rx) = rx.take().filter(|_| show) else {
+                return View::Empty;
+            };
+            Suspense::new(
+                move || {
+                    span().and(ApplyFuture(
+                        async move { p().text(format!("{:?}", rx.await)) },
+                    ))
+                },
+                || p().text("loading"),
+            )
+            .into()
+        }))
+    });
+    assert_eq!(root.find_element("p").unwrap().text_content(), "loading");
+    assert!(!tx.is_canceled());
+    ctx.with(|| show.set(false));
+    assert_eq!(root.inner_html(), "<div><!----></div>");
+    assert!(tx.is_canceled());
+}
+
+#[test]
+fn test_memory_suspense_pending_again() {
+    let state = Mutable::new("first");
+    let guard = Rc::new(RefCell::new(None));
+    let (ctx, root) = launch_memory(|| {
+        let state = state.clone();
+        let guard = guard.clone();
+        Suspense::new(
+            move || {
+                ErrorBoundary::new(
+                    move || {
+                        span().signal(state.signal().map(move |state| match state {
+                            "reload" => {
+                                *guard.borrow_mut() = Some(PendingGuard::new());
+                                Ok(p().text("reloading"))
+                            }
+                            "fail" => Err("failed"),
+                            text => Ok(p().text(text)),
+                        }))
+                    },
+                    |error| p().text(format!("error: {error}")),
+                )
+            },
+            || p().text("loading"),
+        )
+    });
+    assert_eq!(root.find_element("p").unwrap().text_content(), "first");
+
+    // The content is hidden while it is pending again, and views at its top
+    // level can still change.
+    ctx.with(|| state.set("reload"));
+    assert_eq!(root.find_element("p").unwrap().text_content(), "loading");
+    ctx.with(|| state.set("fail"));
+    assert_eq!(root.find_element("p").unwrap().text_content(), "loading");
+
+    ctx.with(|| drop(guard.borrow_mut().take()));
+    assert_eq!(
+        root.inner_html(),
+        r#"<div style="display: contents;"><p>error: failed</p></div>"#
+    );
+}
+
+#[test]
+fn test_memory_resource() {
+    use futures::channel::oneshot;
+
+    type Reply = Result<String, String>;
+    let requests: Rc<RefCell<Vec<(u32, oneshot::Sender<Reply>)>>> = Default::default();
+    let id = Mutable::new(1);
+    let resource = Rc::new(RefCell::new(None));
+
+    let (ctx, root) = {
+        let requests = requests.clone();
+        let resource = resource.clone();
+        let id = id.clone();
+        launch_memory(move || {
+            div().and(Suspense::new(
+                move || {
+                    // Created inside of the suspense, so it shows the
+                    // fallback while loading.
+                    let user = Resource::new(id.signal(), move |id| {
+                        let (tx, rx) = oneshot::channel();
+                        requests.borrow_mut().push((id, tx));
+                        async move { rx.await.unwrap() }
+                    });
+                    *resource.borrow_mut() = Some(user.clone());
+                    span()
+                        .text_signal(user.value().map(|user| user.unwrap_or_default()))
+                        .text_signal(user.error().map(|error| error.unwrap_or_default()))
+                },
+                || p().text("loading"),
+            ))
+        })
+    };
+    let user = resource.borrow().clone().unwrap();
+    let loading = Rc::new(RefCell::new(Vec::new()));
+    let _guard = ctx.with(|| {
+        let loading = loading.clone();
+        brass::effect::spawn_guarded(user.loading().for_each(move |x| {
+            loading.borrow_mut().push(x);
+            async {}
+        }))
+    });
+    let reply = |index: usize, reply: Reply| {
+        let (_, tx) = requests.borrow_mut().remove(index);
+        ctx.with(|| tx.send(reply).unwrap());
+    };
+
+    // The first fetch starts right away and suspends the content.
+    assert_eq!(requests.borrow()[0].0, 1);
+    assert_eq!(root.find_element("p").unwrap().text_content(), "loading");
+    reply(0, Ok("alice".to_string()));
+    assert_eq!(root.find_element("span").unwrap().text_content(), "alice");
+
+    // A new source value aborts the stale fetch.
+    ctx.with(|| id.set(2));
+    ctx.with(|| id.set(3));
+    assert!(requests.borrow()[0].1.is_canceled());
+    assert_eq!(requests.borrow()[1].0, 3);
+    reply(1, Err("not found".to_string()));
+    assert_eq!(
+        root.find_element("span").unwrap().text_content(),
+        "alicenot found"
+    );
+
+    // Optimistic updates, and refetching the same source value.
+    ctx.with(|| user.mutate(|user| *user = Some("bob".to_string())));
+    assert_eq!(
+        root.find_element("span").unwrap().text_content(),
+        "bobnot found"
+    );
+    ctx.with(|| user.refetch());
+    assert_eq!(requests.borrow()[1].0, 3);
+    assert_eq!(root.find_element("p").unwrap().text_content(), "loading");
+    reply(1, Ok("carol".to_string()));
+    assert_eq!(root.find_element("span").unwrap().text_content(), "carol");
+    assert_eq!(*loading.borrow(), [true, false, true, false, true, false]);
+}
+
+#[test]
+fn test_memory_transitions() {
+    let items = MutableVec::new_with_values(vec![1, 2]);
+    let show = Mutable::new(true);
+    let (ctx, root) = launch_memory(|| {
+        div()
+            .signal_vec_keyed_with_transition(
+                items.signal_vec(),
+                |item| *item,
+                |item| p().text(item.to_string()),
+                Transition::new("fade"),
+            )
+            .signal_with_transition(
+                show.signal()
+                    .map(|show| if show { span() } else { button() }),
+                Transition::new("swap"),
+            )
+    });
+    assert_eq!(
+        root.inner_html(),
+        "<div><p>1</p><p>2</p><!----><span></span></div>"
+    );
+
+    // In-memory documents are never rendered, so nothing is animated and
+    // removed views don't wait for an end event.
+    ctx.with(|| items.lock_mut().insert(0, 0));
+    assert_eq!(root.find_element("p").unwrap().attribute("class"), None);
+    ctx.with(|| items.lock_mut().remove(0));
+    ctx.with(|| show.set(false));
+    assert_eq!(
+        root.inner_html(),
+        "<div><p>1</p><p>2</p><!----><button></button></div>"
+    );
+
+    ctx.with(|| items.lock_mut().replace(vec![2, 3]));
+    assert_eq!(
+        root.inner_html(),
+        "<div><p>2</p><p>3</p><!----><button></button></div>"
+    );
+}
+
+struct Counter {
+    count: Mutable<u32>,
+    handle: Handle<Self>,
+}
+
+impl Component for Counter {
+    type Properties = u32;
+
+    fn init(start: u32, ctx: Context<'_, Self>) -> Self {
+        Self {
+            count: Mutable::new(start),
+            handle: ctx.handle(),
+        }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        let handle = self.handle.clone();
+        button()
+            .on_event(Ev::Click, move |_| {
+                handle.apply(|this, _| *this.count.lock_mut() += 1);
+            })
+            .text_signal(self.count.signal_ref(|x| x.to_string()))
+    }
+}
+
+#[test]
+fn test_memory_component() {
+    let (_ctx, root) = launch_memory(|| div().and(Counter::build(5)));
+    assert_eq!(root.inner_html(), "<div><button>5</button></div>");
+
+    let btn = root.find_element("button").unwrap();
+    btn.dispatch_event(Ev::Click);
+    btn.dispatch_event(Ev::Click);
+    assert_eq!(root.inner_html(), "<div><button>7</button></div>");
+}
+
+#[test]
+fn test_memory_svg_namespace() {
+    let (_ctx, root) = launch_memory(|| {
+        view! {
+            div [
+                svg(viewBox="0 0 10 10") [
+                    path(d="M0 0L10 10" strokeWidth="2")
+                    a(xlinkHref="#target") [ title [ "link" ] ]
+                    foreignObject [ a(href="/") ]
+                ]
+                a(href="/")
+                math [ mrow [ mi [ "x" ] ] ]
+            ]
+        }
+    });
+
+    let svg = root.find_element("svg").unwrap();
+    assert_eq!(svg.namespace(), Some(Namespace::Svg));
+    assert_eq!(
+        svg.find_element("path").unwrap().namespace(),
+        Some(Namespace::Svg)
+    );
+    let children = svg.children();
+    // Shared tags take the namespace of their parent.
+    assert_eq!(children[1].namespace(), Some(Namespace::Svg));
+    assert_eq!(
+        children[1].find_element("title").unwrap().namespace(),
+        Some(Namespace::Svg)
+    );
+    assert_eq!(
+        children[2].find_element("a").unwrap().namespace(),
+        Some(Namespace::Html)
+    );
+
+    let div = root.find_element("div").unwrap();
+    assert_eq!(div.children()[1].namespace(), Some(Namespace::Html));
+    assert_eq!(
+        root.find_element("mi").unwrap().namespace(),
+        Some(Namespace::MathMl)
+    );
+
+    assert_eq!(
+        svg.outer_html(),
+        r##"<svg viewBox="0 0 10 10"><path d="M0 0L10 10" stroke-width="2"></path><a xlink:href="#target"><title>link</title></a><foreignObject><a href="/"></a></foreignObject></svg>"##
+    );
+}
+
+#[test]
+fn test_memory_with_namespace() {
+    let (_ctx, root) = launch_memory(|| {
+        tag(Tag::Svg)
+            .tag(tag(Tag::Circle).attr(Attr::R, "4"))
+            .tag(with_namespace(Namespace::Svg, || tag(Tag::Style)))
+            .tag(tag(Tag::Style))
+    });
+
+    let children = root.find_element("svg").unwrap().children();
+    assert_eq!(children[0].namespace(), Some(Namespace::Svg));
+    assert_eq!(children[1].namespace(), Some(Namespace::Svg));
+    assert_eq!(children[2].namespace(), Some(Namespace::Html));
+}
+
+#[test]
+fn test_memory_typed_builders() {
+    let text = Mutable::new("a".to_string());
+    let agree = Mutable::new(false);
+    let choice = Mutable::new("2".to_string());
+
+    let (ctx, root) = launch_memory(|| {
+        form()
+            .tag(
+                input()
+                    .input_type("text")
+                    .value_signal(text.signal_cloned()),
+            )
+            .tag(
+                input()
+                    .input_type("checkbox")
+                    .checked_signal(agree.signal()),
+            )
+            .tag(textarea().value("notes"))
+            .tag(
+                select()
+                    .tag(option().value("1").text("One"))
+                    .tag(option().value("2").text("Two"))
+                    .value_signal(choice.signal_cloned()),
+            )
+            .tag(a().href("/home").text("home"))
+    });
+    assert_eq!(
+        root.inner_html(),
+        concat!(
+            r#"<form><input type="text" value="a"><input type="checkbox">"#,
+            r#"<textarea>notes</textarea>"#,
+            r#"<select><option value="1">One</option><option value="2" selected="">Two</option></select>"#,
+            r#"<a href="/home">home</a></form>"#,
+        )
+    );
+
+    ctx.with(|| {
+        text.set("b".to_string());
+        agree.set(true);
+        choice.set("1".to_string());
+    });
+    assert_eq!(
+        root.inner_html(),
+        concat!(
+            r#"<form><input type="text" value="b"><input type="checkbox" checked="">"#,
+            r#"<textarea>notes</textarea>"#,
+            r#"<select><option value="1" selected="">One</option><option value="2">Two</option></select>"#,
+            r#"<a href="/home">home</a></form>"#,
+        )
+    );
+}
+
+#[test]
+fn test_memory_properties() {
+    let value = Mutable::new(Some("a".to_string()));
+    let checked = Mutable::new(false);
+    let index = Mutable::new(1);
+
+    let (ctx, root) = launch_memory(|| {
+        view! {
+            div [
+                input(name="text" prop:value=value.signal_cloned())
+                input(name="agree" prop:checked=checked.signal() prop:indeterminate=true)
+                select(prop:selectedIndex=index.signal()) [
+                    option [ "One" ]
+                    option [ "Two" ]
+                ]
+            ]
+        }
+    });
+    assert_eq!(
+        root.inner_html(),
+        concat!(
+            r#"<div><input name="text" value="a"><input name="agree">"#,
+            r#"<select><option>One</option><option selected="">Two</option></select></div>"#,
+        )
+    );
+
+    ctx.with(|| {
+        value.set(None);
+        checked.set(true);
+        index.set(-1);
+    });
+    assert_eq!(
+        root.inner_html(),
+        concat!(
+            r#"<div><input name="text"><input name="agree" checked="">"#,
+            r#"<select><option>One</option><option>Two</option></select></div>"#,
+        )
+    );
+}
+
+#[test]
+fn test_memory_builder_properties() {
+    let (_ctx, root) = launch_memory(|| {
+        select()
+            .tag(option().value("1").text("One"))
+            .tag(option().value("2").text("Two"))
+            .prop(Prop::Value, "2")
+            .into_untyped()
+            .prop("customProp", 5)
+    });
+    assert_eq!(
+        root.inner_html(),
+        r#"<select><option value="1">One</option><option value="2" selected="">Two</option></select>"#
+    );
+}
+
+#[test]
+fn test_memory_bind_value() {
+    let text = Mutable::new("a".to_string());
+    let agree = Mutable::new(false);
+
+    let (ctx, root) = launch_memory(|| {
+        view! {
+            form [
+                input(name="text" bind:value=text)
+                input(name="agree" bind:checked=agree)
+                textArea(bind:value=text)
+            ]
+        }
+    });
+    let text_input = root.find_element("input").unwrap();
+    let textarea = root.find_element("textarea").unwrap();
+    assert_eq!(text_input.attribute("value").as_deref(), Some("a"));
+    assert_eq!(textarea.text_content(), "a");
+
+    // User input updates the state, and through it the other bound element.
+    text_input.set_property("value", &"typed".into());
+    text_input.dispatch_event(Ev::Input);
+    assert_eq!(text.get_cloned(), "typed");
+    assert_eq!(textarea.text_content(), "typed");
+
+    let checkbox = &root.find_element("form").unwrap().children()[1];
+    checkbox.set_property("checked", &true.into());
+    checkbox.dispatch_event(Ev::Change);
+    assert!(agree.get());
+
+    ctx.with(|| agree.set(false));
+    assert_eq!(checkbox.attribute("checked"), None);
+}
+
+#[test]
+fn test_memory_bind_radio_and_options() {
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Size {
+        Small,
+        Large,
+    }
+
+    let size = Mutable::new(Size::Small);
+    let (ctx, root) = launch_memory(|| {
+        div()
+            .tag(input().input_type("radio").bind_radio(&size, Size::Small))
+            .tag(input().input_type("radio").bind_radio(&size, Size::Large))
+            .tag(select().bind_options(&size, [(Size::Small, "Small"), (Size::Large, "Large")]))
+    });
+    assert_eq!(
+        root.inner_html(),
+        concat!(
+            r#"<div><input type="radio" checked=""><input type="radio">"#,
+            r#"<select><option value="0" selected="">Small</option><option value="1">Large</option></select></div>"#,
+        )
+    );
+
+    let children = root.find_element("div").unwrap().children();
+    children[1].set_property("checked", &true.into());
+    children[1].dispatch_event(Ev::Change);
+    assert_eq!(size.get(), Size::Large);
+    assert_eq!(children[0].attribute("checked"), None);
+    assert_eq!(children[2].property("selectedIndex"), 1.into());
+
+    children[2].set_property("selectedIndex", &0.into());
+    children[2].dispatch_event(Ev::Change);
+    assert_eq!(size.get(), Size::Small);
+
+    ctx.with(|| size.set(Size::Large));
+    assert_eq!(children[0].attribute("checked"), None);
+    assert_eq!(children[1].attribute("checked").as_deref(), Some(""));
+}
+
+#[test]
+fn test_memory_bind_number() {
+    let amount = Mutable::new(1.5f64);
+    let error = Mutable::new(None);
+
+    let (ctx, root) = launch_memory(|| input().bind_number(&amount, &error));
+    let elem = root.find_element("input").unwrap();
+    assert_eq!(elem.attribute("value").as_deref(), Some("1.5"));
+
+    elem.set_property("value", &"abc".into());
+    elem.dispatch_event(Ev::Input);
+    assert_eq!(amount.get(), 1.5);
+    assert!(error.lock_ref().is_some());
+
+    // Input that parses to the same value is not reformatted.
+    elem.set_property("value", &"2.50".into());
+    elem.dispatch_event(Ev::Input);
+    assert_eq!(amount.get(), 2.5);
+    assert!(error.lock_ref().is_none());
+    assert_eq!(elem.attribute("value").as_deref(), Some("2.50"));
+
+    ctx.with(|| amount.set(3.0));
+    assert_eq!(elem.attribute("value").as_deref(), Some("3"));
+}
+
+#[test]
+fn test_memory_node_ref() {
+    let show = Mutable::new(1);
+    let node_ref = NodeRef::<web_sys::HtmlButtonElement>::new();
+    let tags = node_ref.dom_node_signal().map(|node| {
+        node.and_then(|node| {
+            node.as_memory()
+                .and_then(|node| node.tag().map(String::from))
+        })
+    });
+    let seen = Mutable::new(Vec::new());
+
+    let (ctx, _root) = launch_memory(|| {
+        let node_ref = node_ref.clone();
+        let seen = seen.clone();
+        div()
+            .signal(show.signal().map(move |count| {
+                if count > 0 {
+                    button()
+                        .cast::<web_sys::HtmlButtonElement>()
+                        .node_ref(&node_ref)
+                        .text(count.to_string())
+                        .into()
+                } else {
+                    View::Empty
+                }
+            }))
+            .spawn(tags.for_each(move |tag| {
+                seen.lock_mut().push(tag);
+                async {}
+            }))
+    });
+    assert!(node_ref.is_set());
+    // Browser elements are not available in in-memory documents.
+    assert!(node_ref.get().is_none());
+    let first = node_ref.dom_node().unwrap();
+
+    // A replacement keeps the reference filled.
+    ctx.with(|| show.set(2));
+    let second = node_ref.dom_node().unwrap();
+    assert!(!first.is_same(&second));
+    assert_eq!(second.as_memory().unwrap().text_content(), "2");
+
+    ctx.with(|| show.set(0));
+    assert!(!node_ref.is_set());
+    assert_eq!(seen.get_cloned().last(), Some(&None));
+}
+
+#[test]
+fn test_memory_mount_hooks() {
+    let show = Mutable::new(false);
+    let items = MutableVec::new_with_values(vec![1]);
+    let log = Mutable::new(Vec::<String>::new());
+
+    let logger = {
+        let log = log.clone();
+        move |name: &'static str| {
+            let log = log.clone();
+            move || log.lock_mut().push(name.to_string())
+        }
+    };
+    let (ctx, root) = launch_memory(|| {
+        let list_log = log.clone();
+        let logger = logger.clone();
+        div()
+            .on_mount(logger("root"))
+            .signal(show.signal().map(move |show| {
+                if show {
+                    span()
+                        .on_mount(logger("span"))
+                        .on_unmount(logger("span removed"))
+                        .into()
+                } else {
+                    View::Empty
+                }
+            }))
+            .signal_vec(items.signal_vec(), move |x| {
+                let log = list_log.clone();
+                let x = *x;
+                p().on_mount(move || {
+                    // Mounted elements are in the document.
+                    log.lock_mut().push(format!("item {x}"));
+                })
+            })
+    });
+    assert_eq!(log.get_cloned(), ["root", "item 1"]);
+
+    ctx.with(|| {
+        show.set(true);
+        items.lock_mut().push(2);
+    });
+    assert_eq!(log.get_cloned(), ["root", "item 1", "span", "item 2"]);
+
+    ctx.with(|| show.set(false));
+    assert_eq!(log.lock_ref().last().unwrap(), "span removed");
+
+    // Elements removed before they are rendered are never mounted.
+    let detached = ctx.with(|| span().on_mount(logger("detached")));
+    ctx.with(|| std::mem::drop(detached));
+    assert!(!log.lock_ref().iter().any(|x| x == "detached"));
+    assert_eq!(root.find_element("span"), None);
+}
+
+struct Lifecycle {
+    log: Mutable<Vec<&'static str>>,
+}
+
+impl Component for Lifecycle {
+    type Properties = Mutable<Vec<&'static str>>;
+
+    fn init(log: Self::Properties, _ctx: Context<'_, Self>) -> Self {
+        log.lock_mut().push("init");
+        Self { log }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        self.log.lock_mut().push("render");
+        div()
+    }
+
+    fn mounted(&mut self, _ctx: Context<'_, Self>) {
+        self.log.lock_mut().push("mounted");
+    }
+
+    fn destroyed(&mut self) {
+        self.log.lock_mut().push("destroyed");
+    }
+}
+
+#[test]
+fn test_memory_component_lifecycle() {
+    let show = Mutable::new(true);
+    let log = Mutable::new(Vec::new());
+
+    let (ctx, _root) = launch_memory(|| {
+        let log = log.clone();
+        div().signal(show.signal().map(move |show| {
+            if show {
+                Lifecycle::build(log.clone())
+            } else {
+                View::Empty
+            }
+        }))
+    });
+    assert_eq!(log.get_cloned(), ["init", "render", "mounted"]);
+
+    ctx.with(|| show.set(false));
+    assert_eq!(log.get_cloned(), ["init", "render", "mounted", "destroyed"]);
+}
+
+enum TodoMsg {
+    Add,
+    Noop,
+}
+
+type TodoHandle = Rc<RefCell<Option<Handle<TodoList>>>>;
+
+/// Renders its items as plain values and renders again on changes.
+struct TodoList {
+    items: Vec<u32>,
+    log: Mutable<Vec<&'static str>>,
+}
+
+impl MsgComponent for TodoList {
+    type Properties = (Mutable<Vec<&'static str>>, TodoHandle);
+    type Msg = TodoMsg;
+
+    const RERENDER: bool = true;
+
+    fn init((log, handle): Self::Properties, ctx: Context<Self>) -> Self {
+        *handle.borrow_mut() = Some(ctx.handle());
+        Self {
+            items: vec![1],
+            log,
+        }
+    }
+
+    fn update(&mut self, msg: TodoMsg, _ctx: Context<Self>) -> ShouldRender {
+        match msg {
+            TodoMsg::Add => {
+                self.items.push(self.items.len() as u32 + 1);
+                true
+            }
+            TodoMsg::Noop => false,
+        }
+    }
+
+    fn render(&mut self, ctx: Context<Self>) -> TagBuilder {
+        self.log.lock_mut().push("render");
+        div()
+            .and(
+                button()
+                    .on_event(Ev::Click, ctx.on(|_| TodoMsg::Add))
+                    .text(self.items.len().to_string()),
+            )
+            .and_iter(
+                self.items
+                    .iter()
+                    .map(|item| TodoItem::build((*item, self.log.clone()))),
+            )
+    }
+}
+
+/// A child that keeps its state when the parent renders again.
+struct TodoItem {
+    item: u32,
+    log: Mutable<Vec<&'static str>>,
+}
+
+impl Component for TodoItem {
+    type Properties = (u32, Mutable<Vec<&'static str>>);
+
+    fn init((item, log): Self::Properties, _ctx: Context<'_, Self>) -> Self {
+        log.lock_mut().push("init item");
+        Self { item, log }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        p().text(self.item.to_string())
+    }
+
+    fn changed(&mut self, (item, _): Self::Properties, _ctx: Context<'_, Self>) {
+        self.item = item;
+    }
+
+    fn destroyed(&mut self) {
+        self.log.lock_mut().push("destroyed item");
+    }
+}
+
+#[test]
+fn test_memory_component_render() {
+    let log = Mutable::new(Vec::new());
+    let show = Mutable::new(true);
+    let handle = TodoHandle::default();
+    let (ctx, root) = launch_memory(|| {
+        let (log, handle) = (log.clone(), handle.clone());
+        div().signal(show.signal().map(move |show| {
+            if show {
+                TodoList::build((log.clone(), handle.clone()))
+            } else {
+                View::Empty
+            }
+        }))
+    });
+    assert_eq!(
+        root.inner_html(),
+        "<div><div><button>1</button><p>1</p></div></div>"
+    );
+    assert_eq!(log.get_cloned(), ["render", "init item"]);
+
+    // Renders again in place, keeping the child components.
+    root.find_element("button")
+        .unwrap()
+        .dispatch_event(Ev::Click);
+    root.find_element("button")
+        .unwrap()
+        .dispatch_event(Ev::Click);
+    assert_eq!(
+        root.inner_html(),
+        "<div><div><button>3</button><p>1</p><p>2</p><p>3</p></div></div>"
+    );
+    assert_eq!(
+        log.get_cloned(),
+        [
+            "render",
+            "init item",
+            "render",
+            "init item",
+            "render",
+            "init item"
+        ]
+    );
+
+    // Updates that don't need a render keep the view.
+    let button = root.find_element("button").unwrap();
+    let handle = handle.borrow().clone().unwrap();
+    handle.send(TodoMsg::Noop);
+    assert_eq!(log.get_cloned().len(), 6);
+    assert_eq!(root.find_element("button").unwrap(), button);
+
+    // Renders that were replaced don't destroy the component.
+    ctx.with(|| show.set(false));
+    assert_eq!(
+        log.get_cloned()[6..],
+        ["destroyed item", "destroyed item", "destroyed item"]
+    );
+}
+
+/// Renders a [`Lifecycle`] child, which doesn't override `changed`.
+struct LifecycleParent {
+    log: Mutable<Vec<&'static str>>,
+}
+
+impl Component for LifecycleParent {
+    type Properties = (
+        Mutable<Vec<&'static str>>,
+        Rc<RefCell<Option<Handle<Self>>>>,
+    );
+
+    const RERENDER: bool = true;
+
+    fn init((log, handle): Self::Properties, ctx: Context<'_, Self>) -> Self {
+        *handle.borrow_mut() = Some(ctx.handle());
+        Self { log }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        div().and(Lifecycle::build(self.log.clone()))
+    }
+}
+
+#[test]
+fn test_memory_component_changed_default() {
+    let log = Mutable::new(Vec::new());
+    let handle = Rc::new(RefCell::new(None));
+    let (_ctx, root) = launch_memory(|| LifecycleParent::build((log.clone(), handle.clone())));
+    assert_eq!(log.get_cloned(), ["init", "render", "mounted"]);
+
+    // Without `changed`, the child is destroyed and built again.
+    let handle = handle.borrow().clone().unwrap();
+    handle.update(|_, _| true);
+    assert_eq!(
+        log.get_cloned(),
+        [
+            "init",
+            "render",
+            "mounted",
+            "destroyed",
+            "init",
+            "render",
+            "mounted"
+        ]
+    );
+    assert_eq!(root.inner_html(), "<div><div></div></div>");
+}
+
+/// Like [`LifecycleParent`], but can't render again.
+struct StaticParent {
+    log: Mutable<Vec<&'static str>>,
+}
+
+impl Component for StaticParent {
+    type Properties = (
+        Mutable<Vec<&'static str>>,
+        Rc<RefCell<Option<Handle<Self>>>>,
+    );
+
+    fn init((log, handle): Self::Properties, ctx: Context<'_, Self>) -> Self {
+        *handle.borrow_mut() = Some(ctx.handle());
+        Self { log }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        self.log.lock_mut().push("render parent");
+        div()
+    }
+}
+
+#[test]
+fn test_memory_component_without_rerender() {
+    let log = Mutable::new(Vec::new());
+    let handle = Rc::new(RefCell::new(None));
+    let (_ctx, root) = launch_memory(|| StaticParent::build((log.clone(), handle.clone())));
+
+    let handle = handle.borrow().clone().unwrap();
+    handle.update(|_, _| true);
+    assert_eq!(log.get_cloned(), ["render parent"]);
+    assert_eq!(root.inner_html(), "<div></div>");
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct User(&'static str);
+
+fn user_name() -> String {
+    use_context::<User>()
+        .map_or("anonymous", |user| user.0)
+        .to_string()
+}
+
+/// Provides a user to its children.
+struct UserProvider {
+    items: MutableVec<u32>,
+}
+
+impl Component for UserProvider {
+    type Properties = MutableVec<u32>;
+
+    fn init(items: Self::Properties, _ctx: Context<'_, Self>) -> Self {
+        provide_context(User("ann"));
+        Self { items }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        div()
+            .text(user_name())
+            .signal_vec(self.items.signal_vec(), |item| {
+                p().text(format!("{item} {}", user_name()))
+            })
+    }
+}
+
+#[test]
+fn test_memory_context() {
+    let items = MutableVec::new();
+    let show = Mutable::new(false);
+    let (ctx, root) = launch_memory(|| {
+        let items = items.clone();
+        div()
+            .and(UserProvider::build(items))
+            .text(user_name())
+            .signal(show.signal().map(|show| {
+                if show {
+                    provide_context(User("bob"));
+                    span().text(user_name())
+                } else {
+                    span()
+                }
+            }))
+    });
+    // Values are only visible in the subtree of the component.
+    assert_eq!(
+        root.inner_html(),
+        "<div><div>ann<!----></div>anonymous<span></span></div>"
+    );
+
+    ctx.with(|| items.lock_mut().push(1));
+    ctx.with(|| show.set(true));
+    assert_eq!(
+        root.inner_html(),
+        "<div><div>ann<p>1 ann</p><!----></div>anonymous<span>bob</span></div>"
+    );
+}
+
+#[derive(Route, Clone, PartialEq, Debug)]
+enum TestRoute {
+    #[route("/")]
+    Home,
+    #[route("/users/:id")]
+    User { id: u32 },
+    #[route("/users/:id/posts/:post")]
+    Post(u32, String),
+}
+
+#[test]
+fn test_memory_router() {
+    assert_eq!(TestRoute::from_path("/"), Some(TestRoute::Home));
+    assert_eq!(TestRoute::from_path("/users/x"), None);
+    assert_eq!(
+        TestRoute::from_path("/users/1/posts/a%20b"),
+        Some(TestRoute::Post(1, "a b".into()))
+    );
+    assert_eq!(
+        TestRoute::Post(2, "c/d".into()).to_path(),
+        "/users/2/posts/c%2Fd"
+    );
+
+    let (ctx, root) = launch_memory(|| {
+        router::init(RouterMode::Memory("/users/1?tab=posts".into()));
+        div().and(router::link(&TestRoute::Home).text("Home")).and(
+            Switch::new(|route: TestRoute| match route {
+                TestRoute::Home => p().text("Home"),
+                TestRoute::User { id } => p().text(format!("User {id}")),
+                TestRoute::Post(id, post) => p().text(format!("Post {post} of {id}")),
+            })
+            .not_found(|| p().text("Not found")),
+        )
+    });
+    let page = || root.find_element("p").unwrap().text_content();
+    assert_eq!(page(), "User 1");
+
+    // Links navigate on click.
+    root.find_element("a").unwrap().dispatch_event(Ev::Click);
+    assert_eq!(page(), "Home");
+    assert_eq!(ctx.with(router::current_path), "/");
+
+    ctx.with(|| router::navigate(&TestRoute::Post(3, "x".into())));
+    assert_eq!(page(), "Post x of 3");
+    ctx.with(|| router::replace_path("/missing"));
+    assert_eq!(page(), "Not found");
+
+    // Replacing doesn't add a history entry.
+    ctx.with(router::back);
+    assert_eq!(page(), "Home");
+    ctx.with(router::back);
+    assert_eq!(page(), "User 1");
+    assert_eq!(
+        ctx.with(router::current_route::<TestRoute>),
+        Some(TestRoute::User { id: 1 })
+    );
+}
+
+#[derive(Route, Clone, PartialEq, Debug)]
+enum AppRoute {
+    #[route("/")]
+    Home,
+    #[route("/login")]
+    Login,
+    #[route("/org/:org")]
+    Org {
+        org: String,
+        #[nested]
+        page: OrgRoute,
+    },
+}
+
+#[derive(Route, Clone, PartialEq, Debug)]
+enum OrgRoute {
+    #[route("/")]
+    Overview,
+    #[route("/settings")]
+    Settings,
+    #[route("/members/:id")]
+    Member(u32),
+}
+
+#[test]
+fn test_memory_router_nested() {
+    let route = AppRoute::Org {
+        org: "a".into(),
+        page: OrgRoute::Member(2),
+    };
+    assert_eq!(route.to_path(), "/org/a/members/2");
+    assert_eq!(AppRoute::from_path("/org/a/members/2"), Some(route));
+    assert_eq!(
+        AppRoute::from_path("/org/a"),
+        Some(AppRoute::Org {
+            org: "a".into(),
+            page: OrgRoute::Overview
+        })
+    );
+    assert_eq!(AppRoute::from_path("/org/a/missing"), None);
+
+    let logged_in = Rc::new(std::cell::Cell::new(false));
+    let layouts = Rc::new(std::cell::Cell::new(0));
+    let (ctx, root) = launch_memory({
+        let logged_in = logged_in.clone();
+        let layouts = layouts.clone();
+        move || {
+            router::init(RouterMode::Memory("/org/a/settings".into()));
+            Switch::new(move |route: AppRoute| match route {
+                AppRoute::Home => p().text("Home"),
+                AppRoute::Login => p().text("Login"),
+                AppRoute::Org { org, .. } => {
+                    layouts.set(layouts.get() + 1);
+                    div().text(org).and(Switch::new(|page| match page {
+                        OrgRoute::Overview => p().text("Overview"),
+                        OrgRoute::Settings => p().text("Settings"),
+                        OrgRoute::Member(id) => p().text(format!("Member {id}")),
+                    }))
+                }
+            })
+            .guard(move |route| match route {
+                AppRoute::Org {
+                    page: OrgRoute::Settings,
+                    ..
+                } if !logged_in.get() => Err(Redirect::to(&AppRoute::Login)),
+                _ => Ok(()),
+            })
+        }
+    });
+    // Guards redirect before anything is rendered.
+    assert_eq!(root.inner_html(), "<p>Login</p>");
+    assert_eq!(ctx.with(router::current_path), "/login");
+    assert_eq!(layouts.get(), 0);
+
+    logged_in.set(true);
+    ctx.with(|| router::navigate_path("/org/a/settings"));
+    assert_eq!(root.inner_html(), "<div>a<p>Settings</p></div>");
+
+    // The layout is kept while only the nested route changes.
+    let layout = root.find_element("div").unwrap();
+    ctx.with(|| router::navigate_path("/org/a/members/3"));
+    assert_eq!(root.inner_html(), "<div>a<p>Member 3</p></div>");
+    assert_eq!(root.find_element("div").unwrap(), layout);
+    assert_eq!(layouts.get(), 1);
+
+    ctx.with(|| router::navigate_path("/org/b"));
+    assert_eq!(root.inner_html(), "<div>b<p>Overview</p></div>");
+    assert_eq!(layouts.get(), 2);
+}
+
+#[test]
+fn test_memory_router_loader() {
+    let loads = Rc::new(RefCell::new(Vec::new()));
+    let (ctx, root) = launch_memory({
+        let loads = loads.clone();
+        move || {
+            router::init(RouterMode::Memory("/users/1".into()));
+            Suspense::new(
+                move || {
+                    Switch::load(move |route: TestRoute| {
+                        let (tx, rx) = futures::channel::oneshot::channel::<String>();
+                        loads.borrow_mut().push(tx);
+                        async move {
+                            let data = rx.await.unwrap_or_default();
+                            match route {
+                                TestRoute::Home => p().text("Home"),
+                                TestRoute::User { id } => p().text(format!("User {id}: {data}")),
+                                TestRoute::Post(_, post) => p().text(format!("{post}: {data}")),
+                            }
+                        }
+                    })
+                },
+                || span().text("Loading"),
+            )
+        }
+    });
+    assert_eq!(root.find_element("span").unwrap().text_content(), "Loading");
+
+    let tx = loads.borrow_mut().remove(0);
+    ctx.with(|| tx.send("Ann".into()).unwrap());
+    assert_eq!(
+        root.find_element("p").unwrap().text_content(),
+        "User 1: Ann"
+    );
+
+    // The previous route stays while the next one loads, and loads of
+    // routes that were left are aborted.
+    ctx.with(|| router::navigate(&TestRoute::User { id: 2 }));
+    ctx.with(|| router::navigate(&TestRoute::Post(3, "Hello".into())));
+    assert_eq!(
+        root.find_element("p").unwrap().text_content(),
+        "User 1: Ann"
+    );
+    assert_eq!(root.find_element("span"), None);
+    let (aborted, tx) = {
+        let mut loads = loads.borrow_mut();
+        (loads.remove(0), loads.remove(0))
+    };
+    assert!(aborted.is_canceled());
+    ctx.with(|| tx.send("World".into()).unwrap());
+    assert_eq!(
+        root.find_element("p").unwrap().text_content(),
+        "Hello: World"
+    );
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Default, Debug)]
+struct ListQuery {
+    q: Option<String>,
+    page: Option<u32>,
+}
+
+#[test]
+fn test_memory_router_query() {
+    let (ctx, root) = launch_memory(|| {
+        router::init(RouterMode::Memory("/list?q=caf%C3%A9+au+lait".into()));
+        let query = Query::<ListQuery>::new();
+        div().signal(query.signal().map(|query| {
+            p().text(format!(
+                "{} {}",
+                query.q.unwrap_or_default(),
+                query.page.unwrap_or(1)
+            ))
+        }))
+    });
+    let text = || root.find_element("p").unwrap().text_content();
+    assert_eq!(text(), "café au lait 1");
+
+    // Replacing the query doesn't add history entries.
+    let query = Query::<ListQuery>::new();
+    ctx.with(|| query.update(|query| query.q = Some("a&b=c".into())));
+    assert_eq!(ctx.with(router::current_path), "/list?q=a%26b%3Dc");
+    assert_eq!(text(), "a&b=c 1");
+
+    let query = query.push(true);
+    ctx.with(|| query.update(|query| query.page = Some(2)));
+    assert_eq!(ctx.with(router::current_path), "/list?q=a%26b%3Dc&page=2");
+    assert_eq!(text(), "a&b=c 2");
+
+    ctx.with(router::back);
+    assert_eq!(text(), "a&b=c 1");
+
+    // Invalid values read as the default.
+    ctx.with(|| router::replace_path("/list?page=x"));
+    assert_eq!(text(), " 1");
+    ctx.with(|| query.set(&ListQuery::default()));
+    assert_eq!(ctx.with(router::current_path), "/list");
+}
@@ -0,0 +1,125 @@
+use futures_signals::{
+    signal::{Mutable, SignalExt},
+    signal_vec::MutableVec,
+};
+
+use brass::{
+    dom::{
+        builder::{button, div, input, p, span, tag},
+        Attr, Fragment, Style, Tag, View,
+    },
+    ssr::render_to_string,
+    view,
+};
+
+#[test]
+fn test_ssr_escaping() {
+    let html = render_to_string(|| {
+        div()
+            .attr(Attr::Title, r#"a "quoted" & <b>"#)
+            .text("1 < 2 & 3 > 2")
+    });
+    assert_eq!(
+        html,
+        r#"<div title="a &quot;quoted&quot; &amp; <b>">1 &lt; 2 &amp; 3 &gt; 2</div>"#
+    );
+}
+
+#[test]
+fn test_ssr_attributes() {
+    let html = render_to_string(|| {
+        div()
+            .attr(Attr::Id, "x")
+            .class("a")
+            .classes(["b", "a"])
+            .style(Style::Display, "block")
+            .style(Style::Color, "red")
+            .tag(input().attr_toggle(Attr::Disabled))
+            .tag(tag(Tag::Br))
+            .tag(span().attr_toggle_if(false, Attr::Hidden))
+    });
+    assert_eq!(
+        html,
+        r#"<div id="x" class="a b" style="display: block; color: red;"><input disabled=""><br><span></span></div>"#
+    );
+}
+
+#[test]
+fn test_ssr_signals() {
+    let text = Mutable::new("hello".to_string());
+    let show = Mutable::new(true);
+    let items = MutableVec::new_with_values(vec![1, 2, 3]);
+
+    let html = render_to_string(|| {
+        div()
+            .attr_signal(Attr::Title, text.signal_cloned())
+            .class_signal_toggle("shown", show.signal())
+            .text_signal(text.signal_cloned())
+            .signal(show.signal().map(|show| {
+                if show {
+                    p().text("visible").into()
+                } else {
+                    View::Empty
+                }
+            }))
+            .signal_vec(items.signal_vec(), |x| span().text(x.to_string()))
+    });
+    assert_eq!(
+        html,
+        r#"<div title="hello" class="shown">hello<p>visible</p><span>1</span><span>2</span><span>3</span><!----></div>"#
+    );
+}
+
+#[test]
+fn test_ssr_empty_signal() {
+    let html = render_to_string(|| div().signal(Mutable::new(()).signal().map(|_| View::Empty)));
+    assert_eq!(html, "<div><!----></div>");
+}
+
+#[test]
+fn test_ssr_fragment() {
+    let html = render_to_string(|| Fragment {
+        items: vec![p().into(), View::Empty, span().text("a").into()],
+    });
+    assert_eq!(html, "<p></p><span>a</span>");
+}
+
+#[test]
+fn test_ssr_event_handlers_ignored() {
+    let html = render_to_string(|| button().on_event(brass::dom::Ev::Click, |_| {}).text("ok"));
+    assert_eq!(html, "<button>ok</button>");
+}
+
+#[test]
+fn test_ssr_view_macro() {
+    let html = render_to_string(|| {
+        let name = "world";
+        view! {
+            div(id="root" class="greeting") [
+                p [ "hello " {name} ]
+                input(name="agree" checked)
+            ]
+        }
+    });
+    assert_eq!(
+        html,
+        r#"<div id="root" class="greeting"><p>hello world</p><input name="agree" checked=""></div>"#
+    );
+}
+
+#[test]
+fn test_ssr_raw_text() {
+    let html = render_to_string(|| tag(Tag::Script).text("if (a < b && c) {}"));
+    assert_eq!(html, "<script>if (a < b && c) {}</script>");
+
+    // Text can't close the element early.
+    let html = render_to_string(|| {
+        tag(Tag::Script).text(r#"const data = "</script><img src=x onerror=alert(1)>";"#)
+    });
+    assert_eq!(
+        html,
+        r#"<script>const data = "<\/script><img src=x onerror=alert(1)>";</script>"#
+    );
+    let html = render_to_string(|| tag(Tag::Style).text("a::after { content: '</style>' }"));
+    assert_eq!(html, r#"<style>a::after { content: '<\/style>' }</style>"#);
+}
@@ -1,17 +1,23 @@
 wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 use std::time::Duration;
 
-use futures_signals::{signal::Mutable, signal_vec::MutableVec};
+use futures_signals::{
+    signal::{Mutable, SignalExt},
+    signal_vec::MutableVec,
+};
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use wasm_bindgen_test::wasm_bindgen_test;
 
 use brass::{
+    component::{Component, Context, Handle},
     dom::{
-        builder::{button, div, span},
-        Attr, ClickEvent,
+        builder::{button, div, form, input, p, span},
+        Attr, ClickEvent, Ev, EventOptions, Fragment, Key, KeyUpEvent, NodeRef, Portal, Prop,
+        SubmitEvent, TagBuilder, Transition, TransitionStartEvent, View,
     },
     effect::{set_timeout, TimeoutFuture},
+    router::{self, RouterMode},
     view,
 };
 
@@ -34,6 +40,14 @@ fn elem_by_id(id: &str) -> web_sys::Element {
         .unwrap()
 }
 
+fn nth_child(elem: &web_sys::Element, index: usize) -> web_sys::Node {
+    let mut child = elem.first_child().unwrap();
+    for _ in 0..index {
+        child = child.next_sibling().unwrap();
+    }
+    child
+}
+
 async fn tick() {
     let promise = js_sys::Promise::resolve(&JsValue::NULL);
     JsFuture::from(promise).await.unwrap();
@@ -158,6 +172,365 @@ async fn test_signal_vec_view() {
     assert_eq!(elem.inner_html(), "<!---->");
 }
 
+#[wasm_bindgen_test]
+async fn test_signal_vec_view_move() {
+    let mvec = MutableVec::<&'static str>::new_with_values(vec!["a", "b", "c"]);
+
+    let sig = mvec.signal_vec_cloned();
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_vec_view_move")
+            .signal_vec(sig, |x| span().and(*x))
+    });
+
+    let elem = elem_by_id("test_signal_vec_view_move");
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><span>b</span><span>c</span><!---->"
+    );
+    let a = nth_child(&elem, 0);
+
+    ctx.with(|| {
+        mvec.lock_mut().move_from_to(0, 2);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>b</span><span>c</span><span>a</span><!---->"
+    );
+    // The existing node must be moved, not re-rendered.
+    assert_eq!(nth_child(&elem, 2), a);
+
+    ctx.with(|| {
+        mvec.lock_mut().move_from_to(2, 1);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>b</span><span>a</span><span>c</span><!---->"
+    );
+    assert_eq!(nth_child(&elem, 1), a);
+
+    ctx.with(|| {
+        mvec.lock_mut().move_from_to(1, 0);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><span>b</span><span>c</span><!---->"
+    );
+
+    // Reorder every element at once.
+    ctx.with(|| {
+        mvec.lock_mut().replace_cloned(vec!["c", "a", "b"]);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>c</span><span>a</span><span>b</span><!---->"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_signal_vec_keyed() {
+    let mvec =
+        MutableVec::<(u32, &'static str)>::new_with_values(vec![(1, "a"), (2, "b"), (3, "c")]);
+
+    let sig = mvec.signal_vec_cloned();
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_vec_keyed")
+            .signal_vec_keyed(sig, |(id, _)| *id, |(_, x)| span().and(*x))
+    });
+
+    let elem = elem_by_id("test_signal_vec_keyed");
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><span>b</span><span>c</span><!---->"
+    );
+    let a = nth_child(&elem, 0);
+    let c = nth_child(&elem, 2);
+
+    // Reorder, drop key 2, add key 4 and change the value of key 1.
+    // Retained children must not be re-rendered.
+    ctx.with(|| {
+        mvec.lock_mut()
+            .replace_cloned(vec![(3, "c"), (4, "d"), (1, "A")]);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>c</span><span>d</span><span>a</span><!---->"
+    );
+    assert_eq!(nth_child(&elem, 0), c);
+    assert_eq!(nth_child(&elem, 2), a);
+
+    ctx.with(|| {
+        mvec.lock_mut().insert_cloned(0, (5, "e"));
+        mvec.lock_mut().move_from_to(0, 3);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>c</span><span>d</span><span>a</span><span>e</span><!---->"
+    );
+
+    ctx.with(|| {
+        mvec.lock_mut().replace_cloned(vec![(5, "e"), (3, "c")]);
+    });
+    assert_eq!(elem.inner_html(), "<span>e</span><span>c</span><!---->");
+    assert_eq!(nth_child(&elem, 1), c);
+
+    ctx.with(|| {
+        mvec.lock_mut().clear();
+    });
+    assert_eq!(elem.inner_html(), "<!---->");
+}
+
+#[wasm_bindgen_test]
+async fn test_signal_vec_view_fallback() {
+    let mvec = MutableVec::<&'static str>::new();
+
+    let sig = mvec.signal_vec_cloned();
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_vec_view_fallback")
+            .signal_vec_with_fallback(sig, |x| span().and(*x), p().and("empty"))
+    });
+
+    let elem = elem_by_id("test_signal_vec_view_fallback");
+    assert_eq!(elem.inner_html(), "<p>empty</p><!---->");
+
+    ctx.with(|| {
+        mvec.lock_mut().push("a");
+    });
+    assert_eq!(elem.inner_html(), "<span>a</span><!---->");
+
+    ctx.with(|| {
+        mvec.lock_mut().pop();
+    });
+    assert_eq!(elem.inner_html(), "<p>empty</p><!---->");
+}
+
+#[wasm_bindgen_test]
+async fn test_classes_signal() {
+    let classes = MutableVec::<&'static str>::new_with_values(vec!["a", "b"]);
+
+    let sig = classes.signal_vec_cloned();
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_classes_signal")
+            .class("static")
+            .classes_signal(sig)
+    });
+
+    let elem = elem_by_id("test_classes_signal");
+    assert_eq!(elem.class_name(), "static a b");
+
+    ctx.with(|| {
+        classes.lock_mut().insert(1, "c");
+    });
+    assert_eq!(elem.class_name(), "static a b c");
+
+    ctx.with(|| {
+        classes.lock_mut().set(0, "d");
+    });
+    assert_eq!(elem.class_name(), "static b c d");
+
+    ctx.with(|| {
+        classes.lock_mut().move_from_to(0, 2);
+        classes.lock_mut().remove(0);
+    });
+    assert_eq!(elem.class_name(), "static b d");
+
+    ctx.with(|| {
+        classes.lock_mut().push("b");
+        classes.lock_mut().pop();
+    });
+    assert_eq!(elem.class_name(), "static b d");
+
+    ctx.with(|| {
+        classes.lock_mut().clear();
+    });
+    assert_eq!(elem.class_name(), "static");
+}
+
+#[wasm_bindgen_test]
+async fn test_signal_fragment() {
+    let multi = Mutable::new(true);
+
+    let sig = multi.signal().map(|multi| -> View {
+        if multi {
+            Fragment {
+                items: vec![span().and("a").into(), span().and("b").into()],
+            }
+            .into()
+        } else {
+            div().and("c").into()
+        }
+    });
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_fragment")
+            .signal(sig)
+            .and(span().and("end"))
+    });
+
+    let elem = elem_by_id("test_signal_fragment");
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><span>b</span><span>end</span>"
+    );
+
+    ctx.with(|| {
+        multi.set(false);
+    });
+    assert_eq!(elem.inner_html(), "<div>c</div><span>end</span>");
+
+    ctx.with(|| {
+        multi.set(true);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><span>b</span><span>end</span>"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_signal_fragment_empty() {
+    let count = Mutable::new(0usize);
+
+    let sig = count.signal().map(|count| Fragment {
+        items: (0..count)
+            .map(|i| span().and(i.to_string()).into())
+            .collect(),
+    });
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_fragment_empty")
+            .signal(sig)
+    });
+
+    let elem = elem_by_id("test_signal_fragment_empty");
+    assert_eq!(elem.inner_html(), "<!---->");
+
+    ctx.with(|| {
+        count.set(2);
+    });
+    assert_eq!(elem.inner_html(), "<span>0</span><span>1</span>");
+
+    ctx.with(|| {
+        count.set(0);
+    });
+    assert_eq!(elem.inner_html(), "<!---->");
+}
+
+#[wasm_bindgen_test]
+async fn test_signal_vec_view_fragment_items() {
+    let mvec = MutableVec::<&'static str>::new_with_values(vec!["a", "b"]);
+
+    let sig = mvec.signal_vec_cloned();
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_vec_view_fragment_items")
+            .signal_vec(sig, |x| Fragment {
+                items: vec![span().and(*x).into(), View::Empty, div().and(*x).into()],
+            })
+    });
+
+    let elem = elem_by_id("test_signal_vec_view_fragment_items");
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><div>a</div><span>b</span><div>b</div><!---->"
+    );
+
+    ctx.with(|| {
+        mvec.lock_mut().insert(1, "x");
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>a</span><div>a</div><span>x</span><div>x</div><span>b</span><div>b</div><!---->"
+    );
+
+    ctx.with(|| {
+        mvec.lock_mut().set(0, "A");
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>A</span><div>A</div><span>x</span><div>x</div><span>b</span><div>b</div><!---->"
+    );
+
+    ctx.with(|| {
+        mvec.lock_mut().remove(1);
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>A</span><div>A</div><span>b</span><div>b</div><!---->"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_signal_nested_fragment() {
+    let value = Mutable::new("a");
+
+    let sig = value.signal().map(|v| -> View {
+        if v.is_empty() {
+            View::Empty
+        } else {
+            let inner = Fragment {
+                items: vec![div().and("1").into(), span().and(v).into()],
+            };
+            Fragment {
+                items: vec![inner.into(), div().and("2").into()],
+            }
+            .into()
+        }
+    });
+
+    let ctx = brass::launch(get_root(), || {
+        div()
+            .attr(Attr::Id, "test_signal_nested_fragment")
+            .and(span().and("start"))
+            .signal(sig)
+    });
+
+    let elem = elem_by_id("test_signal_nested_fragment");
+    assert_eq!(
+        elem.inner_html(),
+        "<span>start</span><div>1</div><span>a</span><div>2</div>"
+    );
+
+    ctx.with(|| {
+        value.set("b");
+    });
+    assert_eq!(
+        elem.inner_html(),
+        "<span>start</span><div>1</div><span>b</span><div>2</div>"
+    );
+
+    ctx.with(|| {
+        value.set("");
+    });
+    assert_eq!(elem.inner_html(), "<span>start</span><!---->");
+}
+
+#[wasm_bindgen_test]
+fn test_view_multiple_roots() {
+    let there = "there";
+
+    brass::launch(get_root(), || {
+        div().attr(Attr::Id, "test_view_multiple_roots").and(view! {
+            "hello"
+            span [ {there} ]
+            {div().and("!")}
+        })
+    });
+
+    let html = elem_by_id("test_view_multiple_roots").inner_html();
+    assert_eq!(html, "hello<span>there</span><div>!</div>");
+}
+
 #[wasm_bindgen_test]
 async fn test_timeout() {
     let mut btn = None;
@@ -233,6 +606,505 @@ fn test_view() {
     );
 }
 
+#[wasm_bindgen_test]
+fn test_typed_input() {
+    let text = Mutable::new("a".to_string());
+    let mut elem = None;
+
+    let ctx = brass::launch(get_root(), || {
+        input()
+            .value_signal(text.signal_cloned())
+            .with_ref(&mut elem)
+    });
+    let elem: web_sys::HtmlInputElement = elem.unwrap();
+    assert_eq!(elem.value(), "a");
+
+    // The value property is updated even after the user changed it.
+    elem.set_value("typed");
+    ctx.with(|| text.set("b".to_string()));
+    assert_eq!(elem.value(), "b");
+}
+
+#[wasm_bindgen_test]
+fn test_properties() {
+    let checked = Mutable::new(true);
+    let mut elem = None;
+
+    let ctx = brass::launch(get_root(), || {
+        input()
+            .attr(Attr::Type, "checkbox")
+            .prop_signal(Prop::Checked, checked.signal())
+            .prop(Prop::Indeterminate, true)
+            .with_ref(&mut elem)
+    });
+    let elem: web_sys::HtmlInputElement = elem.unwrap();
+    assert!(elem.checked());
+    assert!(elem.indeterminate());
+    // Properties are not reflected as attributes.
+    assert!(!elem.has_attribute("checked"));
+
+    elem.set_checked(false);
+    ctx.with(|| checked.set(false));
+    ctx.with(|| checked.set(true));
+    assert!(elem.checked());
+}
+
+#[wasm_bindgen_test]
+fn test_node_ref() {
+    let node_ref = NodeRef::new();
+
+    let _ctx = brass::launch(get_root(), || {
+        let target = node_ref.clone();
+        div()
+            .tag(input().value("a").node_ref(&node_ref))
+            .tag(button().on(move |_: ClickEvent| {
+                target.get().unwrap().focus().unwrap();
+            }))
+    });
+    let elem: web_sys::HtmlInputElement = node_ref.get().unwrap();
+    assert_eq!(elem.value(), "a");
+
+    let btn = get_root().query_selector("button").unwrap().unwrap();
+    btn.unchecked_into::<web_sys::HtmlElement>().click();
+    let active = brass::web::window().document().unwrap().active_element();
+    assert_eq!(active, Some(elem.into()));
+}
+
+#[wasm_bindgen_test]
+fn test_node_ref_wrong_type() {
+    let node_ref = NodeRef::<web_sys::HtmlInputElement>::new();
+
+    let _ctx = brass::launch(get_root(), || {
+        div()
+            .cast::<web_sys::HtmlInputElement>()
+            .node_ref(&node_ref)
+    });
+    // The element is set, but it isn't an input.
+    assert!(node_ref.is_set());
+    assert_eq!(node_ref.get(), None);
+}
+
+#[wasm_bindgen_test]
+fn test_delegated_events() {
+    let log = Mutable::new(Vec::<String>::new());
+    let stop = Mutable::new(false);
+    let show = Mutable::new(true);
+
+    let ctx = brass::launch_delegated(get_root(), || {
+        let outer = log.clone();
+        let focus = log.clone();
+        let log = log.clone();
+        let stop = stop.clone();
+        div()
+            .on_event(Ev::Click, move |ev| {
+                let target = ev.current_target().unwrap();
+                let tag = target.unchecked_ref::<web_sys::Element>().tag_name();
+                outer.lock_mut().push(format!("outer {tag}"));
+            })
+            .signal(show.signal().map(move |show| {
+                let log = log.clone();
+                let stop = stop.clone();
+                if show {
+                    button()
+                        .on_event(Ev::Click, move |ev| {
+                            log.lock_mut().push("inner".to_string());
+                            if stop.get() {
+                                ev.stop_propagation();
+                            }
+                        })
+                        .into()
+                } else {
+                    View::Empty
+                }
+            }))
+            .tag(input().on_event(Ev::Focus, move |_| {
+                focus.lock_mut().push("focus".to_string());
+            }))
+    });
+
+    let root = get_root();
+    let click = || {
+        root.query_selector("button")
+            .unwrap()
+            .unwrap()
+            .unchecked_into::<web_sys::HtmlElement>()
+            .click()
+    };
+
+    click();
+    assert_eq!(log.get_cloned(), ["inner", "outer DIV"]);
+
+    stop.set(true);
+    click();
+    assert_eq!(log.get_cloned(), ["inner", "outer DIV", "inner"]);
+
+    // Events that don't bubble get direct listeners.
+    root.query_selector("input")
+        .unwrap()
+        .unwrap()
+        .unchecked_into::<web_sys::HtmlElement>()
+        .focus()
+        .unwrap();
+    assert_eq!(log.lock_ref().last().unwrap(), "focus");
+
+    // Handlers of removed elements are unregistered.
+    let btn = root.query_selector("button").unwrap().unwrap();
+    ctx.with(|| show.set(false));
+    log.lock_mut().clear();
+    root.append_child(&btn).unwrap();
+    btn.unchecked_into::<web_sys::HtmlElement>().click();
+    assert!(log.lock_ref().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_event_options() {
+    let log = Mutable::new(Vec::<&'static str>::new());
+
+    let _ctx = brass::launch_delegated(get_root(), || {
+        let push = |entry: &'static str| {
+            let log = log.clone();
+            move |_| log.lock_mut().push(entry)
+        };
+        div()
+            .on_event(Ev::Click, push("outer"))
+            .on_event_with_options(Ev::Click, EventOptions::new().capture(), push("capture"))
+            .tag(
+                button()
+                    .on_event(Ev::Click, push("button"))
+                    .on_event_with_options(Ev::Click, EventOptions::new().once(), push("once")),
+            )
+            .tag(input().attr(Attr::Type, "checkbox").on_event_with_options(
+                Ev::Click,
+                EventOptions::new().prevent_default().stop_propagation(),
+                push("checkbox"),
+            ))
+    });
+
+    let root = get_root();
+    let click = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into::<web_sys::HtmlElement>()
+            .click()
+    };
+
+    click("button");
+    assert_eq!(log.get_cloned(), ["capture", "button", "once", "outer"]);
+
+    log.lock_mut().clear();
+    click("button");
+    assert_eq!(log.get_cloned(), ["capture", "button", "outer"]);
+
+    // The default action of the click is prevented, and it doesn't bubble.
+    log.lock_mut().clear();
+    click("input");
+    assert_eq!(log.get_cloned(), ["capture", "checkbox"]);
+    let checkbox = root
+        .query_selector("input")
+        .unwrap()
+        .unwrap()
+        .unchecked_into::<web_sys::HtmlInputElement>();
+    assert!(!checkbox.checked());
+}
+
+#[wasm_bindgen_test]
+fn test_typed_events() {
+    let log = Mutable::new(Vec::<String>::new());
+
+    let _ctx = brass::launch(get_root(), || {
+        let keys = log.clone();
+        let submits = log.clone();
+        let transitions = log.clone();
+        form()
+            .on_with_options(
+                EventOptions::new().prevent_default(),
+                move |ev: SubmitEvent| {
+                    submits
+                        .lock_mut()
+                        .push(format!("submit {}", ev.default_prevented()));
+                },
+            )
+            .on(move |_: TransitionStartEvent| {
+                transitions.lock_mut().push("transition".to_string());
+            })
+            .tag(input().on(move |ev: KeyUpEvent| {
+                let entry = match ev.named_key() {
+                    Some(Key::Enter) => "enter".to_string(),
+                    Some(key) => key.as_str().to_string(),
+                    None => ev.key(),
+                };
+                keys.lock_mut().push(entry);
+            }))
+    });
+
+    let root = get_root();
+    let dispatch = |selector: &str, event: &str| {
+        let event = js_sys::eval(event)
+            .unwrap()
+            .unchecked_into::<web_sys::Event>();
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .dispatch_event(&event)
+            .unwrap();
+    };
+
+    dispatch(
+        "input",
+        "new KeyboardEvent('keyup', { key: 'Enter', bubbles: true })",
+    );
+    dispatch(
+        "input",
+        "new KeyboardEvent('keyup', { key: 'x', bubbles: true })",
+    );
+    dispatch("form", "new SubmitEvent('submit', { cancelable: true })");
+    dispatch("form", "new TransitionEvent('transitionstart')");
+    assert_eq!(
+        log.get_cloned(),
+        ["enter", "x", "submit true", "transition"]
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_portal() {
+    let show = Mutable::new(true);
+    let clicks = Mutable::new(0);
+
+    let root = get_root();
+    let ctx = brass::launch_delegated(root.clone(), || {
+        let clicks = clicks.clone();
+        div().signal(show.signal().map(move |show| {
+            let clicks = clicks.clone();
+            if show {
+                Portal::body(move || {
+                    let inner = clicks.clone();
+                    button()
+                        .attr(Attr::Id, "test-portal")
+                        .on_event(Ev::Click, move |_| *inner.lock_mut() += 1)
+                })
+                .into()
+            } else {
+                View::Empty
+            }
+        }))
+    });
+
+    let doc = brass::web::window().document().unwrap();
+    let btn = doc.get_element_by_id("test-portal").unwrap();
+    assert_eq!(btn.parent_element(), doc.body().map(Into::into));
+    assert!(root.query_selector("button").unwrap().is_none());
+
+    // Delegated handlers of the content are invoked.
+    btn.unchecked_ref::<web_sys::HtmlElement>().click();
+    assert_eq!(clicks.get(), 1);
+
+    ctx.with(|| show.set(false));
+    assert!(doc.get_element_by_id("test-portal").is_none());
+}
+
+#[wasm_bindgen_test]
+async fn test_transition_classes() {
+    let items = MutableVec::new_with_values(vec![1]);
+
+    let root = get_root();
+    let ctx = brass::launch(root.clone(), || {
+        div().signal_vec_keyed_with_transition(
+            items.signal_vec(),
+            |item| *item,
+            |item| p().text(item.to_string()),
+            Transition::new("fade"),
+        )
+    });
+
+    ctx.with(|| items.lock_mut().push(2));
+    let entering = root.query_selector("p:last-of-type").unwrap().unwrap();
+    assert_eq!(entering.class_name(), "fade-enter-from fade-enter-active");
+
+    // The classes change once the browser rendered them.
+    TimeoutFuture::new(Duration::from_millis(100)).await;
+    assert_eq!(entering.class_name(), "fade-enter-active fade-enter-to");
+    let end = web_sys::Event::new("transitionend").unwrap();
+    entering.dispatch_event(&end).unwrap();
+    TimeoutFuture::new(Duration::from_millis(10)).await;
+    assert_eq!(entering.class_name(), "");
+
+    // Removed elements stay until their transition ended.
+    ctx.with(|| items.lock_mut().remove(1));
+    assert!(entering.is_connected());
+    TimeoutFuture::new(Duration::from_millis(100)).await;
+    assert_eq!(entering.class_name(), "fade-leave-active fade-leave-to");
+    entering.dispatch_event(&end).unwrap();
+    TimeoutFuture::new(Duration::from_millis(10)).await;
+    assert!(!entering.is_connected());
+}
+
+#[wasm_bindgen_test]
+async fn test_transition_timeout() {
+    let items = MutableVec::new_with_values(vec![1, 2]);
+
+    let root = get_root();
+    let ctx = brass::launch(root.clone(), || {
+        div().signal_vec_keyed_with_transition(
+            items.signal_vec(),
+            |item| *item,
+            |item| p().text(item.to_string()),
+            Transition::new("fade").timeout(Duration::from_millis(50)),
+        )
+    });
+
+    ctx.with(|| items.lock_mut().remove(0));
+    let leaving = root.query_selector("p").unwrap().unwrap();
+    assert!(leaving.class_list().contains("fade-leave-active"));
+
+    // Without a CSS transition the element is removed after the timeout.
+    TimeoutFuture::new(Duration::from_millis(200)).await;
+    assert!(!leaving.is_connected());
+    assert_eq!(root.inner_html(), "<div><p>2</p><!----></div>");
+}
+
+/// Renders its count and an input, which keeps the focus across renders.
+struct FocusForm {
+    count: u32,
+}
+
+impl Component for FocusForm {
+    type Properties = Mutable<Option<Handle<Self>>>;
+
+    const RERENDER: bool = true;
+
+    fn init(handle: Self::Properties, ctx: Context<'_, Self>) -> Self {
+        handle.set(Some(ctx.handle()));
+        Self { count: 0 }
+    }
+
+    fn render(&mut self, _ctx: Context<'_, Self>) -> TagBuilder {
+        div().and(p().text(self.count.to_string())).and(input())
+    }
+}
+
+#[wasm_bindgen_test]
+async fn test_component_render_keeps_focus() {
+    let handle = Mutable::new(None);
+    let root = get_root();
+    let _ctx = brass::launch(root.clone(), {
+        let handle = handle.clone();
+        move || FocusForm::build(handle)
+    });
+    let input = root.query_selector("input").unwrap().unwrap();
+    input
+        .unchecked_ref::<web_sys::HtmlElement>()
+        .focus()
+        .unwrap();
+
+    let handle = handle.get_cloned().unwrap();
+    handle.update(|form, _| {
+        form.count += 1;
+        true
+    });
+    let p = root.query_selector("p").unwrap().unwrap();
+    assert_eq!(p.text_content().unwrap(), "1");
+
+    // The new input is focused instead of the one that was replaced.
+    let new_input = root.query_selector("input").unwrap().unwrap();
+    assert!(!input.is_connected());
+    let doc = brass::web::window().document().unwrap();
+    assert_eq!(doc.active_element(), Some(new_input));
+}
+
+#[wasm_bindgen_test]
+async fn test_router_hash() {
+    let root = get_root();
+    let ctx = brass::launch(root.clone(), || {
+        router::init(RouterMode::Hash);
+        div()
+            .and(router::link_path("/about").text("About"))
+            .signal(router::path().map(|path| p().text(path)))
+    });
+    let link = root.query_selector("a").unwrap().unwrap();
+    assert_eq!(link.get_attribute("href").unwrap(), "#/about");
+
+    link.unchecked_ref::<web_sys::HtmlElement>().click();
+    assert_eq!(brass::web::window().location().hash().unwrap(), "#/about");
+    assert_eq!(
+        root.query_selector("p")
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap(),
+        "/about"
+    );
+
+    // Changes of the fragment outside of the router are picked up.
+    brass::web::window().location().set_hash("/other").unwrap();
+    TimeoutFuture::new(Duration::from_millis(50)).await;
+    assert_eq!(ctx.with(router::current_path), "/other");
+    ctx.with(|| router::replace_path("/"));
+}
+
+#[wasm_bindgen_test]
+async fn test_hydrate() {
+    let count = Mutable::new(0);
+    let items = MutableVec::new_with_values(vec![1, 2]);
+
+    let render = {
+        let count = count.clone();
+        let items = items.clone();
+        move || {
+            let count2 = count.clone();
+            div()
+                .attr(Attr::Id, "test-hydrate")
+                .text("Count: ")
+                .text_signal(count.signal().map(|c| c.to_string()))
+                .tag(button().on(move |_: ClickEvent| {
+                    *count2.lock_mut() += 1;
+                }))
+                .signal(count.signal().map(|c| span().text(c.to_string())))
+                .signal_vec(items.signal_vec(), |x| p().text(x.to_string()))
+        }
+    };
+
+    let root = get_root();
+    root.set_inner_html(&brass::ssr::render_to_string(&render));
+    let server_btn = nth_child(&root.first_element_child().unwrap(), 2);
+
+    let ctx = brass::hydrate(root.clone(), render);
+
+    let elem = elem_by_id("test-hydrate");
+    assert_eq!(
+        elem.inner_html(),
+        "Count: 0<button></button><span>0</span><p>1</p><p>2</p><!---->"
+    );
+    // The server rendered button was adopted.
+    let btn = nth_child(&elem, 2);
+    assert!(btn.is_same_node(Some(&server_btn)));
+
+    btn.unchecked_ref::<web_sys::HtmlElement>().click();
+    tick().await;
+    assert_eq!(
+        elem.inner_html(),
+        "Count: 1<button></button><span>1</span><p>1</p><p>2</p><!---->"
+    );
+
+    ctx.with(|| items.lock_mut().push(3));
+    assert_eq!(
+        elem.inner_html(),
+        "Count: 1<button></button><span>1</span><p>1</p><p>2</p><p>3</p><!---->"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_hydrate_mismatch() {
+    let root = get_root();
+    root.set_inner_html("<div><span>server</span></div><p>extra</p>");
+    let server_div = root.first_child().unwrap();
+
+    brass::hydrate(root.clone(), || div().tag(p().text("client")));
+
+    assert_eq!(root.inner_html(), "<div><p>client</p></div>");
+    assert!(!root.first_child().unwrap().is_same_node(Some(&server_div)));
+}
+
 // // Refs.
 
 // struct RefComponent {
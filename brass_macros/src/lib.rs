@@ -1,5 +1,6 @@
 use proc_macro::TokenStream;
 
+mod route;
 mod view;
 
 /// Construct dom nodes with a convenient helper syntax.
@@ -7,3 +8,13 @@ mod view;
 pub fn view(tokens: TokenStream) -> TokenStream {
     view::view(tokens)
 }
+
+/// Implement `brass::router::Route` for an enum.
+///
+/// Every variant needs a `#[route("/path/:param")]` attribute, and one
+/// field can be marked `#[nested]` to hold a nested route. See
+/// `brass::router::Route` for details.
+#[proc_macro_derive(Route, attributes(route, nested))]
+pub fn derive_route(tokens: TokenStream) -> TokenStream {
+    route::derive_route(tokens)
+}
@@ -0,0 +1,401 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+pub fn derive_route(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
+    match expand(input) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// A variant with its `#[route("...")]` pattern.
+struct RouteVariant {
+    ident: syn::Ident,
+    pattern: syn::LitStr,
+    /// The parameter names, in the order of the pattern.
+    params: Vec<String>,
+    fields: syn::Fields,
+    /// What each field holds, in the order of the fields.
+    kinds: Vec<FieldKind>,
+}
+
+enum FieldKind {
+    /// The parameter with the given name.
+    Param(String),
+    /// The nested route, marked with `#[nested]`.
+    Nested,
+}
+
+impl RouteVariant {
+    fn nested(&self) -> Option<usize> {
+        self.kinds
+            .iter()
+            .position(|kind| matches!(kind, FieldKind::Nested))
+    }
+
+    /// The index of the field of a parameter.
+    fn param_field(&self, param: &str) -> usize {
+        self.kinds
+            .iter()
+            .position(|kind| matches!(kind, FieldKind::Param(name) if name == param))
+            .unwrap()
+    }
+
+    /// The variant with the fields selected by `bind` bound to variables
+    /// starting with `prefix`, as pattern or constructor.
+    fn tokens(&self, prefix: &str, bind: impl Fn(usize) -> bool) -> TokenStream2 {
+        let ident = &self.ident;
+        let values = (0..self.kinds.len()).map(|index| {
+            if bind(index) {
+                let var = format_ident!("{}{}", prefix, index);
+                quote!(#var)
+            } else {
+                quote!(_)
+            }
+        });
+        match &self.fields {
+            syn::Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
+                quote!(Self::#ident { #(#names: #values),* })
+            }
+            syn::Fields::Unnamed(_) => quote!(Self::#ident(#(#values),*)),
+            syn::Fields::Unit => quote!(Self::#ident),
+        }
+    }
+}
+
+/// The parameter names of a pattern like `/users/:id/*rest`.
+fn pattern_params(pattern: &str) -> Result<Vec<String>, String> {
+    let segments: Vec<_> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let mut params = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let name = if let Some(name) = segment.strip_prefix(':') {
+            name
+        } else if let Some(name) = segment.strip_prefix('*') {
+            if index != segments.len() - 1 {
+                return Err(format!("`{segment}` must be the last segment"));
+            }
+            name
+        } else {
+            continue;
+        };
+        if name.is_empty() {
+            return Err(format!("missing parameter name in `{segment}`"));
+        }
+        if params.iter().any(|p| p == name) {
+            return Err(format!("duplicate parameter `{name}`"));
+        }
+        params.push(name.to_string());
+    }
+    Ok(params)
+}
+
+fn parse_variant(variant: &syn::Variant) -> syn::Result<RouteVariant> {
+    let attr = variant
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("route"))
+        .ok_or_else(|| syn::Error::new_spanned(variant, "missing #[route(\"/path\")] attribute"))?;
+    let pattern: syn::LitStr = attr.parse_args()?;
+    if !pattern.value().starts_with('/') {
+        return Err(syn::Error::new_spanned(
+            &pattern,
+            "route patterns must start with `/`",
+        ));
+    }
+    let params =
+        pattern_params(&pattern.value()).map_err(|msg| syn::Error::new_spanned(&pattern, msg))?;
+
+    let is_nested = |field: &syn::Field| field.attrs.iter().any(|a| a.path.is_ident("nested"));
+    let nested: Vec<_> = variant.fields.iter().filter(|f| is_nested(f)).collect();
+    if let Some(field) = nested.get(1) {
+        return Err(syn::Error::new_spanned(
+            field,
+            "only one field can be #[nested]",
+        ));
+    }
+    if !nested.is_empty() && pattern.value().contains("/*") {
+        return Err(syn::Error::new_spanned(
+            &pattern,
+            "routes with a nested route can't have a `*` parameter",
+        ));
+    }
+
+    let mut kinds = Vec::new();
+    match &variant.fields {
+        syn::Fields::Unit if params.is_empty() => {}
+        syn::Fields::Unit => {
+            return Err(syn::Error::new_spanned(
+                &pattern,
+                "the pattern has parameters, but the variant has no fields",
+            ));
+        }
+        syn::Fields::Named(fields) => {
+            for field in &fields.named {
+                if is_nested(field) {
+                    kinds.push(FieldKind::Nested);
+                    continue;
+                }
+                let name = field.ident.as_ref().unwrap().to_string();
+                if !params.contains(&name) {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        format!("the pattern has no parameter `{name}`"),
+                    ));
+                }
+                kinds.push(FieldKind::Param(name));
+            }
+            if let Some(param) = params.iter().find(|p| {
+                !kinds
+                    .iter()
+                    .any(|k| matches!(k, FieldKind::Param(n) if n == *p))
+            }) {
+                return Err(syn::Error::new_spanned(
+                    &pattern,
+                    format!("the variant has no field `{param}`"),
+                ));
+            }
+        }
+        syn::Fields::Unnamed(fields) => {
+            let count = fields.unnamed.len() - nested.len();
+            if count != params.len() {
+                return Err(syn::Error::new_spanned(
+                    &pattern,
+                    format!(
+                        "the pattern has {} parameters, but the variant has {} fields",
+                        params.len(),
+                        count
+                    ),
+                ));
+            }
+            let mut params = params.iter();
+            for field in &fields.unnamed {
+                if is_nested(field) {
+                    kinds.push(FieldKind::Nested);
+                } else {
+                    kinds.push(FieldKind::Param(params.next().unwrap().clone()));
+                }
+            }
+        }
+    }
+
+    Ok(RouteVariant {
+        ident: variant.ident.clone(),
+        pattern,
+        params,
+        fields: variant.fields.clone(),
+        kinds,
+    })
+}
+
+fn expand(input: syn::DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        syn::Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Route can only be derived for enums",
+            ))
+        }
+    };
+    let variants = data
+        .variants
+        .iter()
+        .map(parse_variant)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let from_path = variants.iter().map(from_path_arm);
+    let to_path = variants.iter().map(to_path_arm);
+
+    // Only routes with nested routes need more than the defaults.
+    let nested: Vec<_> = variants.iter().filter(|v| v.nested().is_some()).collect();
+    let nested_methods = if nested.is_empty() {
+        quote!()
+    } else {
+        let nested_path = nested.iter().map(|variant| nested_path_arm(variant));
+        let same_layout = nested.iter().map(|variant| same_layout_arm(variant));
+        quote! {
+            fn nested_path(&self) -> ::std::option::Option<::std::string::String> {
+                match self {
+                    #(#nested_path)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn same_layout(&self, other: &Self) -> bool {
+                match (self, other) {
+                    #(#same_layout)*
+                    _ => self == other,
+                }
+            }
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::brass::router::Route for #name #ty_generics #where_clause {
+            fn from_path(path: &str) -> ::std::option::Option<Self> {
+                #(#from_path)*
+                ::std::option::Option::None
+            }
+
+            fn to_path(&self) -> ::std::string::String {
+                match self {
+                    #(#to_path)*
+                }
+            }
+
+            #nested_methods
+        }
+    })
+}
+
+fn from_path_arm(variant: &RouteVariant) -> TokenStream2 {
+    let pattern = &variant.pattern;
+    let fields: Vec<_> = variant.fields.iter().collect();
+    if fields.is_empty() {
+        let value = variant.tokens("f", |_| true);
+        return quote! {
+            if ::brass::router::match_path(#pattern, path).is_some() {
+                return ::std::option::Option::Some(#value);
+            }
+        };
+    }
+
+    let vars: Vec<_> = (0..fields.len()).map(|i| format_ident!("f{}", i)).collect();
+    let parsed = fields.iter().zip(&variant.kinds).map(|(field, kind)| {
+        let ty = &field.ty;
+        match kind {
+            FieldKind::Param(param) => quote!(params.parse::<#ty>(#param)),
+            FieldKind::Nested => quote!(<#ty as ::brass::router::Route>::from_path(&rest)),
+        }
+    });
+    let params = if variant.params.is_empty() {
+        quote!(_)
+    } else {
+        quote!(params)
+    };
+    let matched = if variant.nested().is_some() {
+        quote!(::std::option::Option::Some((#params, rest)) = ::brass::router::match_prefix(#pattern, path))
+    } else {
+        quote!(::std::option::Option::Some(#params) = ::brass::router::match_path(#pattern, path))
+    };
+    let value = variant.tokens("f", |_| true);
+    quote! {
+        if let #matched {
+            if let (#(::std::option::Option::Some(#vars),)*) = (#(#parsed,)*) {
+                return ::std::option::Option::Some(#value);
+            }
+        }
+    }
+}
+
+fn to_path_arm(variant: &RouteVariant) -> TokenStream2 {
+    let pattern = &variant.pattern;
+    let binding = variant.tokens("f", |_| true);
+    let values = variant
+        .params
+        .iter()
+        .map(|param| format_ident!("f{}", variant.param_field(param)));
+    let path = quote! {
+        ::brass::router::format_path(
+            #pattern,
+            &[#(#values as &dyn ::std::fmt::Display),*],
+        )
+    };
+    match variant.nested() {
+        Some(index) => {
+            let nested = format_ident!("f{}", index);
+            quote! {
+                #binding => ::brass::router::join_path(
+                    &#path,
+                    &::brass::router::Route::to_path(#nested),
+                ),
+            }
+        }
+        None => quote!(#binding => #path,),
+    }
+}
+
+fn nested_path_arm(variant: &RouteVariant) -> TokenStream2 {
+    let index = variant.nested().unwrap();
+    let binding = variant.tokens("f", |i| i == index);
+    let nested = format_ident!("f{}", index);
+    quote! {
+        #binding => ::std::option::Option::Some(::brass::router::Route::to_path(#nested)),
+    }
+}
+
+fn same_layout_arm(variant: &RouteVariant) -> TokenStream2 {
+    let index = variant.nested().unwrap();
+    let left = variant.tokens("a", |i| i != index);
+    let right = variant.tokens("b", |i| i != index);
+    let compare = (0..variant.kinds.len()).filter(|i| *i != index).map(|i| {
+        let (a, b) = (format_ident!("a{}", i), format_ident!("b{}", i));
+        quote!(#a == #b)
+    });
+    quote! {
+        (#left, #right) => true #(&& #compare)*,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn test_pattern_params() {
+        assert_eq!(pattern_params("/").unwrap(), Vec::<String>::new());
+        assert_eq!(
+            pattern_params("/users/:id/files/*path").unwrap(),
+            vec!["id", "path"]
+        );
+        assert!(pattern_params("/*path/edit").is_err());
+        assert!(pattern_params("/:id/:id").is_err());
+        assert!(pattern_params("/users/:").is_err());
+    }
+
+    #[test]
+    fn test_parse_variant_fields() {
+        let input: syn::DeriveInput = syn::parse2(quote! {
+            enum R {
+                #[route("/users/:id")]
+                User { id: u32, name: String },
+            }
+        })
+        .unwrap();
+        let err = expand(input).unwrap_err();
+        assert_eq!(err.to_string(), "the pattern has no parameter `name`");
+
+        let input: syn::DeriveInput = syn::parse2(quote! {
+            enum R {
+                #[route("/users/:id/:tab")]
+                User(u32),
+            }
+        })
+        .unwrap();
+        let err = expand(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the pattern has 2 parameters, but the variant has 1 fields"
+        );
+
+        let input: syn::DeriveInput = syn::parse2(quote! {
+            enum R {
+                #[route("/files/*path")]
+                Files(String, #[nested] Sub),
+            }
+        })
+        .unwrap();
+        let err = expand(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "routes with a nested route can't have a `*` parameter"
+        );
+    }
+}
@@ -13,6 +13,8 @@ enum Node {
     Elem {
         tag: syn::Ident,
         attributes: Vec<Attr>,
+        properties: Vec<Property>,
+        bindings: Vec<Binding>,
         event_handlers: Vec<EventHandler>,
         children: Vec<Self>,
     },
@@ -27,23 +29,71 @@ enum Node {
     },
 }
 
+/// An event handler, written as `onevent=handler` or with modifiers as
+/// `onevent|prevent|stop=handler`.
 #[derive(Debug)]
 struct EventHandler {
     event: syn::Ident,
+    /// The `brass::dom::EventOptions` methods of the modifiers.
+    modifiers: Vec<syn::Ident>,
     handler: syn::Expr,
 }
 
+/// The DOM event name of a handler like `onkeydown`, which is resolved with
+/// `brass::dom::Ev::parse`.
+fn event_name(event: &syn::Ident) -> String {
+    event.to_string()[2..].to_lowercase()
+}
+
+/// Parse the `|modifier` list of an event handler.
+fn parse_event_modifiers(stream: syn::parse::ParseStream) -> syn::Result<Vec<syn::Ident>> {
+    let mut modifiers = Vec::new();
+    while stream.peek(syn::Token![|]) {
+        stream.parse::<syn::Token![|]>()?;
+        let modifier: syn::Ident = stream.parse()?;
+        let method = match modifier.to_string().as_str() {
+            "prevent" => "prevent_default",
+            "stop" => "stop_propagation",
+            "capture" => "capture",
+            "passive" => "passive",
+            "once" => "once",
+            _ => {
+                return Err(syn::parse::Error::new_spanned(
+                    modifier,
+                    "Unknown event modifier, expected one of prevent, stop, capture, passive, once",
+                ));
+            }
+        };
+        modifiers.push(syn::Ident::new(method, modifier.span()));
+    }
+    Ok(modifiers)
+}
+
 #[derive(Debug)]
 struct Attr {
     name: syn::Ident,
     value: AttrValue,
 }
 
+/// A DOM property, written as `prop:name=value`.
+#[derive(Debug)]
+struct Property {
+    name: syn::Ident,
+    value: syn::Expr,
+}
+
+/// A two-way binding, written as `bind:name=state`.
+#[derive(Debug)]
+struct Binding {
+    name: syn::Ident,
+    state: syn::Expr,
+}
+
 #[derive(Debug)]
 enum AttrValue {
     None,
     Str(syn::LitStr),
-    Expr(syn::Expr),
+    Expr(Box<syn::Expr>),
 }
 
 struct Template {
@@ -58,6 +108,8 @@ fn render_node(node: Node, nested: bool) -> proc_macro2::TokenStream {
         Node::Elem {
             tag,
             attributes,
+            properties,
+            bindings,
             event_handlers,
             children,
         } => {
@@ -68,7 +120,7 @@ fn render_node(node: Node, nested: bool) -> proc_macro2::TokenStream {
                 match attr.value {
                 AttrValue::None => {
                     quote! {
-                        parent.add_attr(brass::dom::Attr::#name, brass::web::empty_string());
+                        parent.add_attr_toggle(brass::dom::Attr::#name);
                     }
                 }
                 AttrValue::Str(value) => {
@@ -86,19 +138,39 @@ fn render_node(node: Node, nested: bool) -> proc_macro2::TokenStream {
             });
 
             let event_handlers = event_handlers.into_iter().map(|e| {
-
-                let name = &e.event.to_string()[2..];
-                let ev_ident = syn::Ident::new(name, e.event.span());
+                let name = event_name(&e.event);
+                let unknown = format!("unknown event `{name}`");
                 let handler = e.handler;
+                let modifiers = e.modifiers;
 
-                quote!{
-                    brass::dom::EventHandlerApply::event_handler_apply(#handler, brass::dom::Ev::#ev_ident, &mut parent);
+                quote! {
+                    brass::dom::EventHandlerApply::event_handler_apply(
+                        #handler,
+                        brass::dom::Ev::parse(#name).expect(#unknown),
+                        brass::dom::EventOptions::new() #( .#modifiers() )*,
+                        &mut parent,
+                    );
                     // parent.add_event_listener_cast(
                     //     brass::dom::Event::#ev_ident,
                     //     #handler
                     // );
                 }
+            });
+
+            let properties = properties.into_iter().map(|prop| {
+                let name = prop.name.to_string();
+                let value = prop.value;
+                quote! {
+                    brass::dom::PropValueApply::prop_apply(#value, brass::dom::PropName::Custom(#name), &mut parent);
+                }
+            });
 
+            let bindings = bindings.into_iter().map(|binding| {
+                let method = quote::format_ident!("add_bind_{}", binding.name);
+                let state = binding.state;
+                quote! {
+                    parent.#method(&#state);
+                }
             });
 
             let children = children.into_iter().map(|c| render_node(c, true));
@@ -107,12 +179,35 @@ fn render_node(node: Node, nested: bool) -> proc_macro2::TokenStream {
             tag_name.replace_range(0..1, &tag_name[0..1].to_uppercase());
             let tag_ident = syn::Ident::new(&tag_name, tag.span());
 
+            // Tags like `a` or `title` exist in multiple namespaces, so the
+            // children of elements that switch namespaces need to know it.
+            let child_namespace = match tag_name.as_str() {
+                "Svg" => Some(quote!(Svg)),
+                "Math" => Some(quote!(MathMl)),
+                "ForeignObject" => Some(quote!(Html)),
+                _ => None,
+            };
+            let children = match child_namespace {
+                Some(namespace) => quote! {
+                    brass::dom::with_namespace(brass::dom::Namespace::#namespace, || {
+                        #(#children)*
+                    });
+                },
+                None => quote! {
+                    #(#children)*
+                },
+            };
+
             let builder = quote! {
                 {
                     let mut parent = brass::dom::TagBuilder::new(brass::dom::Tag::#tag_ident);
                     #(#attrs)*
                     #(#event_handlers)*
-                    #(#children)*
+                    #children
+                    // Properties like the value of a `select` depend on the
+                    // children, so they are set last.
+                    #(#properties)*
+                    #(#bindings)*
                     parent
                 }
             };
@@ -154,9 +249,7 @@ fn render_node(node: Node, nested: bool) -> proc_macro2::TokenStream {
             } else {
                 // TODO: use cached JsString ?
                 quote! {
-                    brass::dom::View::Node(
-                        brass::dom::View::Node(brass::dom::Node::new_text(brass::web::DomStr::Str(#value)))
-                    )
+                    brass::dom::View::Node(brass::dom::Node::new_text(brass::web::DomStr::Str(#value)))
                 }
             }
         }
@@ -166,7 +259,9 @@ fn render_node(node: Node, nested: bool) -> proc_macro2::TokenStream {
                     brass::dom::Apply::apply(#expr, &mut parent);
                 }
             } else {
-                todo!()
+                quote! {
+                    brass::dom::Render::render(#expr)
+                }
             }
         }
     }
@@ -204,6 +299,8 @@ impl syn::parse::Parse for Node {
             let tag = stream.parse()?;
 
             let mut attributes = Vec::<Attr>::new();
+            let mut properties = Vec::<Property>::new();
+            let mut bindings = Vec::<Binding>::new();
             let mut event_handlers = Vec::<EventHandler>::new();
 
             if stream.peek(syn::token::Paren) {
@@ -215,17 +312,39 @@ impl syn::parse::Parse for Node {
 
                     let name_value = name.to_string();
                     if name_value.starts_with("on") {
+                        let modifiers = parse_event_modifiers(&inner)?;
                         inner.parse::<syn::token::Eq>()?;
                         let handler: syn::Expr = inner.parse()?;
 
                         event_handlers.push(EventHandler {
                             event: name,
+                            modifiers,
                             handler,
                         });
 
                         continue;
                     }
 
+                    if (name_value == "prop" || name_value == "bind")
+                        && inner.peek(syn::token::Colon)
+                    {
+                        inner.parse::<syn::token::Colon>()?;
+                        let name: syn::Ident = inner.parse()?;
+                        inner.parse::<syn::token::Eq>()?;
+                        let value: syn::Expr = inner.parse()?;
+
+                        if name_value == "prop" {
+                            properties.push(Property { name, value });
+                        } else {
+                            bindings.push(Binding { name, state: value });
+                        }
+
+                        if inner.peek(syn::token::Comma) {
+                            inner.parse::<syn::token::Comma>()?;
+                        }
+                        continue;
+                    }
+
                     let value = if inner.peek(syn::token::Eq) {
                         inner.parse::<syn::token::Eq>()?;
 
@@ -242,7 +361,7 @@ impl syn::parse::Parse for Node {
                                 }
                             },
                             other => {
-                                AttrValue::Expr(other)
+                                AttrValue::Expr(Box::new(other))
                                 // return Err(syn::parse::Error::new_spanned(
                                 //     other,
                                 //     "Unexpected input",
@@ -276,6 +395,8 @@ impl syn::parse::Parse for Node {
             Ok(Self::Elem {
                 tag,
                 attributes,
+                properties,
+                bindings,
                 event_handlers,
                 children,
             })
@@ -376,10 +497,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_node_elem_with_props() {
+        let input = quote! {
+            input(name="agree" prop:checked=flag.signal() prop:indeterminate=true bind:value=text)
+        };
+        let node: Node = syn::parse2(input).unwrap();
+        match node {
+            Node::Elem {
+                attributes,
+                properties,
+                bindings,
+                ..
+            } => {
+                assert_eq!(attributes.len(), 1);
+                assert_eq!(attributes[0].name.to_string(), "name");
+
+                assert_eq!(properties.len(), 2);
+                assert_eq!(properties[0].name.to_string(), "checked");
+                assert_eq!(properties[1].name.to_string(), "indeterminate");
+
+                assert_eq!(bindings.len(), 1);
+                assert_eq!(bindings[0].name.to_string(), "value");
+            }
+
+            other => {
+                panic!("Expected element, got {:?}", other)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_node_elem_with_event_modifiers() {
+        let input = quote! {
+            form(onsubmit|prevent|stop=submit onclick=click onkeydown|once=key)
+        };
+        let node: Node = syn::parse2(input).unwrap();
+        match node {
+            Node::Elem { event_handlers, .. } => {
+                assert_eq!(event_handlers.len(), 3);
+                assert_eq!(event_handlers[0].event.to_string(), "onsubmit");
+                assert_eq!(event_name(&event_handlers[2].event), "keydown");
+                let modifiers: Vec<_> = event_handlers[0]
+                    .modifiers
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect();
+                assert_eq!(modifiers, ["prevent_default", "stop_propagation"]);
+                assert!(event_handlers[1].modifiers.is_empty());
+            }
+            other => {
+                panic!("Expected element, got {:?}", other)
+            }
+        }
+
+        let input = quote! {
+            form(onsubmit|later=submit)
+        };
+        assert!(syn::parse2::<Node>(input).is_err());
+    }
+
     #[test]
     fn test_parse_node_with_empty_children() {
         let input = quote! {
-            div {}
+            div []
         };
         let node: Node = syn::parse2(input).unwrap();
         match node {
@@ -396,10 +577,10 @@ mod tests {
     #[test]
     fn test_parse_node_with_children() {
         let input = quote! {
-            div {
+            div [
                 "hello"
-                p { "no" }
-            }
+                p [ "no" ]
+            ]
         };
         let node: Node = syn::parse2(input).unwrap();
         match node {